@@ -8,6 +8,12 @@ use vulcan_core::pixel_sorting::{
     ImageSortingDirection,
     PixelSegmentSortDirection,
     immediate::{ImmediateSegmentSelectionMode, PixelSortOptions, perform_pixel_sort},
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
 };
 
 const SAMPLE_IMAGE_WIDTH: u32 = 512;
@@ -46,6 +52,15 @@ fn generate_input_image(seed: u64) -> RgbaImage {
     image
 }
 
+/// Generates an image with a horizontal grayscale gradient (dark on the left, bright on the right),
+/// meaning every horizontal segment is already sorted in ascending order of luminance.
+fn generate_gradient_image() -> RgbaImage {
+    RgbaImage::from_fn(SAMPLE_IMAGE_WIDTH, SAMPLE_IMAGE_HEIGHT, |x, _| {
+        let value = (x * u8::MAX as u32 / (SAMPLE_IMAGE_WIDTH - 1)) as u8;
+        Rgba([value, value, value, u8::MAX])
+    })
+}
+
 #[derive(Debug, Clone)]
 struct TestImages([RgbaImage; 8]);
 
@@ -95,6 +110,7 @@ fn luminance_range_sort_benchmark(c: &mut Criterion) {
                                 direction: ImageSortingDirection::Horizontal(
                                     PixelSegmentSortDirection::Ascending,
                                 ),
+                                stable_sort: false,
                             },
                         );
                     }
@@ -125,6 +141,7 @@ fn luminance_range_sort_benchmark(c: &mut Criterion) {
                                 direction: ImageSortingDirection::Vertical(
                                     PixelSegmentSortDirection::Ascending,
                                 ),
+                                stable_sort: false,
                             },
                         );
                     }
@@ -162,6 +179,7 @@ fn hue_range_sort_benchmark(c: &mut Criterion) {
                                 direction: ImageSortingDirection::Horizontal(
                                     PixelSegmentSortDirection::Ascending,
                                 ),
+                                stable_sort: false,
                             },
                         );
                     }
@@ -192,6 +210,7 @@ fn hue_range_sort_benchmark(c: &mut Criterion) {
                                 direction: ImageSortingDirection::Vertical(
                                     PixelSegmentSortDirection::Ascending,
                                 ),
+                                stable_sort: false,
                             },
                         );
                     }
@@ -229,6 +248,7 @@ fn saturation_range_sort_benchmark(c: &mut Criterion) {
                                 direction: ImageSortingDirection::Horizontal(
                                     PixelSegmentSortDirection::Ascending,
                                 ),
+                                stable_sort: false,
                             },
                         );
                     }
@@ -259,6 +279,7 @@ fn saturation_range_sort_benchmark(c: &mut Criterion) {
                                 direction: ImageSortingDirection::Vertical(
                                     PixelSegmentSortDirection::Ascending,
                                 ),
+                                stable_sort: false,
                             },
                         );
                     }
@@ -269,13 +290,66 @@ fn saturation_range_sort_benchmark(c: &mut Criterion) {
     );
 }
 
+fn prepared_gradient_sort_benchmark(c: &mut Criterion) {
+    const LUMINANCE_THRESHOLD_LOW: f32 = 0.05;
+    const LUMINANCE_THRESHOLD_HIGH: f32 = 0.95;
+
+    let gradient_image = generate_gradient_image();
+
+    for (direction_name, direction) in [
+        (
+            "ascending, already sorted",
+            PixelSegmentSortDirection::Ascending,
+        ),
+        (
+            "descending",
+            PixelSegmentSortDirection::Descending,
+        ),
+    ] {
+        c.bench_with_input(
+            BenchmarkId::new(
+                format!(
+                    "prepared luminance range sorting of a gradient, horizontal {direction_name} \
+                    (512x512, 9/10 luma range)"
+                ),
+                1,
+            ),
+            &gradient_image,
+            |bencher, input| {
+                bencher.iter_batched(
+                    || input.to_owned(),
+                    |image| {
+                        let prepared_sort = prepare_pixel_sort(
+                            image,
+                            PreparedSegmentSelectionMode::LuminanceRange {
+                                low: LUMINANCE_THRESHOLD_LOW,
+                                high: LUMINANCE_THRESHOLD_HIGH,
+                                invert: false,
+                            },
+                            PreparedSegmentSortingMode::Luminance,
+                            None,
+                            ImageSortingDirection::Horizontal(direction),
+                            false,
+                        )
+                        .expect("selection mode should be valid");
+
+                        execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+                    },
+                    BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+}
+
 criterion_group! {
     name = benches;
     config =
         Criterion::default()
             .measurement_time(Duration::from_secs(10))
             .sample_size(200);
-    targets = luminance_range_sort_benchmark, hue_range_sort_benchmark, saturation_range_sort_benchmark
+    targets = luminance_range_sort_benchmark, hue_range_sort_benchmark, saturation_range_sort_benchmark,
+        prepared_gradient_sort_benchmark
 }
 
 criterion_main!(benches);
@@ -0,0 +1,244 @@
+use image::{GenericImage, RgbaImage};
+use rayon::prelude::ParallelIterator;
+use thiserror::Error;
+
+
+#[derive(Debug, Error)]
+pub enum AdjustmentError {
+    #[error(
+        "invalid channel mapping: index {channel_index} at position {position} \
+        is not a valid RGBA channel index (0..=3)"
+    )]
+    InvalidChannelMapping {
+        position: usize,
+        channel_index: usize,
+    },
+}
+
+
+/// The axis along which an image is extended by [`mirror_tile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// The mirrored copy is placed to the right of the image, doubling its width.
+    Horizontal,
+
+    /// The mirrored copy is placed below the image, doubling its height.
+    Vertical,
+}
+
+
+/// A flip or rotation applied by [`apply_geometric_operation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometricOperation {
+    /// Mirrors the image left to right.
+    FlipHorizontal,
+
+    /// Mirrors the image top to bottom.
+    FlipVertical,
+
+    /// Rotates the image by 90 degrees clockwise, swapping its width and height.
+    RotateClockwise,
+
+    /// Rotates the image by 90 degrees counter-clockwise, swapping its width and height.
+    RotateCounterClockwise,
+}
+
+
+/// Builds a lookup table that maps every possible `u8` channel value
+/// onto one of `levels` evenly-spaced output values (always including `0` and `255`).
+///
+/// # Invariants
+/// - `levels` must be at least `2`.
+fn build_posterization_lookup_table(levels: u8) -> [u8; 256] {
+    assert!(levels >= 2);
+
+    let number_of_steps = (levels - 1) as f32;
+    let mut lookup_table = [0u8; 256];
+
+    for (input_value, output_value) in lookup_table.iter_mut().enumerate() {
+        let normalized_value = input_value as f32 / u8::MAX as f32;
+        let quantized_value = (normalized_value * number_of_steps).round() / number_of_steps;
+
+        *output_value = (quantized_value * u8::MAX as f32).round() as u8;
+    }
+
+    lookup_table
+}
+
+
+/// Posterizes the given `image` in-place (and in parallel, using `rayon`) by quantizing
+/// each color channel to `levels_per_channel` evenly-spaced levels.
+///
+/// The quantized levels always include both extremes, meaning that `levels_per_channel`
+/// values below `2` are treated as `2` (i.e. each channel is collapsed to either `0` or `255`).
+/// The alpha channel is left untouched.
+///
+/// Applying this *before* sorting produces banded segments, while applying it
+/// *after* sorting gives the result a poster-like look.
+pub fn posterize(image: &mut RgbaImage, levels_per_channel: u8) {
+    let lookup_table = build_posterization_lookup_table(levels_per_channel.max(2));
+
+    image.par_pixels_mut().for_each(|pixel| {
+        let [r, g, b, a] = pixel.0;
+
+        pixel.0 = [
+            lookup_table[r as usize],
+            lookup_table[g as usize],
+            lookup_table[b as usize],
+            a,
+        ];
+    });
+}
+
+
+/// Inverts the colors of the given `image` in-place (and in parallel, using `rayon`)
+/// by replacing each color channel value with `255 - value`. The alpha channel is left untouched.
+pub fn invert_rgba_image(image: &mut RgbaImage) {
+    image.par_pixels_mut().for_each(|pixel| {
+        let [r, g, b, a] = pixel.0;
+
+        pixel.0 = [u8::MAX - r, u8::MAX - g, u8::MAX - b, a];
+    });
+}
+
+
+/// Builds a lookup table that maps every possible `u8` channel value onto its value
+/// after applying the given `brightness` offset and `contrast` factor
+/// (see [`adjust_brightness_contrast`]).
+///
+/// The computation is done in `f32` and saturated to `0..=255`, as naive `u8` arithmetic
+/// would wrap around (e.g. `255 + 10` would become `9`), creating bright-to-dark speckles.
+fn build_brightness_contrast_lookup_table(brightness: f32, contrast: f32) -> [u8; 256] {
+    const CHANNEL_MIDPOINT: f32 = u8::MAX as f32 / 2.0;
+
+    let mut lookup_table = [0u8; 256];
+
+    for (input_value, output_value) in lookup_table.iter_mut().enumerate() {
+        let adjusted_value =
+            (input_value as f32 - CHANNEL_MIDPOINT) * contrast + CHANNEL_MIDPOINT + brightness;
+
+        *output_value = adjusted_value.round().clamp(0.0, u8::MAX as f32) as u8;
+    }
+
+    lookup_table
+}
+
+
+/// Adjusts the brightness and contrast of the given `image` in-place
+/// (and in parallel, using `rayon`).
+///
+/// Each color channel is first scaled by `contrast` around the middle of the channel range
+/// (`1.0` leaves it unchanged, values below `1.0` reduce the contrast), after which
+/// `brightness` (in channel units, e.g. `-255.0..=255.0`) is added to it.
+/// Results saturate at `0` and `255` instead of wrapping around.
+/// The alpha channel is left untouched.
+///
+/// A `brightness` of `0.0` together with a `contrast` of `1.0` leaves the image as it is.
+pub fn adjust_brightness_contrast(image: &mut RgbaImage, brightness: f32, contrast: f32) {
+    if brightness == 0.0 && contrast == 1.0 {
+        return;
+    }
+
+    let lookup_table = build_brightness_contrast_lookup_table(brightness, contrast);
+
+    image.par_pixels_mut().for_each(|pixel| {
+        let [r, g, b, a] = pixel.0;
+
+        pixel.0 = [
+            lookup_table[r as usize],
+            lookup_table[g as usize],
+            lookup_table[b as usize],
+            a,
+        ];
+    });
+}
+
+
+/// Returns a new image consisting of the given `image` followed by its mirror image
+/// along the given `axis`, doubling either the width or the height of the image.
+///
+/// As the pixels on both sides of the seam are identical, the output can be tiled
+/// seamlessly along the given axis (e.g. to create ping-pong texture strips).
+pub fn mirror_tile(image: &RgbaImage, axis: Axis) -> RgbaImage {
+    let width = image.width();
+    let height = image.height();
+
+    match axis {
+        Axis::Horizontal => {
+            let mut tiled_image = RgbaImage::new(width * 2, height);
+            let mirrored_image = image::imageops::flip_horizontal(image);
+
+            // PANIC SAFETY: Both halves fit into the output image, as we've just doubled its width.
+            tiled_image
+                .copy_from(image, 0, 0)
+                .expect("original image should fit into the tiled image");
+            tiled_image
+                .copy_from(&mirrored_image, width, 0)
+                .expect("mirrored image should fit into the tiled image");
+
+            tiled_image
+        }
+        Axis::Vertical => {
+            let mut tiled_image = RgbaImage::new(width, height * 2);
+            let mirrored_image = image::imageops::flip_vertical(image);
+
+            // PANIC SAFETY: Both halves fit into the output image, as we've just doubled its height.
+            tiled_image
+                .copy_from(image, 0, 0)
+                .expect("original image should fit into the tiled image");
+            tiled_image
+                .copy_from(&mirrored_image, 0, height)
+                .expect("mirrored image should fit into the tiled image");
+
+            tiled_image
+        }
+    }
+}
+
+
+/// Returns a flipped or rotated copy of the given `image` (see [`GeometricOperation`]).
+///
+/// Rotating before sorting is an easy way of sorting along the other axis.
+pub fn apply_geometric_operation(image: &RgbaImage, operation: GeometricOperation) -> RgbaImage {
+    match operation {
+        GeometricOperation::FlipHorizontal => image::imageops::flip_horizontal(image),
+        GeometricOperation::FlipVertical => image::imageops::flip_vertical(image),
+        GeometricOperation::RotateClockwise => image::imageops::rotate90(image),
+        GeometricOperation::RotateCounterClockwise => image::imageops::rotate270(image),
+    }
+}
+
+
+/// Swaps the RGBA channels of the given `image` in-place (and in parallel, using `rayon`).
+///
+/// Each output channel `i` is taken from input channel `mapping[i]`, meaning that
+/// e.g. `[2, 1, 0, 3]` swaps the red and blue channels (RGBA to BGRA).
+/// Channels may be repeated (e.g. `[0, 0, 0, 3]` copies red into all color channels).
+///
+/// Returns [`AdjustmentError::InvalidChannelMapping`] (leaving the image untouched)
+/// if any of the indices is not a valid RGBA channel index.
+pub fn swap_channels(image: &mut RgbaImage, mapping: [usize; 4]) -> Result<(), AdjustmentError> {
+    if let Some((position, &channel_index)) = mapping
+        .iter()
+        .enumerate()
+        .find(|(_, channel_index)| **channel_index > 3)
+    {
+        return Err(AdjustmentError::InvalidChannelMapping {
+            position,
+            channel_index,
+        });
+    }
+
+    image.par_pixels_mut().for_each(|pixel| {
+        let original_channels = pixel.0;
+
+        pixel.0 = [
+            original_channels[mapping[0]],
+            original_channels[mapping[1]],
+            original_channels[mapping[2]],
+            original_channels[mapping[3]],
+        ];
+    });
+
+    Ok(())
+}
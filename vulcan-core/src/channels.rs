@@ -0,0 +1,44 @@
+//! Splitting RGBA images into their individual channels and merging them back together,
+//! e.g. for sorting each channel separately.
+
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+
+
+/// Splits the given `image` into four grayscale images, containing
+/// the red, green, blue and alpha channel, respectively.
+pub fn split_rgba(image: &RgbaImage) -> [GrayImage; 4] {
+    let (width, height) = image.dimensions();
+
+    std::array::from_fn(|channel_index| {
+        GrayImage::from_fn(width, height, |x, y| {
+            Luma([image.get_pixel(x, y).0[channel_index]])
+        })
+    })
+}
+
+
+/// Merges four grayscale images (red, green, blue and alpha channel, in that order)
+/// into a single RGBA image. This is the inverse of [`split_rgba`].
+///
+/// # Panics
+/// All four channel images must have the same dimensions; the function will otherwise panic.
+pub fn merge_rgba(channels: [GrayImage; 4]) -> RgbaImage {
+    let [red, green, blue, alpha] = channels;
+    let (width, height) = red.dimensions();
+
+    assert!(
+        [&green, &blue, &alpha]
+            .iter()
+            .all(|channel| channel.dimensions() == (width, height)),
+        "all channel images must have the same dimensions"
+    );
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([
+            red.get_pixel(x, y).0[0],
+            green.get_pixel(x, y).0[0],
+            blue.get_pixel(x, y).0[0],
+            alpha.get_pixel(x, y).0[0],
+        ])
+    })
+}
@@ -0,0 +1,45 @@
+//! Exports internal pixel sorting structures into formats suitable for external analysis.
+
+use serde::Serialize;
+
+use crate::pixel_sorting::prepared::{PreparedPixelSort, PreparedSegmentSpan};
+
+/// The stable, serialized form of the segment structure of a [`PreparedPixelSort`].
+#[derive(Serialize)]
+struct SegmentsExport {
+    /// Whether each row is actually a column of the original image,
+    /// see [`PreparedPixelSort::is_vertical`].
+    vertical: bool,
+
+    /// The segments of each row, in row-major order.
+    rows: Vec<Vec<PreparedSegmentSpan>>,
+}
+
+/// Serializes the segment structure of a prepared pixel sort into JSON.
+///
+/// The output is an object of the following shape:
+/// ```json
+/// {
+///   "vertical": false,
+///   "rows": [
+///     [{ "start_index": 0, "length": 12 }, { "start_index": 20, "length": 3 }],
+///     []
+///   ]
+/// }
+/// ```
+///
+/// Each element of `rows` lists the segments of one row (or column, if `vertical` is `true`),
+/// in the order they appear in, with `start_index` being the index of the first pixel
+/// of the segment inside its row. Rows without any segments are empty arrays.
+pub fn segments_to_json<SortingContext>(prepared: &PreparedPixelSort<SortingContext>) -> String
+where
+    SortingContext: Send + Copy + PartialOrd,
+{
+    let export = SegmentsExport {
+        vertical: prepared.is_vertical(),
+        rows: prepared.segment_spans(),
+    };
+
+    serde_json::to_string_pretty(&export)
+        .expect("serializing plain integers and booleans into JSON should never fail")
+}
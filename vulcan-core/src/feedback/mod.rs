@@ -1,68 +1,503 @@
-use image::{Rgba, RgbaImage};
-use rayon::prelude::ParallelIterator;
+use image::{DynamicImage, GrayImage, Rgba, RgbaImage};
+use imageproc::contrast::otsu_level;
+use rayon::{
+    iter::IndexedParallelIterator,
+    prelude::{IntoParallelRefIterator, ParallelIterator},
+};
 
-use crate::pixel_sorting::properties::{
-    compute_rgba_hsl_hue,
-    compute_rgba_hsl_saturation,
-    compute_rgba_relative_luminance,
+use crate::pixel_sorting::{
+    prepared::compute_canny_edge_mask,
+    properties::{
+        DistanceMetric,
+        LuminanceHistogram,
+        LuminanceWeights,
+        compute_rgba_color_distance,
+        compute_rgba_hsl_hue_gamma,
+        compute_rgba_hsl_saturation,
+        compute_rgba_normalized_alpha,
+        compute_rgba_relative_luminance,
+        compute_rgba_weighted_luminance,
+        convert_gamma_encoded_srgb_u8_to_linear_f32,
+        is_hue_in_range,
+    },
 };
 
 pub enum FeedbackSegmentSelectionMode {
-    LuminanceRange { low: f32, high: f32 },
-    HueRange { low: f32, high: f32 },
-    SaturationRange { low: f32, high: f32 },
+    /// If `invert` is set, the pixels *outside* of the range are selected instead
+    /// (the same applies to the other variants with an `invert` field).
+    LuminanceRange {
+        low: f32,
+        high: f32,
+        invert: bool,
+    },
+    /// Percentiles (`0.0..=100.0`) of the image's luminance distribution,
+    /// see [`PreparedSegmentSelectionMode::LuminancePercentileRange`].
+    ///
+    /// [`PreparedSegmentSelectionMode::LuminancePercentileRange`]:
+    ///     crate::pixel_sorting::prepared::PreparedSegmentSelectionMode::LuminancePercentileRange
+    LuminancePercentileRange {
+        low: f32,
+        high: f32,
+        invert: bool,
+    },
+    WeightedLuminanceRange {
+        weights: LuminanceWeights,
+        low: f32,
+        high: f32,
+        invert: bool,
+    },
+    /// If `low` is larger than `high`, the hue range wraps around 360 degrees.
+    HueRange {
+        low: f32,
+        high: f32,
+        invert: bool,
+    },
+    SaturationRange {
+        low: f32,
+        high: f32,
+        invert: bool,
+    },
+    AlphaRange {
+        low: f32,
+        high: f32,
+    },
+    ColorDistanceRange {
+        target: Rgba<u8>,
+        low: f32,
+        high: f32,
+        distance_metric: DistanceMetric,
+    },
+    /// Previews the (possibly inverted) edge mask that
+    /// [`PreparedSegmentSelectionMode::CannyEdges`] selects, see [`compute_canny_edge_mask`].
+    ///
+    /// [`PreparedSegmentSelectionMode::CannyEdges`]:
+    ///     crate::pixel_sorting::prepared::PreparedSegmentSelectionMode::CannyEdges
+    CannyEdges {
+        low: f32,
+        high: f32,
+        invert_edges: bool,
+        blur_sigma: Option<f32>,
+        dilation: u32,
+    },
+}
+
+/// How pixels that would not be sorted are displayed in a threshold preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdPreviewStyle {
+    /// Non-targeted pixels are replaced with [`PIXEL_BLACK`].
+    MaskBlack,
+
+    /// Non-targeted pixels are desaturated and darkened (see [`dim_non_targeted_pixels`]),
+    /// keeping faint structure visible.
+    Dim,
 }
 
-fn mask_out_non_targeted_pixels_using<SegmentMembershipClosure>(
+fn modify_non_targeted_pixels_using<SegmentMembershipClosure, PixelModificationClosure>(
     image: &mut RgbaImage,
-    non_targeted_pixels_color: Rgba<u8>,
     segment_membership_closure: SegmentMembershipClosure,
+    pixel_modification_closure: PixelModificationClosure,
 ) where
     SegmentMembershipClosure: Fn(&Rgba<u8>) -> bool + Send + Sync,
+    PixelModificationClosure: Fn(&mut Rgba<u8>) + Send + Sync,
 {
     image.par_pixels_mut().for_each(|pixel| {
         let would_be_sorted = segment_membership_closure(pixel);
 
         if !would_be_sorted {
-            *pixel = non_targeted_pixels_color;
+            pixel_modification_closure(pixel);
         }
     });
 }
 
-pub const PIXEL_BLACK: Rgba<u8> = Rgba([0, 0, 0, u8::MAX]);
+/// Lazily computed per-pixel property maps (relative luminance, hue, saturation and alpha)
+/// of a single image, in row-major order.
+///
+/// Rendering threshold previews with [`render_threshold_preview_with_property_maps`]
+/// fills these in on demand, so that repeated previews of the same image (e.g. while dragging
+/// a threshold slider) don't need to recompute the same pixel properties over and over.
+///
+/// The maps have no way of knowing which image they were computed from: it is up to
+/// the caller to only ever use them with that same (unmodified) image.
+#[derive(Debug, Clone, Default)]
+pub struct PixelPropertyMaps {
+    luminance: Option<Vec<f32>>,
+    hue: Option<Vec<f32>>,
+    saturation: Option<Vec<f32>>,
+    alpha: Option<Vec<f32>>,
+}
 
-pub fn mask_out_non_targeted_pixels(
+impl PixelPropertyMaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_or_compute<'m>(
+        property_map: &'m mut Option<Vec<f32>>,
+        image: &RgbaImage,
+        compute_property: fn(&Rgba<u8>) -> f32,
+    ) -> &'m [f32] {
+        property_map.get_or_insert_with(|| image.par_pixels().map(compute_property).collect())
+    }
+
+    /// Returns the relative luminance of each pixel of `image`, computing it if necessary.
+    pub fn luminance(&mut self, image: &RgbaImage) -> &[f32] {
+        Self::get_or_compute(
+            &mut self.luminance,
+            image,
+            compute_rgba_relative_luminance,
+        )
+    }
+
+    /// Returns the HSL hue of each pixel of `image`, computing it if necessary.
+    pub fn hue(&mut self, image: &RgbaImage) -> &[f32] {
+        Self::get_or_compute(&mut self.hue, image, compute_rgba_hsl_hue_gamma)
+    }
+
+    /// Returns the HSL saturation of each pixel of `image`, computing it if necessary.
+    pub fn saturation(&mut self, image: &RgbaImage) -> &[f32] {
+        Self::get_or_compute(
+            &mut self.saturation,
+            image,
+            compute_rgba_hsl_saturation,
+        )
+    }
+
+    /// Returns the normalized alpha of each pixel of `image`, computing it if necessary.
+    pub fn alpha(&mut self, image: &RgbaImage) -> &[f32] {
+        Self::get_or_compute(
+            &mut self.alpha,
+            image,
+            compute_rgba_normalized_alpha,
+        )
+    }
+}
+
+fn modify_non_targeted_pixels_using_property_map<
+    PropertyMembershipClosure,
+    PixelModificationClosure,
+>(
+    image: &mut RgbaImage,
+    property_map: &[f32],
+    property_membership_closure: PropertyMembershipClosure,
+    pixel_modification_closure: PixelModificationClosure,
+) where
+    PropertyMembershipClosure: Fn(f32) -> bool + Send + Sync,
+    PixelModificationClosure: Fn(&mut Rgba<u8>) + Send + Sync,
+{
+    assert_eq!(
+        property_map.len(),
+        image.width() as usize * image.height() as usize,
+        "property map does not match the image"
+    );
+
+    image
+        .par_pixels_mut()
+        .zip(property_map.par_iter())
+        .for_each(|(pixel, property_value)| {
+            if !property_membership_closure(*property_value) {
+                pixel_modification_closure(pixel);
+            }
+        });
+}
+
+/// Applies `pixel_modification_closure` to every pixel of `image` whose pixel in
+/// the binary `selection_mask` (of the same dimensions) is not [`u8::MAX`].
+fn modify_non_targeted_pixels_using_selection_mask<PixelModificationClosure>(
+    image: &mut RgbaImage,
+    selection_mask: &GrayImage,
+    pixel_modification_closure: PixelModificationClosure,
+) where
+    PixelModificationClosure: Fn(&mut Rgba<u8>) + Send + Sync,
+{
+    assert_eq!(
+        selection_mask.dimensions(),
+        image.dimensions(),
+        "selection mask does not match the image"
+    );
+
+    image
+        .par_pixels_mut()
+        .zip(selection_mask.par_pixels())
+        .for_each(|(pixel, mask_pixel)| {
+            if mask_pixel.0[0] != u8::MAX {
+                pixel_modification_closure(pixel);
+            }
+        });
+}
+
+fn modify_non_targeted_pixels<PixelModificationClosure>(
     image: &mut RgbaImage,
     mode: FeedbackSegmentSelectionMode,
-    non_targeted_pixels_color: Rgba<u8>,
-) {
+    pixel_modification_closure: PixelModificationClosure,
+) where
+    PixelModificationClosure: Fn(&mut Rgba<u8>) + Send + Sync,
+{
     match mode {
-        FeedbackSegmentSelectionMode::LuminanceRange { low, high } => {
+        FeedbackSegmentSelectionMode::LuminanceRange { low, high, invert } => {
             let target_luminance_range = low..=high;
 
-            mask_out_non_targeted_pixels_using(image, non_targeted_pixels_color, |pixel| {
-                let relative_luminance = compute_rgba_relative_luminance(pixel);
+            modify_non_targeted_pixels_using(
+                image,
+                |pixel| {
+                    let relative_luminance = compute_rgba_relative_luminance(pixel);
 
-                target_luminance_range.contains(&relative_luminance)
-            })
+                    target_luminance_range.contains(&relative_luminance) != invert
+                },
+                pixel_modification_closure,
+            )
         }
-        FeedbackSegmentSelectionMode::HueRange { low, high } => {
-            let target_hue_range = low..=high;
+        FeedbackSegmentSelectionMode::LuminancePercentileRange { low, high, invert } => {
+            let (low, high) =
+                LuminanceHistogram::from_image(image).luminance_range_at_percentiles(low, high);
 
-            mask_out_non_targeted_pixels_using(image, non_targeted_pixels_color, |pixel| {
-                let hue = compute_rgba_hsl_hue(pixel);
+            modify_non_targeted_pixels(
+                image,
+                FeedbackSegmentSelectionMode::LuminanceRange { low, high, invert },
+                pixel_modification_closure,
+            )
+        }
+        FeedbackSegmentSelectionMode::WeightedLuminanceRange {
+            weights,
+            low,
+            high,
+            invert,
+        } => {
+            let target_luminance_range = low..=high;
 
-                target_hue_range.contains(&hue)
-            })
+            modify_non_targeted_pixels_using(
+                image,
+                |pixel| {
+                    let weighted_luminance = compute_rgba_weighted_luminance(pixel, weights);
+
+                    target_luminance_range.contains(&weighted_luminance) != invert
+                },
+                pixel_modification_closure,
+            )
+        }
+        FeedbackSegmentSelectionMode::HueRange { low, high, invert } => {
+            modify_non_targeted_pixels_using(
+                image,
+                |pixel| {
+                    let hue = compute_rgba_hsl_hue_gamma(pixel);
+
+                    is_hue_in_range(hue, low, high) != invert
+                },
+                pixel_modification_closure,
+            )
         }
-        FeedbackSegmentSelectionMode::SaturationRange { low, high } => {
+        FeedbackSegmentSelectionMode::SaturationRange { low, high, invert } => {
             let target_saturation_range = low..=high;
 
-            mask_out_non_targeted_pixels_using(image, non_targeted_pixels_color, |pixel| {
-                let saturation = compute_rgba_hsl_saturation(pixel);
+            modify_non_targeted_pixels_using(
+                image,
+                |pixel| {
+                    let saturation = compute_rgba_hsl_saturation(pixel);
+
+                    target_saturation_range.contains(&saturation) != invert
+                },
+                pixel_modification_closure,
+            )
+        }
+        FeedbackSegmentSelectionMode::AlphaRange { low, high } => {
+            let target_alpha_range = low..=high;
+
+            modify_non_targeted_pixels_using(
+                image,
+                |pixel| {
+                    let alpha = compute_rgba_normalized_alpha(pixel);
+
+                    target_alpha_range.contains(&alpha)
+                },
+                pixel_modification_closure,
+            )
+        }
+        FeedbackSegmentSelectionMode::ColorDistanceRange {
+            target,
+            low,
+            high,
+            distance_metric,
+        } => {
+            let target_color_distance_range = low..=high;
+
+            modify_non_targeted_pixels_using(
+                image,
+                |pixel| {
+                    let color_distance =
+                        compute_rgba_color_distance(pixel, &target, distance_metric);
 
-                target_saturation_range.contains(&saturation)
+                    target_color_distance_range.contains(&color_distance)
+                },
+                pixel_modification_closure,
+            )
+        }
+        FeedbackSegmentSelectionMode::CannyEdges {
+            low,
+            high,
+            invert_edges,
+            blur_sigma,
+            dilation,
+        } => {
+            let gray_image = DynamicImage::ImageRgba8(image.clone()).into_luma8();
+            let mut edge_mask =
+                compute_canny_edge_mask(gray_image, low, high, blur_sigma, dilation);
+
+            if invert_edges {
+                image::imageops::invert(&mut edge_mask);
+            }
+
+            modify_non_targeted_pixels_using_selection_mask(
+                image,
+                &edge_mask,
+                pixel_modification_closure,
+            )
+        }
+    }
+}
+
+pub const PIXEL_BLACK: Rgba<u8> = Rgba([0, 0, 0, u8::MAX]);
+
+/// How bright dimmed pixels are, relative to their (desaturated) original brightness.
+const DIMMED_PIXEL_BRIGHTNESS_FACTOR: f32 = 0.3;
+
+pub fn mask_out_non_targeted_pixels(
+    image: &mut RgbaImage,
+    mode: FeedbackSegmentSelectionMode,
+    non_targeted_pixels_color: Rgba<u8>,
+) {
+    modify_non_targeted_pixels(image, mode, |pixel| {
+        *pixel = non_targeted_pixels_color;
+    });
+}
+
+/// Desaturates and darkens the pixels that would not be sorted, leaving the ones that would
+/// at full color. Unlike [`mask_out_non_targeted_pixels`], this keeps the structure of
+/// the image faintly visible. Alpha is left untouched.
+pub fn dim_non_targeted_pixels(image: &mut RgbaImage, mode: FeedbackSegmentSelectionMode) {
+    modify_non_targeted_pixels(image, mode, dim_pixel);
+}
+
+fn dim_pixel(pixel: &mut Rgba<u8>) {
+    let [red, green, blue, alpha] = pixel.0;
+
+    // A cheap gamma-space approximation of luminance is plenty for a preview.
+    let gray = 0.2126 * red as f32 + 0.7152 * green as f32 + 0.0722 * blue as f32;
+    let dimmed_gray = (gray * DIMMED_PIXEL_BRIGHTNESS_FACTOR).round() as u8;
+
+    *pixel = Rgba([dimmed_gray, dimmed_gray, dimmed_gray, alpha]);
+}
+
+fn modify_non_targeted_pixels_with_property_maps<PixelModificationClosure>(
+    image: &mut RgbaImage,
+    mode: FeedbackSegmentSelectionMode,
+    property_maps: &mut PixelPropertyMaps,
+    pixel_modification_closure: PixelModificationClosure,
+) where
+    PixelModificationClosure: Fn(&mut Rgba<u8>) + Send + Sync,
+{
+    // Only hue is circular, so only hue ranges wrap around when `low` is larger than `high`.
+    let (property_map, low, high, is_circular, invert) = match mode {
+        FeedbackSegmentSelectionMode::LuminanceRange { low, high, invert } => (
+            property_maps.luminance(image),
+            low,
+            high,
+            false,
+            invert,
+        ),
+        FeedbackSegmentSelectionMode::LuminancePercentileRange { low, high, invert } => {
+            let luminance_map = property_maps.luminance(image);
+            let (low, high) = LuminanceHistogram::from_luminances(luminance_map.iter().copied())
+                .luminance_range_at_percentiles(low, high);
+
+            (luminance_map, low, high, false, invert)
+        }
+        FeedbackSegmentSelectionMode::HueRange { low, high, invert } => {
+            (property_maps.hue(image), low, high, true, invert)
+        }
+        FeedbackSegmentSelectionMode::SaturationRange { low, high, invert } => (
+            property_maps.saturation(image),
+            low,
+            high,
+            false,
+            invert,
+        ),
+        FeedbackSegmentSelectionMode::AlphaRange { low, high } => (
+            property_maps.alpha(image),
+            low,
+            high,
+            false,
+            false,
+        ),
+        // Color distances depend on the target color, weighted luminances on the weights
+        // and edges on neighbouring pixels, so there is no single map to cache.
+        FeedbackSegmentSelectionMode::ColorDistanceRange { .. }
+        | FeedbackSegmentSelectionMode::WeightedLuminanceRange { .. }
+        | FeedbackSegmentSelectionMode::CannyEdges { .. } => {
+            return modify_non_targeted_pixels(image, mode, pixel_modification_closure);
+        }
+    };
+
+    modify_non_targeted_pixels_using_property_map(
+        image,
+        property_map,
+        |property_value| {
+            let is_in_range = if is_circular {
+                is_hue_in_range(property_value, low, high)
+            } else {
+                (low..=high).contains(&property_value)
+            };
+
+            is_in_range != invert
+        },
+        pixel_modification_closure,
+    );
+}
+
+/// Renders a threshold preview onto `image` in the given `style`.
+///
+/// This is equivalent to [`render_threshold_preview`], but takes the pixel properties
+/// from (and stores newly computed ones into) `property_maps`, which must have been
+/// computed from this same `image`.
+pub fn render_threshold_preview_with_property_maps(
+    image: &mut RgbaImage,
+    mode: FeedbackSegmentSelectionMode,
+    style: ThresholdPreviewStyle,
+    property_maps: &mut PixelPropertyMaps,
+) {
+    match style {
+        ThresholdPreviewStyle::MaskBlack => {
+            modify_non_targeted_pixels_with_property_maps(image, mode, property_maps, |pixel| {
+                *pixel = PIXEL_BLACK;
             })
         }
+        ThresholdPreviewStyle::Dim => {
+            modify_non_targeted_pixels_with_property_maps(image, mode, property_maps, dim_pixel)
+        }
+    }
+}
+
+/// Renders a threshold preview onto `image` in the given `style`.
+pub fn render_threshold_preview(
+    image: &mut RgbaImage,
+    mode: FeedbackSegmentSelectionMode,
+    style: ThresholdPreviewStyle,
+) {
+    match style {
+        ThresholdPreviewStyle::MaskBlack => mask_out_non_targeted_pixels(image, mode, PIXEL_BLACK),
+        ThresholdPreviewStyle::Dim => dim_non_targeted_pixels(image, mode),
     }
 }
+
+/// Computes the Otsu threshold[^otsu] of the image's luma histogram, i.e. the luminance
+/// that best separates its pixels into a dark and a bright class, as an `f32`
+/// in the range `0.0..=1.0`.
+///
+/// The luma is gamma-encoded, so the threshold is linearized to match
+/// [`FeedbackSegmentSelectionMode::LuminanceRange`] (which uses relative luminance).
+///
+///
+/// [^otsu]: See <https://en.wikipedia.org/wiki/Otsu%27s_method> for more information.
+pub fn compute_otsu_threshold(image: &RgbaImage) -> f32 {
+    let luma_image = DynamicImage::ImageRgba8(image.clone()).into_luma8();
+
+    convert_gamma_encoded_srgb_u8_to_linear_f32(otsu_level(&luma_image))
+}
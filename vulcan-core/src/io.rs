@@ -1,17 +1,51 @@
 use std::{
+    borrow::Cow,
+    ffi::OsStr,
     fs::OpenOptions,
-    io::{self, BufWriter, Write},
-    path::Path,
+    io::{self, BufWriter, Cursor, Seek, Write},
+    path::{Path, PathBuf},
 };
 
 use image::{
     DynamicImage,
-    codecs::png::{CompressionType, FilterType, PngEncoder},
+    GrayImage,
+    ImageBuffer,
+    ImageDecoder,
+    ImageEncoder,
+    ImageError,
+    ImageReader,
+    Rgba,
+    RgbaImage,
+    codecs::{
+        bmp::BmpEncoder,
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+        tiff::TiffEncoder,
+        webp::WebPEncoder,
+    },
 };
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ImageSaveError {
+    #[error("no permission to write to the output file or its directory")]
+    PermissionDenied {
+        #[source]
+        error: io::Error,
+    },
+
+    #[error("the directory of the output file does not exist")]
+    ParentDirectoryMissing {
+        #[source]
+        error: io::Error,
+    },
+
+    #[error("the output file already exists")]
+    AlreadyExists {
+        #[source]
+        error: io::Error,
+    },
+
     #[error("failed to open file for writing")]
     FileOpenError {
         #[source]
@@ -31,15 +65,543 @@ pub enum ImageSaveError {
     },
 }
 
+/// The quality used for JPEG images, unless specified otherwise.
+pub const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+/// How strongly PNG images are compressed, trading encoding speed for file size.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum PngCompression {
+    /// Fast encoding with minimal compression, for quick iteration.
+    #[default]
+    Fast,
+
+    Balanced,
+
+    /// The smallest files, at the cost of (much) slower encoding, for final exports.
+    Best,
+}
+
+impl PngCompression {
+    /// Returns the compression level the PNG encoder should use.
+    pub fn compression_type(self) -> CompressionType {
+        match self {
+            Self::Fast => CompressionType::Fast,
+            Self::Balanced => CompressionType::Default,
+            Self::Best => CompressionType::Best,
+        }
+    }
+
+    /// Returns the scanline filter the PNG encoder should use.
+    ///
+    /// Adaptive filtering (picking the best filter for each scanline) is cheap compared
+    /// to the compression itself and makes the images considerably smaller at every level.
+    pub fn filter_type(self) -> FilterType {
+        FilterType::Adaptive
+    }
+}
+
+/// An image file format that images can be saved in, see [`save_image`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    Png {
+        compression: PngCompression,
+    },
+
+    /// Lossy JPEG without transparency (the alpha channel is dropped).
+    Jpeg {
+        /// The encoding quality, from `1` (smallest file) to `100` (best quality).
+        quality: u8,
+    },
+
+    /// Lossless WebP.
+    Webp,
+
+    Bmp,
+
+    Tiff,
+}
+
+impl OutputFormat {
+    /// Returns the format matching the given file extension (compared case-insensitively),
+    /// or `None` if it isn't a format we can save in. PNG uses the default [`PngCompression`]
+    /// and JPEG uses [`DEFAULT_JPEG_QUALITY`].
+    pub fn from_extension<E>(extension: E) -> Option<Self>
+    where
+        E: AsRef<OsStr>,
+    {
+        let extension = extension.as_ref().to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "png" => Some(Self::Png {
+                compression: PngCompression::default(),
+            }),
+            "jpg" | "jpeg" => Some(Self::Jpeg {
+                quality: DEFAULT_JPEG_QUALITY,
+            }),
+            "webp" => Some(Self::Webp),
+            "bmp" => Some(Self::Bmp),
+            "tif" | "tiff" => Some(Self::Tiff),
+            _ => None,
+        }
+    }
+
+    /// Returns the file extensions (lowercase) used for this format, the preferred one first.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Png { .. } => &["png"],
+            Self::Jpeg { .. } => &["jpg", "jpeg"],
+            Self::Webp => &["webp"],
+            Self::Bmp => &["bmp"],
+            Self::Tiff => &["tiff", "tif"],
+        }
+    }
+}
+
+/// Returns the given output `file_path`, adjusted so that its extension matches
+/// the PNG format (see [`normalize_output_file_path_for_format`]).
+pub fn normalize_output_file_path<P>(file_path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    normalize_output_file_path_for_format(
+        file_path,
+        OutputFormat::Png {
+            compression: PngCompression::default(),
+        },
+    )
+}
+
+/// Returns the given output `file_path`, adjusted so that its extension matches
+/// the `format` the image will actually be encoded in.
+///
+/// Extensions are compared case-insensitively, so e.g. `image.PNG` is kept as-is.
+/// Paths without an extension get the format's preferred extension, and paths with any other
+/// extension (e.g. `image.foo` or, for PNG, `image.JPG`) have it replaced,
+/// as we would otherwise write data into a file with a misleading extension.
+pub fn normalize_output_file_path_for_format<P>(file_path: P, format: OutputFormat) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    let file_path = file_path.as_ref();
+
+    let has_matching_extension = file_path
+        .extension()
+        .map(|extension| {
+            format
+                .extensions()
+                .iter()
+                .any(|format_extension| extension.eq_ignore_ascii_case(format_extension))
+        })
+        .unwrap_or(false);
+
+    if has_matching_extension {
+        file_path.to_path_buf()
+    } else {
+        file_path.with_extension(format.extensions()[0])
+    }
+}
+
+/// Extensions to [`Path`] for deriving output file paths from input file paths.
+pub trait ExtendablePath {
+    /// Returns the path with the `suffix` appended to its file stem, keeping the extension
+    /// (e.g. `frames/001.png` with the suffix `_sorted` becomes `frames/001_sorted.png`).
+    ///
+    /// Returns `None` if the path has no file name.
+    fn with_suffix_to_stem(&self, suffix: &str) -> Option<PathBuf>;
+}
+
+impl<P> ExtendablePath for P
+where
+    P: AsRef<Path>,
+{
+    fn with_suffix_to_stem(&self, suffix: &str) -> Option<PathBuf> {
+        let path = self.as_ref();
+
+        let mut file_name = path.file_stem()?.to_os_string();
+        file_name.push(suffix);
+
+        if let Some(extension) = path.extension() {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+
+        Some(path.with_file_name(file_name))
+    }
+}
+
+/// An image decoded by [`decode_image_from_memory`]
+/// (or [`decode_image_from_memory_preserving_grayscale`]
+/// and [`decode_image_from_memory_preserving_precision`]).
+pub struct DecodedImage<I = RgbaImage> {
+    /// The decoded image, converted to RGBA8 (or [`DecodedPixels`]).
+    pub image: I,
+
+    /// The ICC color profile embedded in the image, if it had one
+    /// and if it still applies to the image after the conversion.
+    pub icc_profile: Option<Vec<u8>>,
+}
+
+/// An RGBA image with 16 bits per channel.
+pub type Rgba16Image = ImageBuffer<Rgba<u16>, Vec<u16>>;
+
+/// The pixels of an image decoded by [`decode_image_from_memory_preserving_grayscale`]
+/// (or [`decode_image_from_memory_preserving_precision`]).
+pub enum DecodedPixels {
+    Rgba8(RgbaImage),
+
+    /// A grayscale image without an alpha channel.
+    Luma8(GrayImage),
+
+    /// An image with more than 8 bits per channel (e.g. a 16-bit PNG or TIFF scan).
+    Rgba16(Rgba16Image),
+}
+
+/// Decodes the given encoded image (e.g. the contents of a PNG or JPEG file)
+/// and converts it to RGBA8.
+///
+/// If `respect_orientation` is set and the image carries an EXIF orientation (e.g. photos
+/// taken with a rotated phone), the image is rotated and/or flipped into its display
+/// orientation. As this happens before any sorting, "horizontal" and "vertical" sorting
+/// directions then refer to the image as it is displayed: the vertical sorting path
+/// (which internally rotates the image by 90 degrees and back) operates on the already
+/// reoriented image. Otherwise, the pixels are used exactly as they are stored.
+pub fn decode_image_from_memory(
+    encoded_image: &[u8],
+    respect_orientation: bool,
+) -> Result<DecodedImage, ImageError> {
+    let (decoded_image, icc_profile) =
+        decode_dynamic_image_from_memory(encoded_image, respect_orientation)?;
+
+    Ok(DecodedImage {
+        image: decoded_image.to_rgba8(),
+        icc_profile: retain_icc_profile_for_rgb(icc_profile),
+    })
+}
+
+/// Decodes the given encoded image like [`decode_image_from_memory`], except that
+/// grayscale images without an alpha channel are converted to Luma8 instead of RGBA8,
+/// which needs a quarter of the memory (see [`crate::pixel_sorting::grayscale`]).
+///
+/// The ICC color profile of a grayscale image is kept only if it describes
+/// a grayscale color space (see [`icc_profile_describes_grayscale`]).
+pub fn decode_image_from_memory_preserving_grayscale(
+    encoded_image: &[u8],
+    respect_orientation: bool,
+) -> Result<DecodedImage<DecodedPixels>, ImageError> {
+    let (decoded_image, icc_profile) =
+        decode_dynamic_image_from_memory(encoded_image, respect_orientation)?;
+
+    Ok(convert_to_decoded_pixels_preserving_grayscale(
+        decoded_image,
+        icc_profile,
+    ))
+}
+
+/// Decodes the given encoded image like [`decode_image_from_memory_preserving_grayscale`],
+/// except that images with more than 8 bits per channel (e.g. 16-bit PNG or TIFF scans)
+/// are converted to RGBA16 instead of RGBA8, so that no precision is lost before sorting.
+pub fn decode_image_from_memory_preserving_precision(
+    encoded_image: &[u8],
+    respect_orientation: bool,
+) -> Result<DecodedImage<DecodedPixels>, ImageError> {
+    let (decoded_image, icc_profile) =
+        decode_dynamic_image_from_memory(encoded_image, respect_orientation)?;
+
+    if has_more_than_8_bits_per_channel(&decoded_image) {
+        return Ok(DecodedImage {
+            image: DecodedPixels::Rgba16(decoded_image.into_rgba16()),
+            icc_profile: retain_icc_profile_for_rgb(icc_profile),
+        });
+    }
+
+    Ok(convert_to_decoded_pixels_preserving_grayscale(
+        decoded_image,
+        icc_profile,
+    ))
+}
+
+/// Returns `true` if the samples of the given image are wider than 8 bits.
+fn has_more_than_8_bits_per_channel(image: &DynamicImage) -> bool {
+    let color_type = image.color();
+
+    color_type.bytes_per_pixel() / color_type.channel_count() > 1
+}
+
+/// Converts the decoded image to Luma8 if it is a grayscale image without an alpha channel,
+/// and to RGBA8 otherwise (see [`decode_image_from_memory_preserving_grayscale`]).
+fn convert_to_decoded_pixels_preserving_grayscale(
+    decoded_image: DynamicImage,
+    icc_profile: Option<Vec<u8>>,
+) -> DecodedImage<DecodedPixels> {
+    match decoded_image {
+        DynamicImage::ImageLuma8(_) | DynamicImage::ImageLuma16(_) => DecodedImage {
+            image: DecodedPixels::Luma8(decoded_image.into_luma8()),
+            icc_profile: icc_profile.filter(|icc_profile| {
+                let describes_grayscale = icc_profile_describes_grayscale(icc_profile);
+
+                if !describes_grayscale {
+                    tracing::warn!(
+                        "Dropping ICC profile: it does not describe a grayscale color space."
+                    );
+                }
+
+                describes_grayscale
+            }),
+        },
+        _ => DecodedImage {
+            image: DecodedPixels::Rgba8(decoded_image.into_rgba8()),
+            icc_profile: retain_icc_profile_for_rgb(icc_profile),
+        },
+    }
+}
+
+/// Decodes the given encoded image (reoriented if `respect_orientation` is set,
+/// see [`decode_image_from_memory`]), along with its embedded ICC color profile, if any.
+fn decode_dynamic_image_from_memory(
+    encoded_image: &[u8],
+    respect_orientation: bool,
+) -> Result<(DynamicImage, Option<Vec<u8>>), ImageError> {
+    let mut decoder = ImageReader::new(Cursor::new(encoded_image))
+        .with_guessed_format()
+        .map_err(ImageError::IoError)?
+        .into_decoder()?;
+
+    let icc_profile = match decoder.icc_profile() {
+        Ok(icc_profile) => icc_profile,
+        Err(error) => {
+            tracing::warn!("Dropping ICC profile: failed to read it: {error}");
+            None
+        }
+    };
+
+    let orientation = if respect_orientation {
+        Some(decoder.orientation()?)
+    } else {
+        None
+    };
+
+    let mut decoded_image = DynamicImage::from_decoder(decoder)?;
+
+    if let Some(orientation) = orientation {
+        decoded_image.apply_orientation(orientation);
+    }
+
+    Ok((decoded_image, icc_profile))
+}
+
+/// Returns the given ICC profile if it still applies after converting the image to RGBA8
+/// (see [`icc_profile_describes_rgb`]), dropping it (with a warning) otherwise.
+fn retain_icc_profile_for_rgb(icc_profile: Option<Vec<u8>>) -> Option<Vec<u8>> {
+    match icc_profile {
+        Some(icc_profile) if icc_profile_describes_rgb(&icc_profile) => Some(icc_profile),
+        Some(_) => {
+            tracing::warn!("Dropping ICC profile: it does not describe an RGB color space.");
+            None
+        }
+        None => None,
+    }
+}
+
+/// Returns `true` if the given ICC profile describes an RGB color space.
+///
+/// This is the only kind of profile that can be meaningfully embedded into the RGBA images
+/// we save; e.g. a grayscale or CMYK profile no longer matches the image once it has been
+/// converted to RGBA on load. Truncated profiles (without a full header) are never RGB.
+pub fn icc_profile_describes_rgb(icc_profile: &[u8]) -> bool {
+    // The data color space signature is stored at bytes 16..20 of the ICC profile header.
+    icc_profile.get(16..20) == Some(b"RGB ".as_slice())
+}
+
+/// Returns `true` if the given ICC profile describes a grayscale color space, i.e. it can be
+/// embedded into grayscale images (see [`decode_image_from_memory_preserving_grayscale`]).
+pub fn icc_profile_describes_grayscale(icc_profile: &[u8]) -> bool {
+    icc_profile.get(16..20) == Some(b"GRAY".as_slice())
+}
+
+/// Encodes the given `image` as PNG, using the given `compression` and `filter`,
+/// and writes it into the `writer` (e.g. a file, standard output or an in-memory buffer).
+///
+/// If an `icc_profile` is provided, it is embedded into the PNG (as an `iCCP` chunk).
+///
+/// The writer is not flushed; if it is buffered, flushing it is up to the caller.
+pub fn write_image_as_png<W>(
+    image: &DynamicImage,
+    writer: W,
+    compression: CompressionType,
+    filter: FilterType,
+    icc_profile: Option<&[u8]>,
+) -> Result<(), ImageSaveError>
+where
+    W: Write,
+{
+    let encoder = PngEncoder::new_with_quality(writer, compression, filter);
+
+    write_image_with_encoder(image, encoder, icc_profile)
+}
+
+/// Maps an error that occurred while opening the output file to the most specific
+/// [`ImageSaveError`] variant, so that callers can give actionable advice.
+fn map_file_open_error(error: io::Error) -> ImageSaveError {
+    match error.kind() {
+        io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => {
+            ImageSaveError::PermissionDenied { error }
+        }
+        // When creating a file, this means one of the directories leading up to it is missing.
+        io::ErrorKind::NotFound => ImageSaveError::ParentDirectoryMissing { error },
+        io::ErrorKind::AlreadyExists => ImageSaveError::AlreadyExists { error },
+        _ => ImageSaveError::FileOpenError { error },
+    }
+}
+
+/// Options for [`save_image`].
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ImageSaveOptions<'a> {
+    /// Whether to overwrite the file if it already exists
+    /// (otherwise, saving fails with [`ImageSaveError::AlreadyExists`]).
+    pub overwrite_existing: bool,
+
+    /// The ICC color profile to embed, if any. Formats that can't embed
+    /// a profile (BMP, TIFF) save the image without it.
+    pub icc_profile: Option<&'a [u8]>,
+}
+
+/// Hands the `image` to the given `encoder`,
+/// embedding the `icc_profile` if the encoder supports it.
+fn write_image_with_encoder<E>(
+    image: &DynamicImage,
+    mut encoder: E,
+    icc_profile: Option<&[u8]>,
+) -> Result<(), ImageSaveError>
+where
+    E: ImageEncoder,
+{
+    if let Some(icc_profile) = icc_profile
+        && let Err(error) = encoder.set_icc_profile(icc_profile.to_vec())
+    {
+        tracing::warn!("Dropping ICC profile, the encoder could not embed it: {error}");
+    }
+
+    image
+        .write_with_encoder(encoder)
+        .map_err(|error| ImageSaveError::ImageError { error })
+}
+
+/// Returns the given `image` converted to 8 bits per channel if its samples are wider,
+/// for encoders that only support 8-bit images (WebP, BMP).
+fn reduce_to_8_bits_per_channel(image: &DynamicImage) -> Cow<'_, DynamicImage> {
+    if !has_more_than_8_bits_per_channel(image) {
+        return Cow::Borrowed(image);
+    }
+
+    if image.color().has_alpha() {
+        Cow::Owned(DynamicImage::ImageRgba8(image.to_rgba8()))
+    } else {
+        Cow::Owned(DynamicImage::ImageRgb8(image.to_rgb8()))
+    }
+}
+
+/// Encodes the given `image` in the given `format` and writes it into the `writer`.
+///
+/// If an `icc_profile` is provided, it is embedded into the image, if the format supports it.
+///
+/// PNG and TIFF keep images with 16 bits per channel (e.g. [`Rgba16Image`]) as-is,
+/// while the other formats reduce them to 8 bits per channel.
+///
+/// The writer is not flushed; if it is buffered, flushing it is up to the caller.
+pub fn write_image<W>(
+    image: &DynamicImage,
+    mut writer: W,
+    format: OutputFormat,
+    icc_profile: Option<&[u8]>,
+) -> Result<(), ImageSaveError>
+where
+    W: Write + Seek,
+{
+    match format {
+        OutputFormat::Png { compression } => write_image_as_png(
+            image,
+            writer,
+            compression.compression_type(),
+            compression.filter_type(),
+            icc_profile,
+        ),
+        OutputFormat::Jpeg { quality } => {
+            // JPEG has no alpha channel, so the encoder would reject RGBA images.
+            let rgb_image = DynamicImage::ImageRgb8(image.to_rgb8());
+
+            write_image_with_encoder(
+                &rgb_image,
+                JpegEncoder::new_with_quality(writer, quality.clamp(1, 100)),
+                icc_profile,
+            )
+        }
+        OutputFormat::Webp => write_image_with_encoder(
+            &reduce_to_8_bits_per_channel(image),
+            WebPEncoder::new_lossless(writer),
+            icc_profile,
+        ),
+        OutputFormat::Bmp => write_image_with_encoder(
+            &reduce_to_8_bits_per_channel(image),
+            BmpEncoder::new(&mut writer),
+            icc_profile,
+        ),
+        OutputFormat::Tiff => {
+            write_image_with_encoder(image, TiffEncoder::new(writer), icc_profile)
+        }
+    }
+}
+
+/// Encodes the given `image` in the given `format` into an in-memory buffer
+/// (see [`write_image`]), e.g. for copying it to the clipboard or uploading it.
+pub fn encode_image_to_bytes(
+    image: &DynamicImage,
+    format: OutputFormat,
+) -> Result<Vec<u8>, ImageSaveError> {
+    let mut cursor = Cursor::new(Vec::new());
+
+    write_image(image, &mut cursor, format, None)?;
+
+    Ok(cursor.into_inner())
+}
+
+/// Saves the given `image` as a PNG file at `file_path`, using the default
+/// [`PngCompression`] and embedding the `icc_profile`, if provided (see [`save_image`]).
 pub fn save_image_as_png<P>(
     image: &DynamicImage,
     file_path: P,
     overwrite_existing: bool,
+    icc_profile: Option<&[u8]>,
 ) -> Result<(), ImageSaveError>
 where
     P: AsRef<Path>,
 {
-    let file = if overwrite_existing {
+    save_image(
+        image,
+        file_path,
+        OutputFormat::Png {
+            compression: PngCompression::default(),
+        },
+        ImageSaveOptions {
+            overwrite_existing,
+            icc_profile,
+        },
+    )
+}
+
+/// Saves the given `image` in the given `format` as a file at `file_path` (see [`write_image`]).
+///
+/// The file is saved as-is: use [`normalize_output_file_path_for_format`] first
+/// if its extension should match the format.
+pub fn save_image<P>(
+    image: &DynamicImage,
+    file_path: P,
+    format: OutputFormat,
+    options: ImageSaveOptions,
+) -> Result<(), ImageSaveError>
+where
+    P: AsRef<Path>,
+{
+    let file = if options.overwrite_existing {
         OpenOptions::new()
             .create(true)
             .write(true)
@@ -51,17 +613,16 @@ where
             .write(true)
             .open(file_path.as_ref())
     }
-    .map_err(|error| ImageSaveError::FileOpenError { error })?;
+    .map_err(map_file_open_error)?;
 
     let mut buf_writer = BufWriter::new(file);
 
-    image
-        .write_with_encoder(PngEncoder::new_with_quality(
-            &mut buf_writer,
-            CompressionType::Fast,
-            FilterType::Adaptive,
-        ))
-        .map_err(|error| ImageSaveError::ImageError { error })?;
+    write_image(
+        image,
+        &mut buf_writer,
+        format,
+        options.icc_profile,
+    )?;
 
     let mut file = buf_writer
         .into_inner()
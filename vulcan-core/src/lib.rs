@@ -1,3 +1,7 @@
+pub mod adjustments;
+pub mod channels;
+pub mod export;
 pub mod feedback;
 pub mod io;
+pub mod parallelism;
 pub mod pixel_sorting;
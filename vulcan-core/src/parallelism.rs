@@ -0,0 +1,17 @@
+//! Limiting how many threads pixel sorts use.
+
+use std::num::NonZeroUsize;
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+/// Builds a thread pool with exactly `thread_count` threads. Pixel sorts run inside of it
+/// (see [`ThreadPool::install`]) use at most that many threads for their parallel loops,
+/// instead of rayon's global thread pool (which has one thread per logical CPU by default).
+pub fn build_sorting_thread_pool(
+    thread_count: NonZeroUsize,
+) -> Result<ThreadPool, ThreadPoolBuildError> {
+    ThreadPoolBuilder::new()
+        .num_threads(thread_count.get())
+        .thread_name(|thread_index| format!("vulcan-sorting-{thread_index}"))
+        .build()
+}
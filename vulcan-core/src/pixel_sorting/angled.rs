@@ -0,0 +1,343 @@
+use std::ops::Range;
+
+use image::{ImageBuffer, Pixel, RgbaImage};
+use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+/// A single straight line of pixels along the sorting angle.
+struct AngledLine {
+    /// The range of [`AngledLineLayout::pixel_indices`] holding the pixels on this line.
+    pixel_index_range: Range<usize>,
+
+    /// The column at which this line starts in the aligned line image
+    /// (see [`AngledLineLayout::gather_aligned_line_image`]).
+    start_offset: usize,
+}
+
+/// Partitions the pixels of an image into parallel, one pixel wide straight lines
+/// running at an arbitrary angle, so that each line can be sorted like a row.
+///
+/// The angle is measured in degrees counter-clockwise from the positive x axis,
+/// i.e. `0` runs left to right and `90` runs bottom to top. Every pixel belongs to exactly
+/// one line: the one whose one pixel wide strip (perpendicular to the angle) contains
+/// the pixel's center. Lines are ordered across the angle (for `0` degrees, from top
+/// to bottom), and the pixels of each line are ordered along the angle.
+///
+/// No pixels are resampled, so the mapping is lossless. For `0` degrees, the lines are exactly
+/// the rows of the image; for `90` degrees, they are exactly its columns (from left to right,
+/// each read from bottom to top), matching horizontal and vertical sorting, respectively.
+pub struct AngledLineLayout {
+    image_width: u32,
+    image_height: u32,
+
+    /// The (row-major) indices of the pixels of all lines, grouped by line
+    /// and ordered along the sorting angle within each line.
+    pixel_indices: Vec<u32>,
+
+    lines: Vec<AngledLine>,
+
+    /// The width of the aligned line image, i.e. the largest start offset plus line length.
+    aligned_width: usize,
+}
+
+impl AngledLineLayout {
+    /// Partitions an image of the given dimensions into lines at the given angle.
+    ///
+    /// # Panics
+    /// Panics if the image has more than `2^32` pixels, as pixels are indexed using `u32`.
+    pub fn new(image_width: u32, image_height: u32, degrees: f32) -> Self {
+        let (sine, cosine) = (degrees as f64).to_radians().sin_cos();
+
+        // The y axis points downwards, which is why its contribution to the position
+        // along the angle is negated (a positive angle is counter-clockwise on screen).
+        let position_along = |x: u32, y: u32| (x as f64 + 0.5) * cosine - (y as f64 + 0.5) * sine;
+        let position_across = |x: u32, y: u32| (x as f64 + 0.5) * sine + (y as f64 + 0.5) * cosine;
+
+        if image_width == 0 || image_height == 0 {
+            return Self {
+                image_width,
+                image_height,
+                pixel_indices: Vec::new(),
+                lines: Vec::new(),
+                aligned_width: 0,
+            };
+        }
+
+        assert!(
+            image_width as u64 * image_height as u64 <= u32::MAX as u64 + 1,
+            "image is too large to be sorted at an angle"
+        );
+
+        // Both positions are linear in the pixel coordinates, so their extremes are at the corners.
+        let corners = [
+            (0, 0),
+            (image_width - 1, 0),
+            (0, image_height - 1),
+            (image_width - 1, image_height - 1),
+        ];
+
+        let smallest_strip_index = corners
+            .iter()
+            .map(|&(x, y)| position_across(x, y).floor())
+            .fold(f64::INFINITY, f64::min);
+        let largest_strip_index = corners
+            .iter()
+            .map(|&(x, y)| position_across(x, y).floor())
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let number_of_strips = (largest_strip_index - smallest_strip_index) as usize + 1;
+
+        let strip_index_of = |x: u32, y: u32| {
+            let strip_offset = position_across(x, y).floor() - smallest_strip_index;
+            (strip_offset as usize).min(number_of_strips - 1)
+        };
+
+        let strip_lengths = (0..image_height)
+            .into_par_iter()
+            .fold(
+                || vec![0usize; number_of_strips],
+                |mut strip_lengths, y| {
+                    for x in 0..image_width {
+                        strip_lengths[strip_index_of(x, y)] += 1;
+                    }
+
+                    strip_lengths
+                },
+            )
+            .reduce_with(|mut strip_lengths, other_strip_lengths| {
+                for (strip_length, other_strip_length) in
+                    strip_lengths.iter_mut().zip(other_strip_lengths)
+                {
+                    *strip_length += other_strip_length;
+                }
+
+                strip_lengths
+            })
+            .unwrap_or_default();
+
+        let mut next_free_index_per_strip = strip_lengths
+            .iter()
+            .scan(0, |strip_start, &strip_length| {
+                let current_strip_start = *strip_start;
+                *strip_start += strip_length;
+                Some(current_strip_start)
+            })
+            .collect::<Vec<_>>();
+
+        // Visiting the pixels along the angle's main axis first (and along the other axis
+        // within each column or row) visits the pixels of every strip in order along the angle,
+        // as a strip is too narrow to contain a pixel that is further back in a later column
+        // or row. This way, the pixels of each strip never have to be sorted.
+        let visit_x_forwards = cosine >= 0.0;
+        let visit_y_forwards = sine <= 0.0;
+        let ordered_range = |length: u32, forwards: bool| {
+            (0..length).map(move |index| if forwards { index } else { length - 1 - index })
+        };
+
+        let mut pixel_indices = vec![0u32; image_width as usize * image_height as usize];
+        let mut visit_pixel = |x: u32, y: u32| {
+            let next_free_index = &mut next_free_index_per_strip[strip_index_of(x, y)];
+
+            pixel_indices[*next_free_index] = y * image_width + x;
+            *next_free_index += 1;
+        };
+
+        if cosine.abs() >= sine.abs() {
+            for x in ordered_range(image_width, visit_x_forwards) {
+                for y in ordered_range(image_height, visit_y_forwards) {
+                    visit_pixel(x, y);
+                }
+            }
+        } else {
+            for y in ordered_range(image_height, visit_y_forwards) {
+                for x in ordered_range(image_width, visit_x_forwards) {
+                    visit_pixel(x, y);
+                }
+            }
+        }
+
+        let smallest_position_along = corners
+            .iter()
+            .map(|&(x, y)| position_along(x, y))
+            .fold(f64::INFINITY, f64::min);
+
+        let mut aligned_width = 0;
+        let mut strip_start = 0;
+        let mut lines = Vec::new();
+
+        for strip_length in strip_lengths {
+            let pixel_index_range = strip_start..(strip_start + strip_length);
+            strip_start += strip_length;
+
+            if strip_length == 0 {
+                continue;
+            }
+
+            // Offsetting each line by where it starts along the angle keeps pixels that
+            // are close in the original image close in the aligned line image as well.
+            let first_pixel_index = pixel_indices[pixel_index_range.start];
+            let first_position_along = position_along(
+                first_pixel_index % image_width,
+                first_pixel_index / image_width,
+            );
+            let start_offset = (first_position_along - smallest_position_along).round() as usize;
+            aligned_width = aligned_width.max(start_offset + strip_length);
+
+            lines.push(AngledLine {
+                pixel_index_range,
+                start_offset,
+            });
+        }
+
+        Self {
+            image_width,
+            image_height,
+            pixel_indices,
+            lines,
+            aligned_width,
+        }
+    }
+
+    /// Returns the (row-major) indices of the pixels on the given line,
+    /// ordered along the sorting angle.
+    fn line_pixel_indices(&self, line: &AngledLine) -> &[u32] {
+        &self.pixel_indices[line.pixel_index_range.clone()]
+    }
+
+    /// Converts a (row-major) pixel index into the coordinates of the pixel.
+    fn pixel_coordinates(&self, pixel_index: u32) -> (u32, u32) {
+        (
+            pixel_index % self.image_width,
+            pixel_index / self.image_width,
+        )
+    }
+
+    /// Returns the range of columns the given line occupies in the aligned line image.
+    pub fn aligned_column_range(&self, line_index: usize) -> Range<usize> {
+        let line = &self.lines[line_index];
+        line.start_offset..(line.start_offset + line.pixel_index_range.len())
+    }
+
+    /// Returns the coordinates of the pixel at the given column of the given line
+    /// in the aligned line image, or `None` if that column is padding.
+    pub fn pixel_coordinates_at_aligned_column(
+        &self,
+        line_index: usize,
+        aligned_column: usize,
+    ) -> Option<(u32, u32)> {
+        let line = &self.lines[line_index];
+
+        aligned_column
+            .checked_sub(line.start_offset)
+            .and_then(|index_on_line| self.line_pixel_indices(line).get(index_on_line))
+            .map(|&pixel_index| self.pixel_coordinates(pixel_index))
+    }
+
+    /// Copies the pixels of each line into its own row of a new image, starting at the line's
+    /// start offset. The remaining pixels of each row are padding with all channels set to zero
+    /// (i.e. fully transparent for RGBA images).
+    ///
+    /// Neighbouring lines end up roughly aligned, meaning two-dimensional operations
+    /// (e.g. edge detection) on the resulting image remain meaningful.
+    pub fn gather_aligned_line_image<P>(
+        &self,
+        image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> ImageBuffer<P, Vec<P::Subpixel>>
+    where
+        P: Pixel,
+    {
+        assert_eq!(
+            image.dimensions(),
+            (self.image_width, self.image_height)
+        );
+
+        let mut line_image = ImageBuffer::new(self.aligned_width as u32, self.lines.len() as u32);
+
+        for (line_index, line) in self.lines.iter().enumerate() {
+            for (index_on_line, &pixel_index) in self.line_pixel_indices(line).iter().enumerate() {
+                let (x, y) = self.pixel_coordinates(pixel_index);
+
+                line_image.put_pixel(
+                    (line.start_offset + index_on_line) as u32,
+                    line_index as u32,
+                    *image.get_pixel(x, y),
+                );
+            }
+        }
+
+        line_image
+    }
+
+    /// Reverses [`Self::gather_aligned_line_image`], discarding the padding.
+    pub fn scatter_aligned_line_image(&self, line_image: &RgbaImage) -> RgbaImage {
+        let mut image = RgbaImage::new(self.image_width, self.image_height);
+
+        for (line_index, line) in self.lines.iter().enumerate() {
+            for (index_on_line, &pixel_index) in self.line_pixel_indices(line).iter().enumerate() {
+                let (x, y) = self.pixel_coordinates(pixel_index);
+                let line_pixel = line_image.get_pixel(
+                    (line.start_offset + index_on_line) as u32,
+                    line_index as u32,
+                );
+
+                image.put_pixel(x, y, *line_pixel);
+            }
+        }
+
+        image
+    }
+
+    /// Copies the pixels of each line into its own contiguous buffer of samples
+    /// (e.g. RGBA8 or Luma8), without any padding.
+    pub fn gather_line_buffers<P>(
+        &self,
+        image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    ) -> Vec<Vec<P::Subpixel>>
+    where
+        P: Pixel + Sync,
+        P::Subpixel: Send + Sync,
+    {
+        assert_eq!(
+            image.dimensions(),
+            (self.image_width, self.image_height)
+        );
+
+        self.lines
+            .par_iter()
+            .map(|line| {
+                let mut line_buffer =
+                    Vec::with_capacity(line.pixel_index_range.len() * P::CHANNEL_COUNT as usize);
+
+                for &pixel_index in self.line_pixel_indices(line) {
+                    let (x, y) = self.pixel_coordinates(pixel_index);
+                    line_buffer.extend_from_slice(image.get_pixel(x, y).channels());
+                }
+
+                line_buffer
+            })
+            .collect()
+    }
+
+    /// Writes the line buffers (see [`Self::gather_line_buffers`]) back into `image`.
+    pub fn scatter_line_buffers<P>(
+        &self,
+        line_buffers: &[Vec<P::Subpixel>],
+        image: &mut ImageBuffer<P, Vec<P::Subpixel>>,
+    ) where
+        P: Pixel,
+    {
+        assert_eq!(
+            image.dimensions(),
+            (self.image_width, self.image_height)
+        );
+        assert_eq!(line_buffers.len(), self.lines.len());
+
+        for (line, line_buffer) in self.lines.iter().zip(line_buffers) {
+            let line_pixels = line_buffer.chunks_exact(P::CHANNEL_COUNT as usize);
+
+            for (&pixel_index, pixel) in self.line_pixel_indices(line).iter().zip(line_pixels) {
+                let (x, y) = self.pixel_coordinates(pixel_index);
+                image.put_pixel(x, y, *P::from_slice(pixel));
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use image::RgbaImage;
+use thiserror::Error;
+
+use crate::pixel_sorting::{
+    ImageRegion,
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    config::PixelSortConfig,
+    prepared::{
+        PreparedPixelSort,
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        PreparedSortingContext,
+        SegmentRandomizationMode,
+        SelectionModeError,
+        execute_axis_aligned_prepared_pixel_sort,
+        filter_prepared_segments_by_min_length,
+        prepare_pixel_sort,
+        randomize_prepared_segments,
+        restrict_prepared_segments_to_region,
+        split_prepared_segments_at_max_length,
+    },
+};
+
+
+#[derive(Debug, Error)]
+pub enum PixelSortError {
+    #[error("the selection mode can't be used with this image")]
+    InvalidSelectionMode {
+        #[source]
+        error: SelectionModeError,
+    },
+
+    #[error("the maximum segment length must be larger than zero")]
+    ZeroMaximumSegmentLength,
+}
+
+
+/// Prepares a pixel sort of `image` as described by `config`: restricts it to the
+/// `region_of_interest` (if any), then applies the segment length limits and randomization.
+///
+/// This is the complete preparation behind [`PixelSortBuilder::prepare`], for callers
+/// that already hold a [`PixelSortConfig`] (e.g. one decoded from a share code).
+///
+/// Returns an error if the selection mode can't be used with this image
+/// (see [`PreparedSegmentSelectionMode::validate_for_image`]),
+/// or if the maximum segment length is zero.
+pub fn prepare_configured_pixel_sort(
+    image: RgbaImage,
+    config: &PixelSortConfig,
+    region_of_interest: Option<ImageRegion>,
+) -> Result<PreparedPixelSort<PreparedSortingContext>, PixelSortError> {
+    if config.maximum_segment_length == Some(0) {
+        return Err(PixelSortError::ZeroMaximumSegmentLength);
+    }
+
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        config.selection_mode.clone(),
+        config.sorting_mode,
+        config.tiebreak_mode,
+        config.direction,
+        config.premultiply_alpha,
+    )
+    .map_err(|error| PixelSortError::InvalidSelectionMode { error })?;
+
+    let prepared_sort = if let Some(region_of_interest) = region_of_interest {
+        restrict_prepared_segments_to_region(prepared_sort, region_of_interest)
+    } else {
+        prepared_sort
+    };
+
+    // Short segments are filtered out before randomization,
+    // which deliberately produces short segments of its own.
+    let prepared_sort =
+        filter_prepared_segments_by_min_length(prepared_sort, config.minimum_segment_length);
+
+    let prepared_sort = if let Some(maximum_segment_length) = config.maximum_segment_length {
+        split_prepared_segments_at_max_length(prepared_sort, maximum_segment_length)
+    } else {
+        prepared_sort
+    };
+
+    let prepared_sort = if let Some(randomization_mode) = config.randomization_mode {
+        randomize_prepared_segments(
+            prepared_sort,
+            randomization_mode,
+            config.randomization_seed,
+        )
+    } else {
+        prepared_sort
+    };
+
+    Ok(prepared_sort)
+}
+
+
+/// A builder for a complete prepared pixel sort, which wraps preparing the sort,
+/// limiting the segment lengths, randomizing the segments and executing the sort
+/// into a single call to [`Self::run`].
+///
+/// Unless overridden, segments of pixels with a relative luminance between `0.25` and `0.75`
+/// are sorted horizontally by relative luminance (in ascending order),
+/// without any of the optional steps.
+///
+/// The free functions in [`crate::pixel_sorting::prepared`] remain available
+/// for finer control.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelSortBuilder {
+    config: PixelSortConfig,
+    region_of_interest: Option<ImageRegion>,
+}
+
+impl PixelSortBuilder {
+    pub fn new() -> Self {
+        Self {
+            config: PixelSortConfig {
+                selection_mode: PreparedSegmentSelectionMode::LuminanceRange {
+                    low: 0.25,
+                    high: 0.75,
+                    invert: false,
+                },
+                randomization_mode: None,
+                randomization_seed: None,
+                minimum_segment_length: 1,
+                maximum_segment_length: None,
+                sorting_mode: PreparedSegmentSortingMode::Luminance,
+                tiebreak_mode: None,
+                direction: ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+                premultiply_alpha: false,
+                output_only_segments: false,
+                stable_sort: false,
+            },
+            region_of_interest: None,
+        }
+    }
+
+    /// Creates a builder that sorts as described by an existing `config`
+    /// (e.g. one decoded from a share code or a preset).
+    pub fn from_config(config: PixelSortConfig) -> Self {
+        Self {
+            config,
+            region_of_interest: None,
+        }
+    }
+
+    /// Returns the configuration the builder currently describes.
+    pub fn config(&self) -> &PixelSortConfig {
+        &self.config
+    }
+
+    pub fn into_config(self) -> PixelSortConfig {
+        self.config
+    }
+
+    pub fn selection(mut self, selection_mode: PreparedSegmentSelectionMode) -> Self {
+        self.config.selection_mode = selection_mode;
+        self
+    }
+
+    pub fn sorting(mut self, sorting_mode: PreparedSegmentSortingMode) -> Self {
+        self.config.sorting_mode = sorting_mode;
+        self
+    }
+
+    /// Orders pixels with equal values of the [`Self::sorting`] mode, see [`prepare_pixel_sort`].
+    pub fn tiebreak(mut self, tiebreak_mode: PreparedSegmentSortingMode) -> Self {
+        self.config.tiebreak_mode = Some(tiebreak_mode);
+        self
+    }
+
+    pub fn direction(mut self, direction: ImageSortingDirection) -> Self {
+        self.config.direction = direction;
+        self
+    }
+
+    /// Randomly splits the segments, see [`randomize_prepared_segments`].
+    pub fn randomization(mut self, randomization_mode: SegmentRandomizationMode) -> Self {
+        self.config.randomization_mode = Some(randomization_mode);
+        self
+    }
+
+    /// Makes the [`Self::randomization`] reproducible. Without a seed,
+    /// a new random one is picked on every run.
+    pub fn randomization_seed(mut self, seed: u64) -> Self {
+        self.config.randomization_seed = Some(seed);
+        self
+    }
+
+    /// Leaves segments shorter than `minimum_segment_length` pixels unsorted,
+    /// see [`filter_prepared_segments_by_min_length`].
+    pub fn min_segment_length(mut self, minimum_segment_length: usize) -> Self {
+        self.config.minimum_segment_length = minimum_segment_length;
+        self
+    }
+
+    /// Splits segments longer than `maximum_segment_length` pixels,
+    /// see [`split_prepared_segments_at_max_length`].
+    pub fn max_segment_length(mut self, maximum_segment_length: usize) -> Self {
+        self.config.maximum_segment_length = Some(maximum_segment_length);
+        self
+    }
+
+    /// See the `premultiply_alpha` parameter of [`prepare_pixel_sort`].
+    pub fn premultiply_alpha(mut self, premultiply_alpha: bool) -> Self {
+        self.config.premultiply_alpha = premultiply_alpha;
+        self
+    }
+
+    /// See the `output_only_segments` parameter of [`execute_axis_aligned_prepared_pixel_sort`].
+    pub fn output_only_segments(mut self, output_only_segments: bool) -> Self {
+        self.config.output_only_segments = output_only_segments;
+        self
+    }
+
+    /// See the `stable_sort` parameter of [`execute_axis_aligned_prepared_pixel_sort`].
+    pub fn stable_sort(mut self, stable_sort: bool) -> Self {
+        self.config.stable_sort = stable_sort;
+        self
+    }
+
+    /// Only sorts the pixels inside `region` (see [`restrict_prepared_segments_to_region`]),
+    /// leaving the rest of the image as it is.
+    pub fn region_of_interest(mut self, region: ImageRegion) -> Self {
+        self.region_of_interest = Some(region);
+        self
+    }
+
+    /// Prepares the pixel sort of `image` (including the region of interest, the segment
+    /// length limits and randomization), without executing it yet.
+    ///
+    /// Fails under the same conditions as [`prepare_configured_pixel_sort`].
+    pub fn prepare(
+        &self,
+        image: RgbaImage,
+    ) -> Result<PreparedPixelSort<PreparedSortingContext>, PixelSortError> {
+        prepare_configured_pixel_sort(image, &self.config, self.region_of_interest)
+    }
+
+    /// Pixel sorts `image` as configured, returning the sorted image.
+    ///
+    /// Fails under the same conditions as [`Self::prepare`].
+    pub fn run(&self, image: RgbaImage) -> Result<RgbaImage, PixelSortError> {
+        Ok(execute_axis_aligned_prepared_pixel_sort(
+            self.prepare(image)?,
+            self.config.output_only_segments,
+            self.config.stable_sort,
+        ))
+    }
+}
+
+impl Default for PixelSortBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
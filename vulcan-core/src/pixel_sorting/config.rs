@@ -0,0 +1,257 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::pixel_sorting::{
+    ImageRegion,
+    ImageSortingDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        SegmentRandomizationMode,
+    },
+};
+
+
+/// The version byte prepended to every share code produced by [`PixelSortConfig::to_share_code`].
+///
+/// This must be bumped whenever the binary layout of [`PixelSortConfig`] changes
+/// in a backwards-incompatible way (e.g. when a field is added or reordered).
+/// Appending new enum variants does not change the layout of existing ones.
+pub const SHARE_CODE_VERSION: u8 = 14;
+
+/// The version written into every preset produced by [`ProcessingPreset::new`].
+///
+/// Like [`SHARE_CODE_VERSION`], this must be bumped whenever [`PixelSortConfig`] changes
+/// in a way that makes previously saved presets deserialize incorrectly (or not at all).
+pub const PRESET_VERSION: u32 = 1;
+
+/// The version written into every recipe produced by [`ProcessingRecipe::new`].
+///
+/// Must be bumped under the same conditions as [`PRESET_VERSION`]
+/// (or when [`RecordedOperation`] itself changes).
+pub const RECIPE_VERSION: u32 = 1;
+
+
+#[derive(Debug, Error)]
+pub enum ShareCodeError {
+    #[error("share code is empty")]
+    Empty,
+
+    #[error("share code is not valid base64")]
+    InvalidBase64 {
+        #[source]
+        error: base64::DecodeError,
+    },
+
+    #[error("share code has unsupported version {version} (expected {SHARE_CODE_VERSION})")]
+    UnsupportedVersion { version: u8 },
+
+    #[error("share code contents could not be decoded")]
+    InvalidPayload {
+        #[source]
+        error: bincode::Error,
+    },
+}
+
+
+#[derive(Debug, Error)]
+pub enum PresetError {
+    #[error("preset is not valid JSON or does not describe a pixel sort")]
+    InvalidJson {
+        #[source]
+        error: serde_json::Error,
+    },
+
+    #[error("preset has unsupported version {version} (expected {PRESET_VERSION})")]
+    UnsupportedVersion { version: u32 },
+}
+
+#[derive(Debug, Error)]
+pub enum RecipeError {
+    #[error("recipe is not valid JSON or does not describe a chain of pixel sorts")]
+    InvalidJson {
+        #[source]
+        error: serde_json::Error,
+    },
+
+    #[error("recipe has unsupported version {version} (expected {RECIPE_VERSION})")]
+    UnsupportedVersion { version: u32 },
+}
+
+
+/// A complete description of a prepared pixel sort: how segments are selected,
+/// how they are (optionally) randomized, and how they are sorted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PixelSortConfig {
+    pub selection_mode: PreparedSegmentSelectionMode,
+    pub randomization_mode: Option<SegmentRandomizationMode>,
+    pub randomization_seed: Option<u64>,
+
+    /// Prepared segments shorter than this many pixels are left unsorted, see
+    /// [`crate::pixel_sorting::prepared::filter_prepared_segments_by_min_length`].
+    pub minimum_segment_length: usize,
+
+    /// Prepared segments longer than this many pixels are split into consecutive segments, see
+    /// [`crate::pixel_sorting::prepared::split_prepared_segments_at_max_length`].
+    pub maximum_segment_length: Option<usize>,
+
+    pub sorting_mode: PreparedSegmentSortingMode,
+    pub tiebreak_mode: Option<PreparedSegmentSortingMode>,
+    pub direction: ImageSortingDirection,
+    pub premultiply_alpha: bool,
+    pub output_only_segments: bool,
+
+    /// Whether pixels with equal sorting keys keep their original order, see
+    /// [`crate::pixel_sorting::prepared::execute_axis_aligned_prepared_pixel_sort`].
+    #[serde(default)]
+    pub stable_sort: bool,
+}
+
+impl PixelSortConfig {
+    /// Encodes this configuration into a short, URL-safe base64 string that can be
+    /// pasted around (e.g. into a chat) and decoded with [`Self::from_share_code`].
+    ///
+    /// The code consists of a single version byte ([`SHARE_CODE_VERSION`]),
+    /// followed by the `bincode`-encoded configuration.
+    pub fn to_share_code(&self) -> String {
+        let mut share_code_bytes = vec![SHARE_CODE_VERSION];
+
+        bincode::serialize_into(&mut share_code_bytes, self)
+            // PANIC SAFETY: Serializing into a `Vec` can't fail, and the configuration
+            // contains no types that `bincode` can't represent.
+            .expect("failed to serialize pixel sort configuration");
+
+        URL_SAFE_NO_PAD.encode(share_code_bytes)
+    }
+
+    /// Decodes a configuration from a share code produced by [`Self::to_share_code`].
+    ///
+    /// Surrounding whitespace is ignored. Share codes with a version other than
+    /// [`SHARE_CODE_VERSION`] are rejected with [`ShareCodeError::UnsupportedVersion`].
+    pub fn from_share_code(share_code: &str) -> Result<Self, ShareCodeError> {
+        let share_code_bytes = URL_SAFE_NO_PAD
+            .decode(share_code.trim())
+            .map_err(|error| ShareCodeError::InvalidBase64 { error })?;
+
+        let Some((&version, payload)) = share_code_bytes.split_first() else {
+            return Err(ShareCodeError::Empty);
+        };
+
+        if version != SHARE_CODE_VERSION {
+            return Err(ShareCodeError::UnsupportedVersion { version });
+        }
+
+        bincode::deserialize(payload).map_err(|error| ShareCodeError::InvalidPayload { error })
+    }
+}
+
+
+/// A [`PixelSortConfig`] that can be saved to (and loaded from) a human-readable JSON file,
+/// so that a set of parameters can be reused later or shared.
+///
+/// Like with share codes, the selection mask of [`PreparedSegmentSelectionMode::MaskImage`]
+/// is not part of a preset, so it has to be loaded separately.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessingPreset {
+    /// The version of the preset format, see [`PRESET_VERSION`].
+    pub version: u32,
+
+    pub config: PixelSortConfig,
+}
+
+impl ProcessingPreset {
+    pub fn new(config: PixelSortConfig) -> Self {
+        Self {
+            version: PRESET_VERSION,
+            config,
+        }
+    }
+
+    /// Encodes this preset into pretty-printed JSON that can be decoded with [`Self::from_json`].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self)
+            // PANIC SAFETY: The preset contains no maps with non-string keys
+            // (or other types that can't be represented in JSON).
+            .expect("failed to serialize processing preset")
+    }
+
+    /// Decodes a preset from JSON produced by [`Self::to_json`].
+    ///
+    /// Presets with a version other than [`PRESET_VERSION`] are rejected
+    /// with [`PresetError::UnsupportedVersion`].
+    pub fn from_json(json: &str) -> Result<Self, PresetError> {
+        let VersionOnly { version } =
+            serde_json::from_str(json).map_err(|error| PresetError::InvalidJson { error })?;
+
+        if version != PRESET_VERSION {
+            return Err(PresetError::UnsupportedVersion { version });
+        }
+
+        serde_json::from_str(json).map_err(|error| PresetError::InvalidJson { error })
+    }
+}
+
+
+/// A single pixel sort that was applied to an image, see [`ProcessingRecipe`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedOperation {
+    pub config: PixelSortConfig,
+
+    /// The region the sort was restricted to, if any. Given in pixels of the image
+    /// the operation was recorded on; pixels outside of the sorted image are ignored.
+    pub region_of_interest: Option<ImageRegion>,
+}
+
+
+/// An ordered chain of pixel sorts that can be saved to (and loaded from) a JSON file,
+/// so that a multi-pass result can be reproduced, e.g. on another image.
+///
+/// Like presets (see [`ProcessingPreset`]), recipes don't contain selection masks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessingRecipe {
+    /// The version of the recipe format, see [`RECIPE_VERSION`].
+    pub version: u32,
+
+    /// The recorded operations, in the order they are to be applied in.
+    pub operations: Vec<RecordedOperation>,
+}
+
+impl ProcessingRecipe {
+    pub fn new(operations: Vec<RecordedOperation>) -> Self {
+        Self {
+            version: RECIPE_VERSION,
+            operations,
+        }
+    }
+
+    /// Encodes this recipe into pretty-printed JSON that can be decoded with [`Self::from_json`].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self)
+            // PANIC SAFETY: See `ProcessingPreset::to_json`.
+            .expect("failed to serialize processing recipe")
+    }
+
+    /// Decodes a recipe from JSON produced by [`Self::to_json`].
+    ///
+    /// Recipes with a version other than [`RECIPE_VERSION`] are rejected
+    /// with [`RecipeError::UnsupportedVersion`].
+    pub fn from_json(json: &str) -> Result<Self, RecipeError> {
+        let VersionOnly { version } =
+            serde_json::from_str(json).map_err(|error| RecipeError::InvalidJson { error })?;
+
+        if version != RECIPE_VERSION {
+            return Err(RecipeError::UnsupportedVersion { version });
+        }
+
+        serde_json::from_str(json).map_err(|error| RecipeError::InvalidJson { error })
+    }
+}
+
+
+/// Only the version of a preset or recipe, so that it can be checked before the rest
+/// (whose layout may differ between versions) is deserialized.
+#[derive(Deserialize)]
+struct VersionOnly {
+    version: u32,
+}
@@ -1,6 +1,4 @@
-use std::cmp::Ordering;
-
-use image::{Rgba, RgbaImage, flat::SampleLayout};
+use image::{ImageBuffer, Pixel, Primitive, Rgba, flat::SampleLayout};
 use rayon::prelude::*;
 
 use crate::pixel_sorting::{
@@ -8,13 +6,19 @@ use crate::pixel_sorting::{
     PixelSegmentScannerState,
     PixelSegmentSortDirection,
     PixelWithContext,
+    angled::AngledLineLayout,
     properties::{
-        compute_rgba_hsl_hue,
+        compute_rgba_hsl_hue_gamma,
         compute_rgba_hsl_saturation,
         compute_rgba_relative_luminance,
+        is_hue_in_range,
     },
     retrieve_rgba_pixel_from_flat_samples,
-    sorting::sort_with_closure_and_reapply_pixel_segment,
+    sorting::{
+        compare_numeric_sorting_contexts,
+        sort_by_with_stability,
+        sort_with_closure_and_reapply_pixel_segment,
+    },
 };
 
 pub enum ImmediateSegmentSelectionMode {
@@ -32,6 +36,7 @@ pub enum ImmediateSegmentSelectionMode {
         high: f32,
     },
 
+    /// If `low` is larger than `high`, the hue range wraps around 360 degrees.
     HueRange {
         /// The inclusive low end of the hue range (`0.0..360.0`).
         low: f32,
@@ -62,49 +67,77 @@ pub enum ImmediateSegmentSelectionMode {
 
 pub struct PixelSortOptions {
     pub direction: ImageSortingDirection,
+
+    /// Whether pixels with equal sorting keys keep their original order within a segment,
+    /// which makes the result reproducible at some cost in performance
+    /// (see [`sort_array_of_numeric_contextual_pixels_by_direction`]).
+    pub stable_sort: bool,
 }
 
 /// Pixel sorts the given `image`.
 ///
+/// The image can have any RGBA sample type (e.g. an [`RgbaImage`][image::RgbaImage]
+/// or a 16-bit-per-channel RGBA image); the sorted image keeps the same sample type.
+///
 /// TODO document
-pub fn perform_pixel_sort(
-    image: RgbaImage,
+pub fn perform_pixel_sort<S>(
+    image: ImageBuffer<Rgba<S>, Vec<S>>,
     method: ImmediateSegmentSelectionMode,
     options: PixelSortOptions,
-) -> RgbaImage {
+) -> ImageBuffer<Rgba<S>, Vec<S>>
+where
+    S: Primitive + Send + Sync + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+{
+    // Images without any pixels have nothing to sort (their rows can't even be chunked).
+    if image.width() == 0 || image.height() == 0 {
+        return image;
+    }
+
+    let stable_sort = options.stable_sort;
+    let sort_segment = move |pixels_in_segment: &mut [PixelWithContext<f32, S>],
+                             sorting_direction: PixelSegmentSortDirection| {
+        sort_array_of_numeric_contextual_pixels_by_direction(
+            pixels_in_segment,
+            sorting_direction,
+            stable_sort,
+        )
+    };
+
     match method {
         ImmediateSegmentSelectionMode::LuminanceRange { low, high } => {
             let relative_luminance_range = low..=high;
 
-            perform_axis_aligned_generic_pixel_sort(
+            perform_custom_pixel_sort(
                 image,
-                options,
-                |pixel: &Rgba<u8>| -> f32 { compute_rgba_relative_luminance(pixel) },
-                |pixel: &PixelWithContext<f32>| -> bool {
+                options.direction,
+                |pixel: &Rgba<S>| -> f32 { compute_rgba_relative_luminance(pixel) },
+                |pixel: &PixelWithContext<f32, S>| -> bool {
                     relative_luminance_range.contains(&pixel.context)
                 },
+                sort_segment,
             )
         }
-        ImmediateSegmentSelectionMode::HueRange { low, high } => {
-            let hue_range = low..=high;
-
-            perform_axis_aligned_generic_pixel_sort(
-                image,
-                options,
-                |pixel: &Rgba<u8>| -> f32 { compute_rgba_hsl_hue(pixel) },
-                |pixel: &PixelWithContext<f32>| -> bool { hue_range.contains(&pixel.context) },
-            )
-        }
+        ImmediateSegmentSelectionMode::HueRange { low, high } => perform_custom_pixel_sort(
+            image,
+            options.direction,
+            |pixel: &Rgba<S>| -> f32 { compute_rgba_hsl_hue_gamma(pixel) },
+            |pixel: &PixelWithContext<f32, S>| -> bool {
+                is_hue_in_range(pixel.context, low, high)
+            },
+            sort_segment,
+        ),
         ImmediateSegmentSelectionMode::SaturationRange { low, high } => {
             let saturation_range = low..=high;
 
-            perform_axis_aligned_generic_pixel_sort(
+            perform_custom_pixel_sort(
                 image,
-                options,
-                |pixel: &Rgba<u8>| -> f32 { compute_rgba_hsl_saturation(pixel) },
-                |context: &PixelWithContext<f32>| -> bool {
+                options.direction,
+                |pixel: &Rgba<S>| -> f32 { compute_rgba_hsl_saturation(pixel) },
+                |context: &PixelWithContext<f32, S>| -> bool {
                     saturation_range.contains(&context.context)
                 },
+                sort_segment,
             )
         }
     }
@@ -117,23 +150,30 @@ pub fn perform_pixel_sort(
 /// Sorting is performed in-place on `image_contiguous_flat_buffer`, and in parallel (using `rayon`).
 ///
 /// # Invariants
-/// - `image_contiguous_flat_buffer` must be an RGBA8 buffer.
+/// - `image_contiguous_flat_buffer` must be an RGBA buffer (e.g. RGBA8 or RGBA16).
 /// - `image_contiguous_flat_buffer` must point to a single row of the image.
 /// - `relative_luminance_range` must not be outside of the range `0.0..=1.0`
 ///   (i.e. cannot start below zero end above one).
-fn perform_generic_pixel_sort_on_image_row<C, ContextClosure, MembershipClosure, SortingClosure>(
-    // Should point to a single row or column of the image as a flat RGBA8 sample buffer.
-    image_contiguous_flat_buffer: &mut [u8],
+fn perform_generic_pixel_sort_on_image_row<
+    C,
+    S,
+    ContextClosure,
+    MembershipClosure,
+    SortingClosure,
+>(
+    // Should point to a single row or column of the image as a flat RGBA sample buffer.
+    image_contiguous_flat_buffer: &mut [S],
     image_layout: SampleLayout,
     pixel_context_computation_closure: ContextClosure,
     segment_membership_closure: MembershipClosure,
     mut segment_sorting_closure: SortingClosure,
 ) where
-    ContextClosure: Fn(&Rgba<u8>) -> C,
-    MembershipClosure: Fn(&PixelWithContext<C>) -> bool,
-    SortingClosure: FnMut(&mut [PixelWithContext<C>]),
+    S: Primitive,
+    ContextClosure: Fn(&Rgba<S>) -> C,
+    MembershipClosure: Fn(&PixelWithContext<C, S>) -> bool,
+    SortingClosure: FnMut(&mut [PixelWithContext<C, S>]),
 {
-    let mut current_state: PixelSegmentScannerState<PixelWithContext<C>> =
+    let mut current_state: PixelSegmentScannerState<PixelWithContext<C, S>> =
         PixelSegmentScannerState::OutsideSortableSegment;
 
     let image_channel_stride = image_layout.channel_stride;
@@ -239,50 +279,74 @@ fn perform_generic_pixel_sort_on_image_row<C, ContextClosure, MembershipClosure,
 /// Given the mutable slice `pixels_in_segment`, this function
 /// sorts the pixels in-place by sorting their numeric context
 /// (generic `C`; must be a number) in the provided `sorting_direction`.
-fn sort_array_of_numeric_contextual_pixels_by_direction<C>(
-    pixels_in_segment: &mut [PixelWithContext<C>],
+///
+/// This is the segment sorting closure used by [`perform_pixel_sort`], and can be used
+/// in [`perform_custom_pixel_sort`] (wrapped in a closure that picks `stable`)
+/// when the pixel context is numeric.
+///
+/// NaN contexts are sorted as the minimum, i.e. before all other pixels when sorting
+/// in ascending order, and after them when sorting in descending order.
+///
+/// If `stable` is set, pixels with equal contexts keep their original (spatial) order,
+/// otherwise the faster unstable sort is used, which can reorder them arbitrarily.
+pub fn sort_array_of_numeric_contextual_pixels_by_direction<C, S>(
+    pixels_in_segment: &mut [PixelWithContext<C, S>],
     sorting_direction: PixelSegmentSortDirection,
+    stable: bool,
 ) where
     C: num::Num + Copy + PartialOrd,
+    S: Primitive,
 {
     match sorting_direction {
         PixelSegmentSortDirection::Ascending => {
-            pixels_in_segment.sort_unstable_by(|first, second| {
-                first
-                    .context
-                    .partial_cmp(&second.context)
-                    .unwrap_or(Ordering::Equal)
+            sort_by_with_stability(pixels_in_segment, stable, |first, second| {
+                compare_numeric_sorting_contexts(&first.context, &second.context)
             });
         }
         PixelSegmentSortDirection::Descending => {
-            pixels_in_segment.sort_unstable_by(|first, second| {
-                second
-                    .context
-                    .partial_cmp(&first.context)
-                    .unwrap_or(Ordering::Equal)
+            sort_by_with_stability(pixels_in_segment, stable, |first, second| {
+                compare_numeric_sorting_contexts(&second.context, &first.context)
             });
         }
+        PixelSegmentSortDirection::Reverse => {
+            pixels_in_segment.reverse();
+        }
     }
 }
 
-// TODO document
-fn perform_axis_aligned_generic_pixel_sort<PixelProperty, PropertyClosure, MembershipClosure>(
-    mut image: RgbaImage,
-    options: PixelSortOptions,
-    pixel_context_computation_closure: PropertyClosure,
+/// Pixel sorts the given `image` in the given `direction` using caller-provided closures,
+/// which allows for custom segment selection criteria (e.g. "pixels near a target color").
+///
+/// - `pixel_context_computation_closure` computes the context (e.g. some property) of each pixel,
+/// - `segment_membership_closure` decides whether a pixel (with its context) belongs
+///   inside a sorted segment; continuous runs of such pixels form the segments, and
+/// - `segment_sorting_closure` sorts the pixels of a single segment in-place, given the
+///   [`PixelSegmentSortDirection`] from `direction` (see
+///   [`sort_array_of_numeric_contextual_pixels_by_direction`] for numeric contexts).
+///
+/// Sorting is performed in parallel (using `rayon`), which is why the closures
+/// must be [`Sync`] and [`Send`]. Like [`perform_pixel_sort`], this works on RGBA images
+/// of any sample type (e.g. RGBA8 or RGBA16).
+pub fn perform_custom_pixel_sort<C, S, ContextClosure, MembershipClosure, SortingClosure>(
+    mut image: ImageBuffer<Rgba<S>, Vec<S>>,
+    direction: ImageSortingDirection,
+    pixel_context_computation_closure: ContextClosure,
     segment_membership_closure: MembershipClosure,
-) -> RgbaImage
+    segment_sorting_closure: SortingClosure,
+) -> ImageBuffer<Rgba<S>, Vec<S>>
 where
-    PixelProperty: num::Num + Copy + PartialOrd,
-    PropertyClosure: Fn(&Rgba<u8>) -> PixelProperty + Sync + Send,
-    MembershipClosure: Fn(&PixelWithContext<PixelProperty>) -> bool + Sync + Send,
+    S: Primitive + Send + Sync + 'static,
+    Rgba<S>: Pixel<Subpixel = S>,
+    ContextClosure: Fn(&Rgba<S>) -> C + Sync + Send,
+    MembershipClosure: Fn(&PixelWithContext<C, S>) -> bool + Sync + Send,
+    SortingClosure: Fn(&mut [PixelWithContext<C, S>], PixelSegmentSortDirection) + Sync + Send,
 {
-    match options.direction {
+    match direction {
         ImageSortingDirection::Horizontal(horizontal_direction) => {
-            // For performance reasons, we'll operate directly on the underlying RGBA8 image buffer.
+            // For performance reasons, we'll operate directly on the underlying RGBA image buffer.
             let mut flat_samples = image.as_flat_samples_mut();
 
-            // This is known to us, since we are expecting RGBA8.
+            // This is known to us, since we are expecting RGBA.
             // Still, we'll use the values from the `layout` struct directly from here on.
             assert!(!flat_samples.has_aliased_samples());
             assert!(flat_samples.layout.channel_stride == 1);
@@ -302,24 +366,19 @@ where
                     image_layout,
                     &pixel_context_computation_closure,
                     &segment_membership_closure,
-                    |pixel_segment| {
-                        sort_array_of_numeric_contextual_pixels_by_direction(
-                            pixel_segment,
-                            horizontal_direction,
-                        );
-                    },
+                    |pixel_segment| segment_sorting_closure(pixel_segment, horizontal_direction),
                 );
             });
         }
         ImageSortingDirection::Vertical(vertical_direction) => {
             let mut rotated_image = image::imageops::rotate90(&image);
 
-            // For performance reasons, we'll operate directly on the underlying RGBA8 image buffer.
+            // For performance reasons, we'll operate directly on the underlying RGBA image buffer.
             // The rows of this buffer correspond to columns in the original image
             // (we just rotated our source image by 90 degrees and we'll do the inverse afterwards).
             let mut flat_samples = rotated_image.as_flat_samples_mut();
 
-            // This is known to us, since we are expecting RGBA8.
+            // This is known to us, since we are expecting RGBA.
             // Still, we'll use the values from the `layout` struct directly from here on.
             assert!(!flat_samples.has_aliased_samples());
             assert!(flat_samples.layout.channel_stride == 1);
@@ -339,12 +398,7 @@ where
                     image_layout,
                     &pixel_context_computation_closure,
                     &segment_membership_closure,
-                    |pixel_segment| {
-                        sort_array_of_numeric_contextual_pixels_by_direction(
-                            pixel_segment,
-                            vertical_direction,
-                        );
-                    },
+                    |pixel_segment| segment_sorting_closure(pixel_segment, vertical_direction),
                 );
             });
 
@@ -353,6 +407,31 @@ where
             image::imageops::rotate270_in(&rotated_image, &mut image)
                 .expect("unexpected failure while inversing the image rotation");
         }
+        ImageSortingDirection::Angled {
+            degrees,
+            direction: line_direction,
+        } => {
+            let line_layout = AngledLineLayout::new(image.width(), image.height(), degrees);
+
+            // Each line along the sorting angle is copied into its own contiguous buffer,
+            // which is then sorted like a single image row of the same width.
+            let mut line_buffers = line_layout.gather_line_buffers(&image);
+
+            line_buffers.par_iter_mut().for_each(|line_buffer| {
+                let line_buffer_layout =
+                    SampleLayout::row_major_packed(4, (line_buffer.len() / 4) as u32, 1);
+
+                perform_generic_pixel_sort_on_image_row(
+                    line_buffer,
+                    line_buffer_layout,
+                    &pixel_context_computation_closure,
+                    &segment_membership_closure,
+                    |pixel_segment| segment_sorting_closure(pixel_segment, line_direction),
+                );
+            });
+
+            line_layout.scatter_line_buffers(&line_buffers, &mut image);
+        }
     }
 
     image
@@ -1,5 +1,10 @@
-use image::{Rgba, flat::SampleLayout};
+use image::{Primitive, Rgba, flat::SampleLayout};
+use serde::{Deserialize, Serialize};
 
+mod angled;
+pub mod builder;
+pub mod config;
+pub mod grayscale;
 pub mod immediate;
 pub mod prepared;
 pub mod properties;
@@ -7,19 +12,75 @@ mod sorting;
 
 /// Describes the direction in which a continuous segment of pixels is sorted;
 /// either ascending or descending in regards to some underlying pixel property (set separately).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PixelSegmentSortDirection {
     Ascending,
     Descending,
+
+    /// Reverses the order of pixels in each segment without comparing any pixel property,
+    /// producing a mirror image within each segment.
+    Reverse,
+}
+
+/// Describes how the pixels of a segment are laid out after being sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PixelSegmentArrangement {
+    /// Sorted pixels are laid out from the start of the segment to its end.
+    Linear,
+
+    /// Sorted pixels ramp from both ends of the segment toward its center, i.e. the first
+    /// sorted pixels end up at the ends of the segment and the last ones in its center.
+    TowardCenter,
+
+    /// Sorted pixels ramp from the center of the segment toward both of its ends, i.e. the first
+    /// sorted pixels end up in the center of the segment and the last ones at its ends.
+    AwayFromCenter,
 }
 
 /// The direction of pixel sorting.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ImageSortingDirection {
     /// Horizontal pixel sorting, either left-to-right or right-to-left.
     Horizontal(PixelSegmentSortDirection),
 
     /// Vertical pixel sorting, either top-to-bototm or bottom-to-top.
     Vertical(PixelSegmentSortDirection),
+
+    /// Pixel sorting along straight lines at an arbitrary angle.
+    ///
+    /// The pixels of the image are partitioned into parallel, one pixel wide lines
+    /// without any resampling, so no pixel is lost or blurred. An angle of `0` degrees
+    /// is equivalent to [`Self::Horizontal`] and an angle of `90` degrees to [`Self::Vertical`].
+    Angled {
+        /// The angle of the lines, in degrees counter-clockwise from the positive x axis
+        /// (i.e. `0` degrees runs left to right and `90` degrees runs bottom to top).
+        degrees: f32,
+
+        /// The direction in which the selected underlying pixel property will be sorted
+        /// along each line.
+        direction: PixelSegmentSortDirection,
+    },
+}
+
+/// A rectangular region of an image, in pixels (e.g. a region of interest to restrict
+/// a pixel sort to, see [`prepared::restrict_prepared_segments_to_region`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageRegion {
+    /// The column of the left edge of the region.
+    pub x: u32,
+
+    /// The row of the top edge of the region.
+    pub y: u32,
+
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageRegion {
+    /// Returns `true` if the pixel at the given coordinates lies inside this region.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
 }
 
 /// A small internal enum containing pixel segment scanning state.
@@ -52,18 +113,22 @@ enum PixelSegmentScannerState<P> {
     },
 }
 
-/// Returns data about a single RGBA pixel ([`Rgba`]`<`[`u8`]`>`) at some specific pixel index
-/// in the given `flat_slice` of the image.
+/// Returns data about a single RGBA pixel ([`Rgba`]`<S>`, e.g. [`Rgba`]`<`[`u8`]`>`)
+/// at some specific pixel index in the given `flat_slice` of the image.
 ///
 /// # Invariants
-/// - The `flat_slice` must be the flat sample buffer of an RGBA8 image.
+/// - The `flat_slice` must be the flat sample buffer of an RGBA image
+///   (e.g. RGBA8 or RGBA16).
 #[inline(always)]
-fn retrieve_rgba_pixel_from_flat_samples(
-    flat_slice: &[u8],
+fn retrieve_rgba_pixel_from_flat_samples<S>(
+    flat_slice: &[S],
     pixel_index: usize,
     channel_stride: usize,
     num_channels: usize,
-) -> Rgba<u8> {
+) -> Rgba<S>
+where
+    S: Primitive,
+{
     Rgba([
         flat_slice[pixel_index * channel_stride * num_channels],
         flat_slice[pixel_index * channel_stride * num_channels + channel_stride],
@@ -83,22 +148,31 @@ fn retrieve_starting_rgba_pixel_from_flat_samples(flat_slice: &[u8]) -> Rgba<u8>
 }
 
 /// An internal struct that carries contextual information (e.g. relative luminance)
-/// alongside the actual [`Rgba`]`<`[`u8`]`>` pixel value.
+/// alongside the actual [`Rgba`]`<S>` pixel value (by default [`Rgba`]`<`[`u8`]`>`).
 #[derive(Debug, Clone, PartialEq)]
-pub struct PixelWithContext<C> {
-    pub pixel: Rgba<u8>,
+pub struct PixelWithContext<C, S = u8>
+where
+    S: Primitive,
+{
+    pub pixel: Rgba<S>,
     pub context: C,
 }
 
-impl<C> PixelWithContext<C> {
+impl<C, S> PixelWithContext<C, S>
+where
+    S: Primitive,
+{
     #[inline(always)]
-    pub fn new(pixel: Rgba<u8>, context: C) -> Self {
+    pub fn new(pixel: Rgba<S>, context: C) -> Self {
         Self { pixel, context }
     }
 }
 
-impl<C> AsRef<Rgba<u8>> for PixelWithContext<C> {
-    fn as_ref(&self) -> &Rgba<u8> {
+impl<C, S> AsRef<Rgba<S>> for PixelWithContext<C, S>
+where
+    S: Primitive,
+{
+    fn as_ref(&self) -> &Rgba<S> {
         &self.pixel
     }
 }
@@ -122,19 +196,20 @@ impl<'p, C> AsRef<Rgba<u8>> for PixelRefWithContext<'p, C> {
     }
 }
 
-/// Given a `Vec` of pixels and a contiguous RGBA8 image buffer,
+/// Given a `Vec` of pixels and a contiguous RGBA image buffer (e.g. RGBA8 or RGBA16),
 /// this function will copy the pixels one after another onto that buffer,
 /// overwriting the previous pixel values.
 ///
 /// # Panics
 /// The length of `target_image_contiguous_flat_buffer` must be precisely large
 /// enough to fit all the `source_pixels`; the function will otherwise panic.
-fn copy_pixel_segment_onto_image<P>(
+fn copy_pixel_segment_onto_image<P, S>(
     source_pixels: Vec<P>,
-    target_contiguous_flat_buffer: &mut [u8],
+    target_contiguous_flat_buffer: &mut [S],
     target_layout: SampleLayout,
 ) where
-    P: AsRef<Rgba<u8>>,
+    P: AsRef<Rgba<S>>,
+    S: Primitive,
 {
     assert!(
         source_pixels.len() * target_layout.channel_stride * target_layout.channels as usize
@@ -1,31 +1,190 @@
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
-use image::{DynamicImage, GrayImage, Rgba, RgbaImage, flat::SampleLayout};
-use rand::prelude::Distribution;
+use image::{DynamicImage, GrayImage, ImageBuffer, Luma, Rgba, RgbaImage, flat::SampleLayout};
+use rand::{Rng, SeedableRng, prelude::Distribution};
+use rand_chacha::ChaCha8Rng;
 use rand_distr::{Normal, Uniform};
-use rayon::prelude::{IndexedParallelIterator, ParallelIterator, ParallelSlice, ParallelSliceMut};
+use rayon::prelude::{
+    IndexedParallelIterator,
+    IntoParallelIterator,
+    IntoParallelRefIterator,
+    ParallelIterator,
+    ParallelSlice,
+    ParallelSliceMut,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::pixel_sorting::{
+    ImageRegion,
     ImageSortingDirection,
+    PixelSegmentArrangement,
     PixelSegmentScannerState,
     PixelSegmentSortDirection,
     PixelWithContext,
+    angled::AngledLineLayout,
     properties::{
-        compute_rgba_hsl_hue,
+        DistanceMetric,
+        LuminanceHistogram,
+        LuminanceWeights,
+        compute_rgba_color_distance,
+        compute_rgba_hsl_hue_gamma,
+        compute_rgba_hsl_lightness,
         compute_rgba_hsl_saturation,
+        compute_rgba_normalized_alpha,
+        compute_rgba_oklab_chroma,
+        compute_rgba_oklab_hue,
+        compute_rgba_oklab_lightness,
         compute_rgba_relative_luminance,
+        compute_rgba_weighted_luminance,
+        is_hue_in_range,
     },
     retrieve_rgba_pixel_from_flat_samples,
     retrieve_starting_rgba_pixel_from_flat_samples,
-    sorting::sort_with_numeric_context_and_reapply_pixel_segment,
+    sorting::{
+        reverse_pixel_segment_in_place,
+        sort_with_numeric_context_and_reapply_pixel_segment,
+    },
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PreparedSegmentSortingMode {
     Luminance,
     Hue,
     Saturation,
+    Lightness,
+
+    /// Sorts by the raw red channel value (`0.0..=255.0`).
+    Red,
+
+    /// Sorts by the raw green channel value (`0.0..=255.0`).
+    Green,
+
+    /// Sorts by the raw blue channel value (`0.0..=255.0`).
+    Blue,
+
+    /// Sorts by the alpha channel value, normalized to `0.0..=1.0`.
+    Alpha,
+
+    /// Sorts by the distance to the `target` color, as measured by `distance_metric`
+    /// (see [`compute_rgba_color_distance`], `0.0..=1.0`).
+    ColorDistance {
+        #[serde(with = "rgba_serde")]
+        target: Rgba<u8>,
+
+        #[serde(default)]
+        distance_metric: DistanceMetric,
+    },
+
+    /// Sorts by the perceptually uniform OKLab lightness (`0.0..=1.0`).
+    OklabLightness,
+
+    /// Sorts by the OKLab chroma (roughly `0.0..=0.33`).
+    OklabChroma,
+
+    /// Sorts by the OKLab hue (`0.0..360.0`).
+    OklabHue,
+
+    /// Sorts by the luminance computed with custom `weights`
+    /// (see [`compute_rgba_weighted_luminance`]), e.g. [`LuminanceWeights::REC_601`].
+    /// [`Self::Luminance`] always uses [`LuminanceWeights::REC_709`].
+    WeightedLuminance {
+        weights: LuminanceWeights,
+    },
+}
+
+impl PreparedSegmentSortingMode {
+    /// Computes the pixel property this sorting mode sorts by.
+    pub fn compute_sorting_value(self, pixel: &Rgba<u8>) -> f32 {
+        match self {
+            Self::Luminance => compute_rgba_relative_luminance(pixel),
+            Self::Hue => compute_rgba_hsl_hue_gamma(pixel),
+            Self::Saturation => compute_rgba_hsl_saturation(pixel),
+            Self::Lightness => compute_rgba_hsl_lightness(pixel),
+            Self::Red => pixel.0[0] as f32,
+            Self::Green => pixel.0[1] as f32,
+            Self::Blue => pixel.0[2] as f32,
+            Self::Alpha => compute_rgba_normalized_alpha(pixel),
+            Self::ColorDistance {
+                target,
+                distance_metric,
+            } => compute_rgba_color_distance(pixel, &target, distance_metric),
+            Self::OklabLightness => compute_rgba_oklab_lightness(pixel),
+            Self::OklabChroma => compute_rgba_oklab_chroma(pixel),
+            Self::OklabHue => compute_rgba_oklab_hue(pixel),
+            Self::WeightedLuminance { weights } => compute_rgba_weighted_luminance(pixel, weights),
+        }
+    }
+}
+
+/// (De)serializes an [`Rgba`]`<`[`u8`]`>` as its four channel values,
+/// since `image` doesn't implement `serde` traits for its pixel types.
+mod rgba_serde {
+    use image::Rgba;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S>(pixel: &Rgba<u8>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        pixel.0.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Rgba<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <[u8; 4]>::deserialize(deserializer).map(Rgba)
+    }
+}
+
+
+/// The sorting context of prepared pixel sorts: a primary sorting value and a tiebreak value,
+/// compared lexicographically. When no tiebreak mode is set, the tiebreak value is always zero.
+pub type PreparedSortingContext = (f32, f32);
+
+fn compute_tiebreak_sorting_value(
+    pixel: &Rgba<u8>,
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+) -> f32 {
+    tiebreak_mode.map_or(0.0, |tiebreak_mode| {
+        tiebreak_mode.compute_sorting_value(pixel)
+    })
+}
+
+/// Combines the `sorting_value` of the `pixel` with its tiebreak value into its sorting context.
+///
+/// NaN values are replaced with negative infinity, i.e. they sort as the minimum. Sorting contexts
+/// are compared with `partial_cmp`, and NaNs would make the comparisons inconsistent,
+/// which unstable sorting can turn into nondeterministically scrambled segments.
+fn build_sorting_context(
+    sorting_value: f32,
+    pixel: &Rgba<u8>,
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+) -> PreparedSortingContext {
+    let replace_nan = |value: f32| {
+        if value.is_nan() {
+            f32::NEG_INFINITY
+        } else {
+            value
+        }
+    };
+
+    let tiebreak_value = compute_tiebreak_sorting_value(pixel, tiebreak_mode);
+
+    (
+        replace_nan(sorting_value),
+        replace_nan(tiebreak_value),
+    )
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PreparedSegmentSelectionMode {
     /// This mode creates pixel sorting segments that consist *only* of
     /// continuous pixels whose relative luminance[^relative-luminance]
@@ -39,14 +198,59 @@ pub enum PreparedSegmentSelectionMode {
 
         /// The inclusive high end of the relative luminance range (`0.0..=1.0`).
         high: f32,
+
+        /// Whether to select the pixels *outside* of the range instead,
+        /// e.g. everything except the mid-tones.
+        #[serde(default)]
+        invert: bool,
+    },
+
+    /// Like [`Self::LuminanceRange`], but `low` and `high` are percentiles of the image's
+    /// relative luminance distribution instead of absolute luminances, e.g. `50.0` to `100.0`
+    /// always selects the brighter half of the pixels, regardless of how bright the image is.
+    ///
+    /// The percentiles are converted to absolute luminances in [`prepare_pixel_sort`]
+    /// (see [`LuminanceHistogram::luminance_range_at_percentiles`]).
+    LuminancePercentileRange {
+        /// The inclusive low end of the percentile range (`0.0..=100.0`).
+        low: f32,
+
+        /// The inclusive high end of the percentile range (`0.0..=100.0`).
+        high: f32,
+
+        /// See [`Self::LuminanceRange::invert`].
+        #[serde(default)]
+        invert: bool,
+    },
+
+    /// Like [`Self::LuminanceRange`], but the luminance is computed with custom `weights`
+    /// (see [`compute_rgba_weighted_luminance`]) instead of [`LuminanceWeights::REC_709`].
+    WeightedLuminanceRange {
+        weights: LuminanceWeights,
+
+        /// The inclusive low end of the weighted luminance range (`0.0..=1.0`).
+        low: f32,
+
+        /// The inclusive high end of the weighted luminance range (`0.0..=1.0`).
+        high: f32,
+
+        /// See [`Self::LuminanceRange::invert`].
+        #[serde(default)]
+        invert: bool,
     },
 
+    /// If `low` is larger than `high`, the hue range wraps around 360 degrees
+    /// (e.g. `350.0` to `20.0` selects reds on both sides of zero).
     HueRange {
         /// The inclusive low end of the hue range (`0.0..360.0`).
         low: f32,
 
         /// The inclusive high end of the hue range (`0.0..360.0`).
         high: f32,
+
+        /// See [`Self::LuminanceRange::invert`].
+        #[serde(default)]
+        invert: bool,
     },
 
     SaturationRange {
@@ -55,22 +259,389 @@ pub enum PreparedSegmentSelectionMode {
 
         /// The inclusive high end of the saturation range (`0.0..=1.0`).
         high: f32,
+
+        /// See [`Self::LuminanceRange::invert`].
+        #[serde(default)]
+        invert: bool,
+    },
+
+    /// This mode creates pixel sorting segments that consist *only* of
+    /// continuous pixels whose alpha (normalized to `0.0..=1.0`) is between
+    /// `low` and `high` (both inclusive), e.g. to sort only semi-transparent regions.
+    AlphaRange {
+        /// The inclusive low end of the alpha range (`0.0..=1.0`).
+        low: f32,
+
+        /// The inclusive high end of the alpha range (`0.0..=1.0`).
+        high: f32,
     },
 
     CannyEdges {
-        /// The inclusive low end of the Canny edge detection threshold (`0.0..=1140.39`, see [`canny`][imageproc::edges::canny]).
+        /// The inclusive low end of the Canny edge detection threshold (`0.1..=1140.39`, see [`canny`][imageproc::edges::canny]).
+        /// Out-of-range values are clamped.
         low: f32,
 
-        /// The inclusive high end of the Canny edge detection threshold (`0.0..=1140.39`, see [`canny`][imageproc::edges::canny]).
+        /// The inclusive high end of the Canny edge detection threshold (`0.1..=1140.39`, see [`canny`][imageproc::edges::canny]).
+        /// Out-of-range values are clamped, and values below `low` are raised to `low`.
         high: f32,
 
         /// Whether the first sortable pixel segment starts on the edge of the image, or at the first detected edge inside the image.
         /// Depends on the kind of effect you want; this will basically invert the segment ranges.
         segment_starts_on_image_edge: bool,
+
+        /// How the pixels of each segment are laid out after sorting, e.g. ramping from both
+        /// detected edges toward the center of the segment to emphasize the edge structure.
+        segment_arrangement: PixelSegmentArrangement,
+
+        /// Whether to sort the pixels *between* detected edges (i.e. flat areas)
+        /// instead of the edge pixels themselves.
+        invert_edges: bool,
+
+        /// If set, the grayscale image is blurred with a gaussian blur of this standard deviation
+        /// before edge detection, which suppresses edges caused by noise. Values of `0.0`
+        /// or less are treated as `None`.
+        blur_sigma: Option<f32>,
+
+        /// How many pixels the detected edges are dilated by (in every direction,
+        /// see [`imageproc::morphology::dilate`]), widening them into thicker bands.
+        /// Zero leaves the edges one pixel thin. Values above [`u8::MAX`] are clamped.
+        dilation: u32,
+    },
+
+    /// This mode creates pixel sorting segments that consist *only* of continuous pixels
+    /// whose normalized high-pass value is between `low` and `high` (both inclusive).
+    ///
+    /// The high-pass value of a pixel is the absolute difference between its relative luminance
+    /// and the relative luminance of a gaussian-blurred version of the image, normalized
+    /// by the largest such difference in the image. In contrast to [`Self::CannyEdges`],
+    /// this selects detailed or edgy areas softly, without producing hard one-pixel edges.
+    HighPassRange {
+        /// The standard deviation of the gaussian blur (must be larger than `0.0`).
+        blur_sigma: f32,
+
+        /// The inclusive low end of the normalized high-pass range (`0.0..=1.0`).
+        low: f32,
+
+        /// The inclusive high end of the normalized high-pass range (`0.0..=1.0`).
+        high: f32,
+    },
+
+    /// This mode creates pixel sorting segments that consist *only* of continuous pixels
+    /// whose Sobel gradient magnitude is at least `threshold`.
+    ///
+    /// In contrast to [`Self::CannyEdges`], the edges are neither thinned nor filtered
+    /// by hysteresis, so they end up thicker and more continuous.
+    SobelEdges {
+        /// The smallest gradient magnitude that counts as an edge
+        /// (`0.0..=1442.5`, see [`LARGEST_SOBEL_GRADIENT_MAGNITUDE`]).
+        threshold: f32,
+    },
+
+    /// This mode creates pixel sorting segments that consist *only* of continuous pixels
+    /// whose distance to the `target` color (see [`compute_rgba_color_distance`])
+    /// is between `low` and `high` (both inclusive), e.g. to sort everything close to sky blue.
+    ColorDistanceRange {
+        /// The color to measure distances to. Its alpha is ignored.
+        #[serde(with = "rgba_serde")]
+        target: Rgba<u8>,
+
+        /// The inclusive low end of the normalized color distance range (`0.0..=1.0`).
+        low: f32,
+
+        /// The inclusive high end of the normalized color distance range (`0.0..=1.0`).
+        high: f32,
+
+        /// How the distance to the `target` color is measured.
+        #[serde(default)]
+        distance_metric: DistanceMetric,
+    },
+
+    /// This mode creates pixel sorting segments that consist *only* of continuous pixels
+    /// whose pixel in `mask` is non-zero, e.g. with a black and white mask painted
+    /// in an external editor. The mask must be as large as the image (see
+    /// [`Self::validate_for_image`]).
+    MaskImage {
+        /// The selection mask. It is not serialized (e.g. into share codes),
+        /// so deserialized masks are always empty and must be replaced before sorting
+        /// (otherwise [`prepare_pixel_sort`] fails with [`SelectionModeError::EmptyMask`]).
+        #[serde(skip)]
+        mask: Arc<GrayImage>,
+    },
+
+    /// This mode creates pixel sorting segments that consist *only* of continuous pixels
+    /// selected by both (see [`BooleanOp::And`]) or either (see [`BooleanOp::Or`])
+    /// of the `first` and `second` selection modes, e.g. pixels that are bright *and* red.
+    ///
+    /// Both sub-modes are evaluated into selection masks (see [`Self::compute_selection_mask`]),
+    /// so the segment layout options of [`Self::CannyEdges`] have no effect inside a combination.
+    /// Sub-modes can themselves be combined.
+    Combined {
+        first: Box<PreparedSegmentSelectionMode>,
+        second: Box<PreparedSegmentSelectionMode>,
+        op: BooleanOp,
+    },
+}
+
+/// How [`PreparedSegmentSelectionMode::Combined`] combines its two selection modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BooleanOp {
+    /// Selects pixels that are selected by both selection modes.
+    And,
+
+    /// Selects pixels that are selected by at least one of the selection modes.
+    Or,
+}
+
+#[derive(Debug, Error)]
+pub enum SelectionModeError {
+    #[error("the selection mask is empty (has a mask been loaded?)")]
+    EmptyMask,
+
+    #[error(
+        "the selection mask is {mask_width}x{mask_height} pixels, \
+        but the image is {image_width}x{image_height} pixels"
+    )]
+    MaskDimensionMismatch {
+        mask_width: u32,
+        mask_height: u32,
+        image_width: u32,
+        image_height: u32,
     },
+
+    #[error(
+        "the low end of the selection range ({low}) is larger than its high end ({high}), \
+        so no pixels would be selected"
+    )]
+    InvertedRange { low: f32, high: f32 },
+
+    #[error("the blur standard deviation ({blur_sigma}) must be larger than zero")]
+    NonPositiveBlurSigma { blur_sigma: f32 },
+}
+
+impl PreparedSegmentSelectionMode {
+    /// Returns `true` if this selection mode's range covers the entire valid range
+    /// of its pixel property (e.g. a luminance range of `0.0..=1.0`).
+    ///
+    /// In that case every pixel is selected, meaning each row (or column) of the image
+    /// becomes a single segment that is sorted in its entirety. This is valid,
+    /// but often not what users expect.
+    pub fn selects_full_range(&self) -> bool {
+        match *self {
+            // Inverting a full range would select no pixels at all.
+            Self::LuminanceRange { invert: true, .. }
+            | Self::LuminancePercentileRange { invert: true, .. }
+            | Self::WeightedLuminanceRange { invert: true, .. }
+            | Self::HueRange { invert: true, .. }
+            | Self::SaturationRange { invert: true, .. } => false,
+            Self::LuminanceRange { low, high, .. }
+            | Self::WeightedLuminanceRange { low, high, .. }
+            | Self::SaturationRange { low, high, .. }
+            | Self::AlphaRange { low, high }
+            | Self::ColorDistanceRange { low, high, .. }
+            | Self::HighPassRange { low, high, .. } => low <= 0.0 && high >= 1.0,
+            Self::HueRange { low, high, .. } => low <= 0.0 && high >= 360.0,
+            Self::LuminancePercentileRange { low, high, .. } => low <= 0.0 && high >= 100.0,
+            Self::SobelEdges { threshold } => threshold <= 0.0,
+            Self::CannyEdges { .. } | Self::MaskImage { .. } => false,
+            Self::Combined {
+                ref first,
+                ref second,
+                op,
+            } => match op {
+                BooleanOp::And => first.selects_full_range() && second.selects_full_range(),
+                BooleanOp::Or => first.selects_full_range() || second.selects_full_range(),
+            },
+        }
+    }
+
+    /// Checks whether the range of this selection mode (if any) is not inverted,
+    /// i.e. whether its low end is not larger than its high end (which would select no pixels).
+    ///
+    /// Hue ranges are never inverted, as they wrap around 360 degrees instead
+    /// (see [`Self::HueRange`]). The blur of [`Self::HighPassRange`] is checked as well,
+    /// as a standard deviation that is not larger than zero (or NaN) can't be blurred with.
+    pub fn validate_range(&self) -> Result<(), SelectionModeError> {
+        match *self {
+            Self::HighPassRange { blur_sigma, .. } if blur_sigma.is_nan() || blur_sigma <= 0.0 => {
+                Err(SelectionModeError::NonPositiveBlurSigma { blur_sigma })
+            }
+            Self::LuminanceRange { low, high, .. }
+            | Self::LuminancePercentileRange { low, high, .. }
+            | Self::WeightedLuminanceRange { low, high, .. }
+            | Self::SaturationRange { low, high, .. }
+            | Self::AlphaRange { low, high }
+            | Self::ColorDistanceRange { low, high, .. }
+            | Self::HighPassRange { low, high, .. }
+                if low > high =>
+            {
+                Err(SelectionModeError::InvertedRange { low, high })
+            }
+            Self::Combined {
+                ref first,
+                ref second,
+                ..
+            } => {
+                first.validate_range()?;
+                second.validate_range()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks whether this selection mode can be used to sort an image
+    /// of the given dimensions, i.e. whether its range is valid (see [`Self::validate_range`])
+    /// and whether its mask, if any, matches the image.
+    pub fn validate_for_image(
+        &self,
+        image_width: u32,
+        image_height: u32,
+    ) -> Result<(), SelectionModeError> {
+        self.validate_range()?;
+
+        let mask = match self {
+            Self::MaskImage { mask } => mask,
+            Self::Combined { first, second, .. } => {
+                first.validate_for_image(image_width, image_height)?;
+                return second.validate_for_image(image_width, image_height);
+            }
+            _ => return Ok(()),
+        };
+
+        if mask.width() == 0 || mask.height() == 0 {
+            return Err(SelectionModeError::EmptyMask);
+        }
+
+        if mask.dimensions() != (image_width, image_height) {
+            return Err(SelectionModeError::MaskDimensionMismatch {
+                mask_width: mask.width(),
+                mask_height: mask.height(),
+                image_width,
+                image_height,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Computes a binary selection mask for the given `image`, where each pixel this selection
+    /// mode selects is set to [`u8::MAX`] and all others to zero (see [`Self::Combined`]).
+    ///
+    /// Edge detection modes select their (possibly inverted) edge pixels, regardless of
+    /// where their segments would start (see [`Self::CannyEdges::segment_starts_on_image_edge`]).
+    ///
+    /// # Panics
+    /// Panics if a mask of [`Self::MaskImage`] doesn't match the dimensions of the image
+    /// (see [`Self::validate_for_image`]).
+    pub fn compute_selection_mask(&self, image: &RgbaImage) -> GrayImage {
+        match self {
+            Self::LuminanceRange { low, high, invert } => {
+                let target_luminance_range = *low..=*high;
+
+                compute_pixel_predicate_selection_mask(image, |pixel| {
+                    target_luminance_range.contains(&compute_rgba_relative_luminance(pixel))
+                        != *invert
+                })
+            }
+            Self::LuminancePercentileRange { low, high, invert } => {
+                let (low, high) = LuminanceHistogram::from_image(image)
+                    .luminance_range_at_percentiles(*low, *high);
+
+                Self::LuminanceRange {
+                    low,
+                    high,
+                    invert: *invert,
+                }
+                .compute_selection_mask(image)
+            }
+            Self::WeightedLuminanceRange {
+                weights,
+                low,
+                high,
+                invert,
+            } => {
+                let target_luminance_range = *low..=*high;
+
+                compute_pixel_predicate_selection_mask(image, |pixel| {
+                    target_luminance_range
+                        .contains(&compute_rgba_weighted_luminance(pixel, *weights))
+                        != *invert
+                })
+            }
+            Self::HueRange { low, high, invert } => {
+                compute_pixel_predicate_selection_mask(image, |pixel| {
+                    is_hue_in_range(compute_rgba_hsl_hue_gamma(pixel), *low, *high) != *invert
+                })
+            }
+            Self::SaturationRange { low, high, invert } => {
+                let target_saturation_range = *low..=*high;
+
+                compute_pixel_predicate_selection_mask(image, |pixel| {
+                    target_saturation_range.contains(&compute_rgba_hsl_saturation(pixel)) != *invert
+                })
+            }
+            Self::AlphaRange { low, high } => {
+                let target_alpha_range = *low..=*high;
+
+                compute_pixel_predicate_selection_mask(image, |pixel| {
+                    target_alpha_range.contains(&compute_rgba_normalized_alpha(pixel))
+                })
+            }
+            Self::ColorDistanceRange {
+                target,
+                low,
+                high,
+                distance_metric,
+            } => {
+                let target_color_distance_range = *low..=*high;
+
+                compute_pixel_predicate_selection_mask(image, |pixel| {
+                    target_color_distance_range.contains(&compute_rgba_color_distance(
+                        pixel,
+                        target,
+                        *distance_metric,
+                    ))
+                })
+            }
+            Self::CannyEdges {
+                low,
+                high,
+                invert_edges,
+                blur_sigma,
+                dilation,
+                ..
+            } => {
+                let mut edge_mask = compute_canny_edge_mask(
+                    image::imageops::grayscale(image),
+                    *low,
+                    *high,
+                    *blur_sigma,
+                    *dilation,
+                );
+
+                if *invert_edges {
+                    image::imageops::invert(&mut edge_mask);
+                }
+
+                edge_mask
+            }
+            Self::SobelEdges { threshold } => compute_sobel_edge_mask(image, *threshold),
+            Self::HighPassRange {
+                blur_sigma,
+                low,
+                high,
+            } => compute_high_pass_selection_mask(image, *blur_sigma, *low, *high),
+            Self::MaskImage { mask } => compute_binary_selection_mask(mask),
+            Self::Combined { first, second, op } => combine_selection_masks(
+                &first.compute_selection_mask(image),
+                &second.compute_selection_mask(image),
+                *op,
+            ),
+        }
+    }
 }
 
 
+#[allow(clippy::enum_variant_names)]
 enum PreparedPixelSortImage {
     PreparedHorizontal {
         /// The image to be sorted.
@@ -87,6 +658,20 @@ enum PreparedPixelSortImage {
         /// The vertical direction in which the selected underlying pixel property will be sorted.
         direction: PixelSegmentSortDirection,
     },
+
+    PreparedAngled {
+        /// The image gathered into lines along the sorting angle, one line per row
+        /// (see [`AngledLineLayout::gather_aligned_line_image`]). After sorting, scatter it
+        /// using `line_layout` to obtain the original image orientation.
+        line_image: RgbaImage,
+
+        /// The layout of the lines along the sorting angle.
+        line_layout: AngledLineLayout,
+
+        /// The direction along each line in which the selected underlying pixel property
+        /// will be sorted.
+        direction: PixelSegmentSortDirection,
+    },
 }
 
 impl PreparedPixelSortImage {
@@ -94,6 +679,7 @@ impl PreparedPixelSortImage {
         match self {
             Self::PreparedHorizontal { image, .. } => image.width() as usize,
             Self::PreparedVertical { rotated_image, .. } => rotated_image.height() as usize,
+            Self::PreparedAngled { line_image, .. } => line_image.width() as usize,
         }
     }
 
@@ -101,6 +687,50 @@ impl PreparedPixelSortImage {
         match self {
             Self::PreparedHorizontal { image, .. } => image.height() as usize,
             Self::PreparedVertical { rotated_image, .. } => rotated_image.width() as usize,
+            Self::PreparedAngled { line_image, .. } => line_image.height() as usize,
+        }
+    }
+
+    /// Maps a pixel of the prepared (possibly rotated or line-aligned) image to its coordinates
+    /// in the original image. Returns `None` for the padding of angled sorts.
+    fn original_pixel_coordinates(
+        &self,
+        row_index: usize,
+        column_index: usize,
+    ) -> Option<(u32, u32)> {
+        match self {
+            Self::PreparedHorizontal { .. } => Some((column_index as u32, row_index as u32)),
+            // `rotate90` moves the original pixel at (x, y) to (height - 1 - y, x).
+            Self::PreparedVertical { rotated_image, .. } => Some((
+                row_index as u32,
+                rotated_image.width() - 1 - column_index as u32,
+            )),
+            Self::PreparedAngled { line_layout, .. } => {
+                line_layout.pixel_coordinates_at_aligned_column(row_index, column_index)
+            }
+        }
+    }
+
+    /// Replaces the pixels to be sorted with the ones of `image` (in the original orientation),
+    /// which must have the same dimensions as the image this was prepared from.
+    fn replace_pixels_with(&mut self, image: RgbaImage) {
+        match self {
+            Self::PreparedHorizontal {
+                image: prepared_image,
+                ..
+            } => {
+                *prepared_image = image;
+            }
+            Self::PreparedVertical { rotated_image, .. } => {
+                *rotated_image = image::imageops::rotate90(&image);
+            }
+            Self::PreparedAngled {
+                line_image,
+                line_layout,
+                ..
+            } => {
+                *line_image = line_layout.gather_aligned_line_image(&image);
+            }
         }
     }
 }
@@ -110,17 +740,20 @@ impl PreparedPixelSortImage {
 /// (this allows us to set up sorting functions more simply).
 pub struct PreparedPixelSort<SortingContext>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
 {
     image: PreparedPixelSortImage,
 
     /// These are the custom sorting contexts, presented in row-major order.
     prepared_row_data: Vec<PreparedPixelSortRow<SortingContext>>,
+
+    /// How the pixels of each segment are laid out after sorting.
+    segment_arrangement: PixelSegmentArrangement,
 }
 
 impl<SortingContext> Debug for PreparedPixelSort<SortingContext>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -142,6 +775,52 @@ where
     }
 }
 
+impl<SortingContext> PreparedPixelSort<SortingContext>
+where
+    SortingContext: Send + Copy + PartialOrd,
+{
+    /// Returns `true` if this is a vertical pixel sort, i.e. if each prepared row
+    /// (see [`Self::segment_spans`]) is actually a column of the original image.
+    pub fn is_vertical(&self) -> bool {
+        matches!(
+            self.image,
+            PreparedPixelSortImage::PreparedVertical { .. }
+        )
+    }
+
+    /// Returns the location of every prepared segment, grouped by row in row-major order.
+    ///
+    /// For vertical sorts (see [`Self::is_vertical`]), each row corresponds to a column
+    /// of the original image (from left to right), and the segment start indices
+    /// are counted from the top of the original image. For angled sorts, each row corresponds
+    /// to a line along the sorting angle, and the segment start indices are counted
+    /// in the aligned line image (see [`ImageSortingDirection::Angled`]).
+    pub fn segment_spans(&self) -> Vec<Vec<PreparedSegmentSpan>> {
+        self.prepared_row_data
+            .iter()
+            .map(|row| {
+                row.sorting_contexts_for_row
+                    .iter()
+                    .map(|segment| PreparedSegmentSpan {
+                        start_index: segment.start_column_index,
+                        length: segment.pixel_sorting_contexts.len(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// The location of a single prepared pixel sorting segment inside its row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreparedSegmentSpan {
+    /// The index of the first pixel of the segment in its row.
+    pub start_index: usize,
+
+    /// The number of pixels in the segment.
+    pub length: usize,
+}
+
 /// This represents a single continous segment of pixels that is to be sorted.
 struct PreparedPixelSortSegment<SortingContext>
 where
@@ -297,7 +976,7 @@ fn prepare_axis_aligned_numeric_pixel_sort<
     sorting_context_computation_closure: SortingContextClosure,
 ) -> PreparedPixelSort<SortingContext>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
     MembershipContextClosure: Fn(&Rgba<u8>) -> MembershipContext + Send + Sync,
     SegmentMembershipClosure: Fn(&PixelWithContext<MembershipContext>) -> bool + Send + Sync,
     SortingContextClosure: Fn(&PixelWithContext<MembershipContext>) -> SortingContext + Send + Sync,
@@ -347,6 +1026,7 @@ where
                     direction: pixel_segment_sort_direction,
                 },
                 prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
             }
         }
         ImageSortingDirection::Vertical(pixel_segment_sort_direction) => {
@@ -395,8 +1075,12 @@ where
                     direction: pixel_segment_sort_direction,
                 },
                 prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
             }
         }
+        ImageSortingDirection::Angled { .. } => {
+            unreachable!("angled pixel sorts are prepared as horizontal sorts of their line image")
+        }
     }
 }
 
@@ -407,10 +1091,11 @@ fn prepare_segments_using_detected_edges_for_single_row<SortingContext, SortingC
     edge_image_row_contiguous_flat_buffer: &[u8],
     edge_image_layout: SampleLayout,
     initial_segment_starts_on_left_edge: bool,
+    invert_edges: bool,
     sorting_context_computation_closure: SortingContextClosure,
 ) -> PreparedPixelSortRow<SortingContext>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
     SortingContextClosure: Fn(&Rgba<u8>) -> SortingContext + Send + Sync,
 {
     assert_eq!(edge_image_layout.width_stride, 1);
@@ -439,8 +1124,9 @@ where
     for (column_index, (target_pixel, edge_pixel)) in zipped_pixel_pair_iterator {
         let target_pixel = retrieve_starting_rgba_pixel_from_flat_samples(target_pixel);
 
+        let is_edge_pixel = (*edge_pixel == u8::MAX) != invert_edges;
         let belongs_to_segment =
-            *edge_pixel == u8::MAX || (column_index == 0 && initial_segment_starts_on_left_edge);
+            is_edge_pixel || (column_index == 0 && initial_segment_starts_on_left_edge);
 
         if belongs_to_segment {
             match current_state {
@@ -485,6 +1171,19 @@ where
         }
     }
 
+    // If the last pixel was also inside a sortable segment,
+    // we conclude that segment here.
+    if let PixelSegmentScannerState::CollectingSortableSegment {
+        segment_start_index,
+        collected_pixels,
+    } = current_state
+    {
+        prepared_segments.push(PreparedPixelSortSegment {
+            start_column_index: segment_start_index as usize,
+            pixel_sorting_contexts: collected_pixels,
+        });
+    }
+
     PreparedPixelSortRow {
         sorting_contexts_for_row: prepared_segments,
     }
@@ -496,9 +1195,10 @@ fn prepare_segments_using_detected_edges<SortingContext, SortingContextClosure>(
     binary_edge_image: GrayImage,
     sorting_context_computation_closure: SortingContextClosure,
     segment_starts_on_image_edge: bool,
+    invert_edges: bool,
 ) -> Vec<PreparedPixelSortRow<SortingContext>>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
     SortingContextClosure: Fn(&Rgba<u8>) -> SortingContext + Send + Sync,
 {
     let target_image_layout = target_image.sample_layout();
@@ -522,6 +1222,7 @@ where
                     binary_edge_image_buffer,
                     edge_image_layout,
                     segment_starts_on_image_edge,
+                    invert_edges,
                     &sorting_context_computation_closure,
                 )
             },
@@ -531,14 +1232,107 @@ where
     prepared_rows
 }
 
+/// The smallest Canny edge detection threshold that is safe to use.
+///
+/// A threshold of exactly zero makes [`imageproc::edges::canny`] treat the (always zero)
+/// image border as edges, at which point its hysteresis step indexes out of bounds and panics.
+pub const SMALLEST_CANNY_EDGE_THRESHOLD: f32 = 0.1;
+
+/// The largest meaningful Canny edge detection threshold.
+///
+/// See <https://docs.rs/imageproc/latest/imageproc/edges/fn.canny.html> for more information.
+pub const LARGEST_CANNY_EDGE_THRESHOLD: f32 = 1140.39;
+
+/// Clamps the given Canny edge detection thresholds to
+/// [`SMALLEST_CANNY_EDGE_THRESHOLD`]`..=`[`LARGEST_CANNY_EDGE_THRESHOLD`],
+/// ensuring `low <= high` (which [`imageproc::edges::canny`] asserts).
+/// NaN thresholds are treated as the smallest threshold.
+fn clamp_canny_edge_thresholds(low: f32, high: f32) -> (f32, f32) {
+    let clamp_threshold = |threshold: f32| {
+        if threshold.is_nan() {
+            SMALLEST_CANNY_EDGE_THRESHOLD
+        } else {
+            threshold.clamp(
+                SMALLEST_CANNY_EDGE_THRESHOLD,
+                LARGEST_CANNY_EDGE_THRESHOLD,
+            )
+        }
+    };
+
+    let clamped_low = clamp_threshold(low);
+    let clamped_high = clamp_threshold(high).max(clamped_low);
+
+    (clamped_low, clamped_high)
+}
+
+/// Blurs `gray_image` with a gaussian blur of standard deviation `blur_sigma` (if set and
+/// larger than zero), see [`PreparedSegmentSelectionMode::CannyEdges::blur_sigma`].
+fn blur_image_before_edge_detection(gray_image: GrayImage, blur_sigma: Option<f32>) -> GrayImage {
+    match blur_sigma {
+        Some(blur_sigma) if blur_sigma > 0.0 => {
+            imageproc::filter::gaussian_blur_f32(&gray_image, blur_sigma)
+        }
+        _ => gray_image,
+    }
+}
+
+/// Dilates the binary `edge_image` by `dilation` pixels,
+/// see [`PreparedSegmentSelectionMode::CannyEdges::dilation`].
+fn dilate_detected_edges(edge_image: GrayImage, dilation: u32) -> GrayImage {
+    if dilation == 0 {
+        return edge_image;
+    }
+
+    let dilation = dilation.min(u8::MAX as u32) as u8;
+
+    imageproc::morphology::dilate(
+        &edge_image,
+        imageproc::distance_transform::Norm::LInf,
+        dilation,
+    )
+}
+
+/// Computes the binary edge mask of [`PreparedSegmentSelectionMode::CannyEdges`] for
+/// `gray_image`, where edge pixels are set to [`u8::MAX`] and all others to zero.
+///
+/// The thresholds are clamped (see [`clamp_canny_edge_thresholds`]), and the image
+/// is blurred beforehand and the edges dilated afterwards, if requested.
+pub(crate) fn compute_canny_edge_mask(
+    gray_image: GrayImage,
+    low: f32,
+    high: f32,
+    blur_sigma: Option<f32>,
+    dilation: u32,
+) -> GrayImage {
+    let (low, high) = clamp_canny_edge_thresholds(low, high);
+
+    let gray_image = blur_image_before_edge_detection(gray_image, blur_sigma);
+    let edge_image = imageproc::edges::canny(&gray_image, low, high);
+
+    dilate_detected_edges(edge_image, dilation)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn prepare_axis_aligned_numeric_edge_detected_pixel_sort(
     image: RgbaImage,
     edge_detection_low_threshold: f32,
     edge_detection_high_threshold: f32,
     initial_segment_starts_on_left_image_edge: bool,
+    invert_edges: bool,
+    blur_sigma: Option<f32>,
+    dilation: u32,
     direction: ImageSortingDirection,
     sorting_mode: PreparedSegmentSortingMode,
-) -> PreparedPixelSort<f32> {
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+) -> PreparedPixelSort<PreparedSortingContext> {
+    let sorting_context_computation_closure = |pixel: &Rgba<u8>| {
+        build_sorting_context(
+            sorting_mode.compute_sorting_value(pixel),
+            pixel,
+            tiebreak_mode,
+        )
+    };
+
     match direction {
         ImageSortingDirection::Horizontal(pixel_segment_sort_direction) => {
             let dynamic_image = DynamicImage::ImageRgba8(image);
@@ -549,10 +1343,12 @@ fn prepare_axis_aligned_numeric_edge_detected_pixel_sort(
                 );
             };
 
-            let image_edges = imageproc::edges::canny(
-                &gray_image,
+            let image_edges = compute_canny_edge_mask(
+                gray_image,
                 edge_detection_low_threshold,
                 edge_detection_high_threshold,
+                blur_sigma,
+                dilation,
             );
 
             assert!(image_edges.width() == image.width());
@@ -561,12 +1357,9 @@ fn prepare_axis_aligned_numeric_edge_detected_pixel_sort(
             let prepared_row_data = prepare_segments_using_detected_edges(
                 &image,
                 image_edges,
-                |pixel| match sorting_mode {
-                    PreparedSegmentSortingMode::Luminance => compute_rgba_relative_luminance(pixel),
-                    PreparedSegmentSortingMode::Hue => compute_rgba_hsl_hue(pixel),
-                    PreparedSegmentSortingMode::Saturation => compute_rgba_hsl_saturation(pixel),
-                },
+                sorting_context_computation_closure,
                 initial_segment_starts_on_left_image_edge,
+                invert_edges,
             );
 
             PreparedPixelSort {
@@ -575,6 +1368,7 @@ fn prepare_axis_aligned_numeric_edge_detected_pixel_sort(
                     direction: pixel_segment_sort_direction,
                 },
                 prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
             }
         }
         ImageSortingDirection::Vertical(pixel_segment_sort_direction) => {
@@ -588,10 +1382,12 @@ fn prepare_axis_aligned_numeric_edge_detected_pixel_sort(
                 );
             };
 
-            let image_edges = imageproc::edges::canny(
-                &gray_image,
+            let image_edges = compute_canny_edge_mask(
+                gray_image,
                 edge_detection_low_threshold,
                 edge_detection_high_threshold,
+                blur_sigma,
+                dilation,
             );
 
             assert!(image_edges.width() == rotated_image.width());
@@ -600,89 +1396,583 @@ fn prepare_axis_aligned_numeric_edge_detected_pixel_sort(
             let prepared_row_data = prepare_segments_using_detected_edges(
                 &rotated_image,
                 image_edges,
-                |pixel| match sorting_mode {
-                    PreparedSegmentSortingMode::Luminance => compute_rgba_relative_luminance(pixel),
-                    PreparedSegmentSortingMode::Hue => compute_rgba_hsl_hue(pixel),
-                    PreparedSegmentSortingMode::Saturation => compute_rgba_hsl_saturation(pixel),
-                },
+                sorting_context_computation_closure,
                 initial_segment_starts_on_left_image_edge,
+                invert_edges,
             );
 
             PreparedPixelSort {
-                image: PreparedPixelSortImage::PreparedHorizontal {
-                    image: rotated_image,
+                image: PreparedPixelSortImage::PreparedVertical {
+                    rotated_image,
                     direction: pixel_segment_sort_direction,
                 },
                 prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
             }
         }
+        ImageSortingDirection::Angled { .. } => {
+            unreachable!("angled pixel sorts are prepared as horizontal sorts of their line image")
+        }
     }
 }
 
 
-pub fn prepare_pixel_sort(
+/// The largest possible Sobel gradient magnitude of an 8-bit grayscale image,
+/// i.e. `sqrt(2) * 4 * 255` (see [`imageproc::gradients::sobel_gradients`]).
+pub const LARGEST_SOBEL_GRADIENT_MAGNITUDE: f32 = 1442.5;
+
+/// Computes a binary edge mask for the given `image`, where a pixel is set to [`u8::MAX`]
+/// if the magnitude of its Sobel gradient is at least `threshold` and to zero otherwise
+/// (see [`PreparedSegmentSelectionMode::SobelEdges`]).
+fn compute_sobel_edge_mask(image: &RgbaImage, threshold: f32) -> GrayImage {
+    let gray_image = image::imageops::grayscale(image);
+    let gradient_magnitudes = imageproc::gradients::sobel_gradients(&gray_image);
+
+    GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        if gradient_magnitudes.get_pixel(x, y).0[0] as f32 >= threshold {
+            Luma([u8::MAX])
+        } else {
+            Luma([0])
+        }
+    })
+}
+
+fn prepare_axis_aligned_numeric_sobel_edge_pixel_sort(
     image: RgbaImage,
-    selection_mode: PreparedSegmentSelectionMode,
-    sorting_mode: PreparedSegmentSortingMode,
+    threshold: f32,
     direction: ImageSortingDirection,
-) -> PreparedPixelSort<f32> {
-    match selection_mode {
-        PreparedSegmentSelectionMode::LuminanceRange { low, high } => {
-            let target_luminance_range = low..=high;
+    sorting_mode: PreparedSegmentSortingMode,
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+) -> PreparedPixelSort<PreparedSortingContext> {
+    let sorting_context_computation_closure = |pixel: &Rgba<u8>| {
+        build_sorting_context(
+            sorting_mode.compute_sorting_value(pixel),
+            pixel,
+            tiebreak_mode,
+        )
+    };
 
-            prepare_axis_aligned_numeric_pixel_sort(
-                image,
-                direction,
-                |pixel: &Rgba<u8>| -> f32 { compute_rgba_relative_luminance(pixel) },
-                |pixel: &PixelWithContext<f32>| -> bool {
-                    target_luminance_range.contains(&pixel.context)
-                },
-                |pixel| match sorting_mode {
-                    PreparedSegmentSortingMode::Luminance => pixel.context,
-                    PreparedSegmentSortingMode::Hue => compute_rgba_hsl_hue(&pixel.pixel),
-                    PreparedSegmentSortingMode::Saturation => {
-                        compute_rgba_hsl_saturation(&pixel.pixel)
-                    }
-                },
-            )
-        }
-        PreparedSegmentSelectionMode::HueRange { low, high } => {
-            let target_hue_range = low..=high;
+    match direction {
+        ImageSortingDirection::Horizontal(pixel_segment_sort_direction) => {
+            let edge_mask = compute_sobel_edge_mask(&image, threshold);
 
-            prepare_axis_aligned_numeric_pixel_sort(
-                image,
-                direction,
-                |pixel: &Rgba<u8>| -> f32 { compute_rgba_hsl_hue(pixel) },
-                |pixel: &PixelWithContext<f32>| -> bool {
-                    target_hue_range.contains(&pixel.context)
-                },
-                |pixel| match sorting_mode {
-                    PreparedSegmentSortingMode::Luminance => {
-                        compute_rgba_relative_luminance(&pixel.pixel)
-                    }
-                    PreparedSegmentSortingMode::Hue => pixel.context,
-                    PreparedSegmentSortingMode::Saturation => {
-                        compute_rgba_hsl_saturation(&pixel.pixel)
-                    }
+            let prepared_row_data = prepare_segments_using_detected_edges(
+                &image,
+                edge_mask,
+                sorting_context_computation_closure,
+                false,
+                false,
+            );
+
+            PreparedPixelSort {
+                image: PreparedPixelSortImage::PreparedHorizontal {
+                    image,
+                    direction: pixel_segment_sort_direction,
                 },
-            )
+                prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
+            }
         }
-        PreparedSegmentSelectionMode::SaturationRange { low, high } => {
-            let target_saturation_range = low..=high;
+        ImageSortingDirection::Vertical(pixel_segment_sort_direction) => {
+            let rotated_image = image::imageops::rotate90(&image);
+
+            let edge_mask = compute_sobel_edge_mask(&rotated_image, threshold);
+
+            let prepared_row_data = prepare_segments_using_detected_edges(
+                &rotated_image,
+                edge_mask,
+                sorting_context_computation_closure,
+                false,
+                false,
+            );
+
+            PreparedPixelSort {
+                image: PreparedPixelSortImage::PreparedVertical {
+                    rotated_image,
+                    direction: pixel_segment_sort_direction,
+                },
+                prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
+            }
+        }
+        ImageSortingDirection::Angled { .. } => {
+            unreachable!("angled pixel sorts are prepared as horizontal sorts of their line image")
+        }
+    }
+}
+
+
+/// Computes a binary selection mask for the given `image`, where a pixel is set to [`u8::MAX`]
+/// if its normalized high-pass value is inside `low..=high` and to zero otherwise
+/// (see [`PreparedSegmentSelectionMode::HighPassRange`]).
+fn compute_high_pass_selection_mask(
+    image: &RgbaImage,
+    blur_sigma: f32,
+    low: f32,
+    high: f32,
+) -> GrayImage {
+    let luminance_map: ImageBuffer<Luma<f32>, Vec<f32>> =
+        ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+            Luma([compute_rgba_relative_luminance(image.get_pixel(x, y))])
+        });
+
+    let blurred_luminance_map = imageproc::filter::gaussian_blur_f32(&luminance_map, blur_sigma);
+
+    let high_pass_values: Vec<f32> = luminance_map
+        .as_raw()
+        .par_iter()
+        .zip(blurred_luminance_map.as_raw().par_iter())
+        .map(|(original, blurred)| (original - blurred).abs())
+        .collect();
+
+    let largest_high_pass_value = high_pass_values
+        .par_iter()
+        .copied()
+        .reduce(|| 0f32, f32::max);
+
+    let target_high_pass_range = low..=high;
+
+    let mask_buffer: Vec<u8> = high_pass_values
+        .into_par_iter()
+        .map(|high_pass_value| {
+            // A completely flat image has no detail at all, so we treat every pixel as zero.
+            let normalized_high_pass_value = if largest_high_pass_value > 0f32 {
+                high_pass_value / largest_high_pass_value
+            } else {
+                0f32
+            };
+
+            if target_high_pass_range.contains(&normalized_high_pass_value) {
+                u8::MAX
+            } else {
+                0
+            }
+        })
+        .collect();
+
+    GrayImage::from_raw(image.width(), image.height(), mask_buffer)
+        .expect("mask buffer should have exactly one sample per pixel")
+}
+
+fn prepare_axis_aligned_numeric_high_pass_pixel_sort(
+    image: RgbaImage,
+    blur_sigma: f32,
+    low: f32,
+    high: f32,
+    direction: ImageSortingDirection,
+    sorting_mode: PreparedSegmentSortingMode,
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+) -> PreparedPixelSort<PreparedSortingContext> {
+    let sorting_context_computation_closure = |pixel: &Rgba<u8>| {
+        build_sorting_context(
+            sorting_mode.compute_sorting_value(pixel),
+            pixel,
+            tiebreak_mode,
+        )
+    };
+
+    match direction {
+        ImageSortingDirection::Horizontal(pixel_segment_sort_direction) => {
+            let selection_mask = compute_high_pass_selection_mask(&image, blur_sigma, low, high);
+
+            let prepared_row_data = prepare_segments_using_detected_edges(
+                &image,
+                selection_mask,
+                sorting_context_computation_closure,
+                false,
+                false,
+            );
+
+            PreparedPixelSort {
+                image: PreparedPixelSortImage::PreparedHorizontal {
+                    image,
+                    direction: pixel_segment_sort_direction,
+                },
+                prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
+            }
+        }
+        ImageSortingDirection::Vertical(pixel_segment_sort_direction) => {
+            let rotated_image = image::imageops::rotate90(&image);
+
+            let selection_mask =
+                compute_high_pass_selection_mask(&rotated_image, blur_sigma, low, high);
+
+            let prepared_row_data = prepare_segments_using_detected_edges(
+                &rotated_image,
+                selection_mask,
+                sorting_context_computation_closure,
+                false,
+                false,
+            );
+
+            PreparedPixelSort {
+                image: PreparedPixelSortImage::PreparedVertical {
+                    rotated_image,
+                    direction: pixel_segment_sort_direction,
+                },
+                prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
+            }
+        }
+        ImageSortingDirection::Angled { .. } => {
+            unreachable!("angled pixel sorts are prepared as horizontal sorts of their line image")
+        }
+    }
+}
+
+
+/// Converts a user-provided selection mask into a binary one, where every non-zero pixel
+/// is set to [`u8::MAX`] (see [`PreparedSegmentSelectionMode::MaskImage`]).
+fn compute_binary_selection_mask(mask: &GrayImage) -> GrayImage {
+    GrayImage::from_fn(mask.width(), mask.height(), |x, y| {
+        if mask.get_pixel(x, y).0[0] != 0 {
+            Luma([u8::MAX])
+        } else {
+            Luma([0])
+        }
+    })
+}
+
+/// Computes a binary selection mask for the given `image`, where a pixel is set to [`u8::MAX`]
+/// if `is_selected` returns `true` for it and to zero otherwise.
+fn compute_pixel_predicate_selection_mask<SelectionClosure>(
+    image: &RgbaImage,
+    is_selected: SelectionClosure,
+) -> GrayImage
+where
+    SelectionClosure: Fn(&Rgba<u8>) -> bool,
+{
+    GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        if is_selected(image.get_pixel(x, y)) {
+            Luma([u8::MAX])
+        } else {
+            Luma([0])
+        }
+    })
+}
+
+/// Combines two binary selection masks of the same dimensions pixel by pixel
+/// (see [`PreparedSegmentSelectionMode::Combined`]).
+fn combine_selection_masks(first: &GrayImage, second: &GrayImage, op: BooleanOp) -> GrayImage {
+    assert_eq!(first.dimensions(), second.dimensions());
+
+    GrayImage::from_fn(first.width(), first.height(), |x, y| {
+        let is_selected_by_first = first.get_pixel(x, y).0[0] != 0;
+        let is_selected_by_second = second.get_pixel(x, y).0[0] != 0;
+
+        let is_selected = match op {
+            BooleanOp::And => is_selected_by_first && is_selected_by_second,
+            BooleanOp::Or => is_selected_by_first || is_selected_by_second,
+        };
+
+        if is_selected {
+            Luma([u8::MAX])
+        } else {
+            Luma([0])
+        }
+    })
+}
+
+/// Prepares a pixel sort whose segments are selected by a binary `selection_mask`
+/// of the same (unrotated) dimensions as `image`.
+fn prepare_axis_aligned_numeric_mask_pixel_sort(
+    image: RgbaImage,
+    selection_mask: GrayImage,
+    direction: ImageSortingDirection,
+    sorting_mode: PreparedSegmentSortingMode,
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+) -> PreparedPixelSort<PreparedSortingContext> {
+    let sorting_context_computation_closure = |pixel: &Rgba<u8>| {
+        build_sorting_context(
+            sorting_mode.compute_sorting_value(pixel),
+            pixel,
+            tiebreak_mode,
+        )
+    };
+
+    match direction {
+        ImageSortingDirection::Horizontal(pixel_segment_sort_direction) => {
+            let prepared_row_data = prepare_segments_using_detected_edges(
+                &image,
+                selection_mask,
+                sorting_context_computation_closure,
+                false,
+                false,
+            );
+
+            PreparedPixelSort {
+                image: PreparedPixelSortImage::PreparedHorizontal {
+                    image,
+                    direction: pixel_segment_sort_direction,
+                },
+                prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
+            }
+        }
+        ImageSortingDirection::Vertical(pixel_segment_sort_direction) => {
+            let rotated_image = image::imageops::rotate90(&image);
+            let rotated_selection_mask = image::imageops::rotate90(&selection_mask);
+
+            let prepared_row_data = prepare_segments_using_detected_edges(
+                &rotated_image,
+                rotated_selection_mask,
+                sorting_context_computation_closure,
+                false,
+                false,
+            );
+
+            PreparedPixelSort {
+                image: PreparedPixelSortImage::PreparedVertical {
+                    rotated_image,
+                    direction: pixel_segment_sort_direction,
+                },
+                prepared_row_data,
+                segment_arrangement: PixelSegmentArrangement::Linear,
+            }
+        }
+        ImageSortingDirection::Angled { .. } => {
+            unreachable!("angled pixel sorts are prepared as horizontal sorts of their line image")
+        }
+    }
+}
+
+
+/// Multiplies the color channels of each pixel by its alpha (in parallel, using `rayon`).
+fn premultiply_image_alpha(image: &mut RgbaImage) {
+    image.par_pixels_mut().for_each(|pixel| {
+        let alpha = pixel.0[3] as u32;
+
+        for channel in &mut pixel.0[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / u8::MAX as u32) as u8;
+        }
+    });
+}
+
+
+/// Prepares a pixel sort of `image`: selects the segments to sort using `selection_mode`
+/// and pre-computes the sorting context of each selected pixel.
+///
+/// Pixels are sorted by `sorting_mode` first; pixels with equal values are then ordered
+/// by `tiebreak_mode`, if set (e.g. luminance with a hue tiebreak avoids flat bands).
+///
+/// If `premultiply_alpha` is set, the color channels are multiplied by alpha before
+/// selecting segments and computing sorting contexts, so that mostly transparent pixels
+/// contribute as little as they do when composited. The pixels themselves keep their
+/// straight (non-premultiplied) colors, so pixels outside the sorted segments are unchanged.
+///
+/// Returns an error if the selection mode can't be used with this image
+/// (see [`PreparedSegmentSelectionMode::validate_for_image`]).
+pub fn prepare_pixel_sort(
+    mut image: RgbaImage,
+    selection_mode: PreparedSegmentSelectionMode,
+    sorting_mode: PreparedSegmentSortingMode,
+    tiebreak_mode: Option<PreparedSegmentSortingMode>,
+    direction: ImageSortingDirection,
+    premultiply_alpha: bool,
+) -> Result<PreparedPixelSort<PreparedSortingContext>, SelectionModeError> {
+    // Images without any pixels have nothing to sort (their rows can't even be chunked),
+    // so they are prepared without any rows and returned unchanged when executed.
+    if image.width() == 0 || image.height() == 0 {
+        return Ok(PreparedPixelSort {
+            image: PreparedPixelSortImage::PreparedHorizontal {
+                image,
+                direction: PixelSegmentSortDirection::Ascending,
+            },
+            prepared_row_data: Vec::new(),
+            segment_arrangement: PixelSegmentArrangement::Linear,
+        });
+    }
+
+    if selection_mode.selects_full_range() {
+        tracing::warn!(
+            "Selection mode {selection_mode:?} covers the full range, entire rows will be sorted."
+        );
+    }
+
+    selection_mode.validate_for_image(image.width(), image.height())?;
+
+    // Only the selection and the sorting contexts are computed from the premultiplied colors;
+    // the straight pixels are what gets sorted, as un-premultiplying them again would be lossy.
+    let straight_image = premultiply_alpha.then(|| image.clone());
+    if premultiply_alpha {
+        premultiply_image_alpha(&mut image);
+    }
+
+    // Percentiles are computed over the pixels of the image, so they must be converted
+    // before the image is gathered into (padded) angled lines. The same goes for combined
+    // selection modes, which are evaluated into a selection mask.
+    let selection_mode = match selection_mode {
+        PreparedSegmentSelectionMode::LuminancePercentileRange { low, high, invert } => {
+            let (low, high) =
+                LuminanceHistogram::from_image(&image).luminance_range_at_percentiles(low, high);
+
+            PreparedSegmentSelectionMode::LuminanceRange { low, high, invert }
+        }
+        PreparedSegmentSelectionMode::Combined { .. } => PreparedSegmentSelectionMode::MaskImage {
+            mask: Arc::new(selection_mode.compute_selection_mask(&image)),
+        },
+        selection_mode => selection_mode,
+    };
+
+    // Angled sorts are prepared as horizontal sorts of an image whose rows are the lines
+    // along the sorting angle, and are converted afterwards.
+    let (image, direction, angled_line_layout) = match direction {
+        ImageSortingDirection::Angled {
+            degrees,
+            direction: line_direction,
+        } => {
+            let line_layout = AngledLineLayout::new(image.width(), image.height(), degrees);
+            let line_image = line_layout.gather_aligned_line_image(&image);
+
+            (
+                line_image,
+                ImageSortingDirection::Horizontal(line_direction),
+                Some(line_layout),
+            )
+        }
+        _ => (image, direction, None),
+    };
+
+    let mut prepared_pixel_sort = match selection_mode {
+        PreparedSegmentSelectionMode::LuminanceRange { low, high, invert } => {
+            let target_luminance_range = low..=high;
+
+            prepare_axis_aligned_numeric_pixel_sort(
+                image,
+                direction,
+                |pixel: &Rgba<u8>| -> f32 { compute_rgba_relative_luminance(pixel) },
+                |pixel: &PixelWithContext<f32>| -> bool {
+                    target_luminance_range.contains(&pixel.context) != invert
+                },
+                |pixel| {
+                    let sorting_value = match sorting_mode {
+                        PreparedSegmentSortingMode::Luminance => pixel.context,
+                        sorting_mode => sorting_mode.compute_sorting_value(&pixel.pixel),
+                    };
+
+                    build_sorting_context(sorting_value, &pixel.pixel, tiebreak_mode)
+                },
+            )
+        }
+        PreparedSegmentSelectionMode::WeightedLuminanceRange {
+            weights,
+            low,
+            high,
+            invert,
+        } => {
+            let target_luminance_range = low..=high;
+
+            prepare_axis_aligned_numeric_pixel_sort(
+                image,
+                direction,
+                |pixel: &Rgba<u8>| -> f32 { compute_rgba_weighted_luminance(pixel, weights) },
+                |pixel: &PixelWithContext<f32>| -> bool {
+                    target_luminance_range.contains(&pixel.context) != invert
+                },
+                |pixel| {
+                    let sorting_value = match sorting_mode {
+                        PreparedSegmentSortingMode::WeightedLuminance {
+                            weights: sorting_weights,
+                        } if sorting_weights == weights => pixel.context,
+                        sorting_mode => sorting_mode.compute_sorting_value(&pixel.pixel),
+                    };
+
+                    build_sorting_context(sorting_value, &pixel.pixel, tiebreak_mode)
+                },
+            )
+        }
+        PreparedSegmentSelectionMode::HueRange { low, high, invert } => {
+            prepare_axis_aligned_numeric_pixel_sort(
+                image,
+                direction,
+                |pixel: &Rgba<u8>| -> f32 { compute_rgba_hsl_hue_gamma(pixel) },
+                |pixel: &PixelWithContext<f32>| -> bool {
+                    is_hue_in_range(pixel.context, low, high) != invert
+                },
+                |pixel| {
+                    let sorting_value = match sorting_mode {
+                        PreparedSegmentSortingMode::Hue => pixel.context,
+                        sorting_mode => sorting_mode.compute_sorting_value(&pixel.pixel),
+                    };
+
+                    build_sorting_context(sorting_value, &pixel.pixel, tiebreak_mode)
+                },
+            )
+        }
+        PreparedSegmentSelectionMode::SaturationRange { low, high, invert } => {
+            let target_saturation_range = low..=high;
 
             prepare_axis_aligned_numeric_pixel_sort(
                 image,
                 direction,
                 |pixel: &Rgba<u8>| -> f32 { compute_rgba_hsl_saturation(pixel) },
                 |pixel: &PixelWithContext<f32>| -> bool {
-                    target_saturation_range.contains(&pixel.context)
+                    target_saturation_range.contains(&pixel.context) != invert
                 },
-                |pixel| match sorting_mode {
-                    PreparedSegmentSortingMode::Luminance => {
-                        compute_rgba_relative_luminance(&pixel.pixel)
-                    }
-                    PreparedSegmentSortingMode::Hue => compute_rgba_hsl_hue(&pixel.pixel),
-                    PreparedSegmentSortingMode::Saturation => pixel.context,
+                |pixel| {
+                    let sorting_value = match sorting_mode {
+                        PreparedSegmentSortingMode::Saturation => pixel.context,
+                        sorting_mode => sorting_mode.compute_sorting_value(&pixel.pixel),
+                    };
+
+                    build_sorting_context(sorting_value, &pixel.pixel, tiebreak_mode)
+                },
+            )
+        }
+        PreparedSegmentSelectionMode::AlphaRange { low, high } => {
+            let target_alpha_range = low..=high;
+
+            prepare_axis_aligned_numeric_pixel_sort(
+                image,
+                direction,
+                |pixel: &Rgba<u8>| -> f32 { compute_rgba_normalized_alpha(pixel) },
+                |pixel: &PixelWithContext<f32>| -> bool {
+                    target_alpha_range.contains(&pixel.context)
+                },
+                |pixel| {
+                    let sorting_value = match sorting_mode {
+                        PreparedSegmentSortingMode::Alpha => pixel.context,
+                        sorting_mode => sorting_mode.compute_sorting_value(&pixel.pixel),
+                    };
+
+                    build_sorting_context(sorting_value, &pixel.pixel, tiebreak_mode)
+                },
+            )
+        }
+        PreparedSegmentSelectionMode::ColorDistanceRange {
+            target,
+            low,
+            high,
+            distance_metric,
+        } => {
+            let target_color_distance_range = low..=high;
+
+            prepare_axis_aligned_numeric_pixel_sort(
+                image,
+                direction,
+                |pixel: &Rgba<u8>| -> f32 {
+                    compute_rgba_color_distance(pixel, &target, distance_metric)
+                },
+                |pixel: &PixelWithContext<f32>| -> bool {
+                    target_color_distance_range.contains(&pixel.context)
+                },
+                |pixel| {
+                    // The selection context can only be reused when sorting
+                    // by the distance to the same color, measured the same way.
+                    let sorting_value = match sorting_mode {
+                        PreparedSegmentSortingMode::ColorDistance {
+                            target: sorting_target,
+                            distance_metric: sorting_distance_metric,
+                        } if sorting_target == target
+                            && sorting_distance_metric == distance_metric =>
+                        {
+                            pixel.context
+                        }
+                        sorting_mode => sorting_mode.compute_sorting_value(&pixel.pixel),
+                    };
+
+                    build_sorting_context(sorting_value, &pixel.pixel, tiebreak_mode)
                 },
             )
         }
@@ -690,14 +1980,154 @@ pub fn prepare_pixel_sort(
             low,
             high,
             segment_starts_on_image_edge: initial_segment_starts_on_image_edge,
-        } => prepare_axis_aligned_numeric_edge_detected_pixel_sort(
+            segment_arrangement,
+            invert_edges,
+            blur_sigma,
+            dilation,
+        } => {
+            let mut prepared_pixel_sort = prepare_axis_aligned_numeric_edge_detected_pixel_sort(
+                image,
+                low,
+                high,
+                initial_segment_starts_on_image_edge,
+                invert_edges,
+                blur_sigma,
+                dilation,
+                direction,
+                sorting_mode,
+                tiebreak_mode,
+            );
+
+            prepared_pixel_sort.segment_arrangement = segment_arrangement;
+            prepared_pixel_sort
+        }
+        PreparedSegmentSelectionMode::SobelEdges { threshold } => {
+            prepare_axis_aligned_numeric_sobel_edge_pixel_sort(
+                image,
+                threshold,
+                direction,
+                sorting_mode,
+                tiebreak_mode,
+            )
+        }
+        PreparedSegmentSelectionMode::HighPassRange {
+            blur_sigma,
+            low,
+            high,
+        } => prepare_axis_aligned_numeric_high_pass_pixel_sort(
             image,
+            blur_sigma,
             low,
             high,
-            initial_segment_starts_on_image_edge,
             direction,
             sorting_mode,
+            tiebreak_mode,
         ),
+        PreparedSegmentSelectionMode::LuminancePercentileRange { .. } => {
+            unreachable!("luminance percentiles are converted to a luminance range above")
+        }
+        PreparedSegmentSelectionMode::Combined { .. } => {
+            unreachable!("combined selection modes are converted to a selection mask above")
+        }
+        PreparedSegmentSelectionMode::MaskImage { mask } => {
+            let selection_mask = compute_binary_selection_mask(&mask);
+
+            // The mask is gathered into lines the same way as the image, with the padding
+            // being zero, i.e. never selected.
+            let selection_mask = match &angled_line_layout {
+                Some(line_layout) => line_layout.gather_aligned_line_image(&selection_mask),
+                None => selection_mask,
+            };
+
+            prepare_axis_aligned_numeric_mask_pixel_sort(
+                image,
+                selection_mask,
+                direction,
+                sorting_mode,
+                tiebreak_mode,
+            )
+        }
+    };
+
+    if let Some(line_layout) = angled_line_layout {
+        prepared_pixel_sort = convert_to_angled_pixel_sort(prepared_pixel_sort, line_layout);
+    }
+
+    if let Some(straight_image) = straight_image {
+        prepared_pixel_sort
+            .image
+            .replace_pixels_with(straight_image);
+    }
+
+    Ok(prepared_pixel_sort)
+}
+
+/// Converts a horizontal pixel sort of an aligned line image (see [`AngledLineLayout`])
+/// into an angled pixel sort, clipping each segment to the pixels of its line.
+///
+/// The padding around each line is transparent black, which the selection modes
+/// can select just like any other pixel, so it must never end up in a segment.
+fn convert_to_angled_pixel_sort<SortingContext>(
+    prepared_pixel_sort: PreparedPixelSort<SortingContext>,
+    line_layout: AngledLineLayout,
+) -> PreparedPixelSort<SortingContext>
+where
+    SortingContext: Send + Copy + PartialOrd,
+{
+    let PreparedPixelSortImage::PreparedHorizontal {
+        image: line_image,
+        direction,
+    } = prepared_pixel_sort.image
+    else {
+        unreachable!("line images are always prepared as horizontal pixel sorts");
+    };
+
+    let prepared_row_data = prepared_pixel_sort
+        .prepared_row_data
+        .into_iter()
+        .enumerate()
+        .map(|(line_index, row)| {
+            let line_column_range = line_layout.aligned_column_range(line_index);
+
+            let sorting_contexts_for_row = row
+                .sorting_contexts_for_row
+                .into_iter()
+                .filter_map(|segment| {
+                    let segment_start = segment.start_column_index;
+                    let segment_end = segment_start + segment.pixel_sorting_contexts.len();
+
+                    let clipped_start = segment_start.max(line_column_range.start);
+                    let clipped_end = segment_end.min(line_column_range.end);
+
+                    if clipped_start >= clipped_end {
+                        return None;
+                    }
+
+                    let mut pixel_sorting_contexts = segment.pixel_sorting_contexts;
+                    pixel_sorting_contexts.truncate(clipped_end - segment_start);
+                    pixel_sorting_contexts.drain(..(clipped_start - segment_start));
+
+                    Some(PreparedPixelSortSegment {
+                        start_column_index: clipped_start,
+                        pixel_sorting_contexts,
+                    })
+                })
+                .collect();
+
+            PreparedPixelSortRow {
+                sorting_contexts_for_row,
+            }
+        })
+        .collect();
+
+    PreparedPixelSort {
+        image: PreparedPixelSortImage::PreparedAngled {
+            line_image,
+            line_layout,
+            direction,
+        },
+        prepared_row_data,
+        segment_arrangement: prepared_pixel_sort.segment_arrangement,
     }
 }
 
@@ -707,16 +2137,131 @@ pub fn modify_prepared_pixel_sort_segments_with<SortingContext, SegmentsClosure>
     mut segment_modification_closure: SegmentsClosure,
 ) -> PreparedPixelSort<SortingContext>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
     SegmentsClosure: FnMut(&mut Vec<PreparedPixelSortRow<SortingContext>>),
 {
     segment_modification_closure(&mut prepared_pixel_sort.prepared_row_data);
     prepared_pixel_sort
 }
 
+/// Drops every prepared segment that is shorter than `minimum_segment_length` pixels,
+/// leaving its pixels unsorted. On noisy images, very short segments mostly add speckles.
+pub fn filter_prepared_segments_by_min_length<SortingContext>(
+    prepared_pixel_sort: PreparedPixelSort<SortingContext>,
+    minimum_segment_length: usize,
+) -> PreparedPixelSort<SortingContext>
+where
+    SortingContext: Send + Copy + PartialOrd,
+{
+    modify_prepared_pixel_sort_segments_with(prepared_pixel_sort, |prepared_rows| {
+        for prepared_row in prepared_rows {
+            prepared_row
+                .sorting_contexts_for_row
+                .retain(|segment| segment.pixel_sorting_contexts.len() >= minimum_segment_length);
+        }
+    })
+}
+
+/// Splits every prepared segment that is longer than `maximum_segment_length` pixels
+/// into consecutive segments of exactly `maximum_segment_length` pixels (the last one
+/// being shorter, if needed), e.g. to keep large bright areas from turning into one long smear.
+///
+/// Unlike [`randomize_prepared_segments`], the splits are fully deterministic.
+///
+/// # Panics
+/// Panics if `maximum_segment_length` is zero.
+pub fn split_prepared_segments_at_max_length<SortingContext>(
+    prepared_pixel_sort: PreparedPixelSort<SortingContext>,
+    maximum_segment_length: usize,
+) -> PreparedPixelSort<SortingContext>
+where
+    SortingContext: Send + Copy + PartialOrd,
+{
+    assert!(
+        maximum_segment_length > 0,
+        "maximum segment length must be positive"
+    );
+
+    modify_prepared_pixel_sort_segments_with(prepared_pixel_sort, |prepared_rows| {
+        for prepared_row in prepared_rows {
+            let original_segments = std::mem::take(&mut prepared_row.sorting_contexts_for_row);
+
+            for segment in original_segments {
+                if segment.pixel_sorting_contexts.len() <= maximum_segment_length {
+                    prepared_row.sorting_contexts_for_row.push(segment);
+                    continue;
+                }
+
+                let split_segments = segment
+                    .pixel_sorting_contexts
+                    .chunks(maximum_segment_length)
+                    .enumerate()
+                    .map(|(chunk_index, chunk)| PreparedPixelSortSegment {
+                        start_column_index: segment.start_column_index
+                            + chunk_index * maximum_segment_length,
+                        pixel_sorting_contexts: chunk.to_vec(),
+                    });
+
+                prepared_row.sorting_contexts_for_row.extend(split_segments);
+            }
+        }
+    })
+}
+
+
+/// Restricts every prepared segment to the pixels inside `region` (in original image
+/// coordinates), as if the pixels outside of it were never selected. Segments crossing
+/// the edge of the region are cut at it, so pixels outside the region are never sorted.
+pub fn restrict_prepared_segments_to_region<SortingContext>(
+    mut prepared_pixel_sort: PreparedPixelSort<SortingContext>,
+    region: ImageRegion,
+) -> PreparedPixelSort<SortingContext>
+where
+    SortingContext: Send + Copy + PartialOrd,
+{
+    let image = &prepared_pixel_sort.image;
+
+    for (row_index, prepared_row) in prepared_pixel_sort.prepared_row_data.iter_mut().enumerate() {
+        let original_segments = std::mem::take(&mut prepared_row.sorting_contexts_for_row);
+
+        for segment in original_segments {
+            let mut restricted_segment: Option<PreparedPixelSortSegment<SortingContext>> = None;
+
+            for (pixel_offset, sorting_context) in
+                segment.pixel_sorting_contexts.into_iter().enumerate()
+            {
+                let column_index = segment.start_column_index + pixel_offset;
+
+                let is_inside_region = image
+                    .original_pixel_coordinates(row_index, column_index)
+                    .is_some_and(|(x, y)| region.contains(x, y));
+
+                if is_inside_region {
+                    restricted_segment
+                        .get_or_insert_with(|| PreparedPixelSortSegment {
+                            start_column_index: column_index,
+                            pixel_sorting_contexts: Vec::new(),
+                        })
+                        .pixel_sorting_contexts
+                        .push(sorting_context);
+                } else if let Some(finished_segment) = restricted_segment.take() {
+                    prepared_row.sorting_contexts_for_row.push(finished_segment);
+                }
+            }
+
+            if let Some(finished_segment) = restricted_segment {
+                prepared_row.sorting_contexts_for_row.push(finished_segment);
+            }
+        }
+    }
+
+    prepared_pixel_sort
+}
+
 
 // TODO write random splitter of segments, then integrate it into the GUI
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SegmentRandomizationMode {
     Uniform {
         low_inclusive: usize,
@@ -733,20 +2278,46 @@ pub enum SegmentRandomizationMode {
 }
 
 
+/// Derives the seed of a single row's random number generator
+/// from the `base_seed` and the index of the row.
+///
+/// The row index is mixed into the base seed using the SplitMix64 finalizer, so that neighbouring
+/// rows get unrelated seeds, while a given row of *any* image always gets the same seed for the
+/// same base seed. This keeps randomized segment splits stable across e.g. frames of a video.
+fn derive_row_seed(base_seed: u64, row_index: usize) -> u64 {
+    let mut mixed_seed = base_seed ^ (row_index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    mixed_seed = (mixed_seed ^ (mixed_seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    mixed_seed = (mixed_seed ^ (mixed_seed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    mixed_seed ^ (mixed_seed >> 31)
+}
+
+/// Splits the prepared segments into smaller, randomly-sized segments, with lengths sampled
+/// from the distribution described by `mode`.
+///
+/// If a `seed` is provided, the result is reproducible: each row gets its own random number
+/// generator, seeded from `seed` and the row index (see [`derive_row_seed`]). Segments starting
+/// at the same column of the same row are therefore split at the same offsets in any image,
+/// as long as they are long enough. Without a seed, a random base seed is used.
 pub fn randomize_prepared_segments<SortingContext>(
     prepared_pixel_sort: PreparedPixelSort<SortingContext>,
     mode: SegmentRandomizationMode,
+    seed: Option<u64>,
 ) -> PreparedPixelSort<SortingContext>
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
 {
-    let mut thread_rng = rand::rng();
+    let base_seed = seed.unwrap_or_else(|| rand::rng().random());
 
     let image = prepared_pixel_sort.image;
     let mut randomized_prepared_rows =
         Vec::with_capacity(prepared_pixel_sort.prepared_row_data.len());
 
-    for row in prepared_pixel_sort.prepared_row_data {
+    for (row_index, row) in prepared_pixel_sort
+        .prepared_row_data
+        .into_iter()
+        .enumerate()
+    {
+        let mut row_rng = ChaCha8Rng::seed_from_u64(derive_row_seed(base_seed, row_index));
         let mut randomized_row_data = Vec::with_capacity(row.sorting_contexts_for_row.len());
 
         for original_segment in row.sorting_contexts_for_row {
@@ -765,7 +2336,7 @@ where
                     while current_pixel_offset < number_of_pixels_in_segment {
                         let pixels_left = number_of_pixels_in_segment - current_pixel_offset;
                         let target_segment_length =
-                            distribution.sample(&mut thread_rng).min(pixels_left);
+                            distribution.sample(&mut row_rng).min(pixels_left);
 
                         let mut randomized_partial_segment: Vec<SortingContext> =
                             Vec::with_capacity(target_segment_length);
@@ -796,9 +2367,8 @@ where
 
                     while current_pixel_offset < number_of_pixels_in_segment {
                         let pixels_left = number_of_pixels_in_segment - current_pixel_offset;
-                        let target_segment_length = (distribution.sample(&mut thread_rng).round()
-                            as usize)
-                            .min(pixels_left);
+                        let target_segment_length =
+                            (distribution.sample(&mut row_rng).round() as usize).min(pixels_left);
 
                         let mut randomized_partial_segment: Vec<SortingContext> =
                             Vec::with_capacity(target_segment_length);
@@ -830,22 +2400,88 @@ where
     PreparedPixelSort {
         image,
         prepared_row_data: randomized_prepared_rows,
+        segment_arrangement: prepared_pixel_sort.segment_arrangement,
     }
 }
 
 
 
+/// Returns `true` if the given sorting contexts are already ordered in the given direction.
+///
+/// Always returns `false` for [`PixelSegmentSortDirection::Reverse`], as reversing a segment
+/// changes it regardless of its contents.
+fn is_segment_already_sorted<SortingContext>(
+    pixel_sorting_contexts: &[SortingContext],
+    sorting_direction: PixelSegmentSortDirection,
+) -> bool
+where
+    SortingContext: PartialOrd,
+{
+    match sorting_direction {
+        PixelSegmentSortDirection::Ascending => {
+            pixel_sorting_contexts.is_sorted_by(|first, second| first <= second)
+        }
+        PixelSegmentSortDirection::Descending => {
+            pixel_sorting_contexts.is_sorted_by(|first, second| first >= second)
+        }
+        PixelSegmentSortDirection::Reverse => false,
+    }
+}
+
+/// Sets the alpha of every pixel in the given image row that is not part of
+/// any of the `prepared_row`'s segments to zero.
+fn make_non_segment_pixels_transparent<SortingContext>(
+    image_row_contiguous_flat_buffer: &mut [u8],
+    image_layout: SampleLayout,
+    prepared_row: &PreparedPixelSortRow<SortingContext>,
+) where
+    SortingContext: Send,
+{
+    let image_row_width = image_layout.width as usize;
+    let mut make_columns_transparent = |columns: std::ops::Range<usize>| {
+        for column_index in columns {
+            let alpha_sample_index =
+                column_index * image_layout.width_stride + 3 * image_layout.channel_stride;
+
+            image_row_contiguous_flat_buffer[alpha_sample_index] = 0;
+        }
+    };
+
+    // Segments are stored left to right, so the gaps between them are the non-segment pixels.
+    let mut first_uncovered_column_index = 0;
+
+    for segment in &prepared_row.sorting_contexts_for_row {
+        make_columns_transparent(first_uncovered_column_index..segment.start_column_index);
+
+        first_uncovered_column_index = first_uncovered_column_index
+            .max(segment.start_column_index + segment.pixel_sorting_contexts.len());
+    }
+
+    make_columns_transparent(first_uncovered_column_index..image_row_width);
+}
+
 fn execute_prepared_pixel_sort_on_image_row<SortingContext>(
     image_row_contiguous_flat_buffer: &mut [u8],
     image_layout: SampleLayout,
     sorting_direction: PixelSegmentSortDirection,
+    segment_arrangement: PixelSegmentArrangement,
     prepared_row: PreparedPixelSortRow<SortingContext>,
+    output_only_segments: bool,
+    stable_sort: bool,
 ) where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
 {
     let image_channel_stride = image_layout.channel_stride;
     let image_number_of_channels = image_layout.channels as usize;
 
+    if output_only_segments {
+        make_non_segment_pixels_transparent(
+            image_row_contiguous_flat_buffer,
+            image_layout,
+            &prepared_row,
+        );
+    }
+
     for segment in prepared_row.sorting_contexts_for_row {
         let start_column_index = segment.start_column_index;
 
@@ -856,6 +2492,22 @@ fn execute_prepared_pixel_sort_on_image_row<SortingContext>(
             segment.pixel_sorting_contexts.len() * image_channel_stride * image_number_of_channels,
         );
 
+        // Reversing doesn't compare any pixel properties, so we can skip pairing
+        // pixels with their sorting contexts entirely.
+        if sorting_direction == PixelSegmentSortDirection::Reverse {
+            reverse_pixel_segment_in_place(clipped_segment_slice, image_layout.width_stride);
+            continue;
+        }
+
+        // Segments that are already in order (common in e.g. gradients) don't need to be sorted
+        // and copied back; their current order is just as valid a result as any sorted one.
+        // This does not hold if the sorted pixels are to be rearranged afterwards.
+        if segment_arrangement == PixelSegmentArrangement::Linear
+            && is_segment_already_sorted(&segment.pixel_sorting_contexts, sorting_direction)
+        {
+            continue;
+        }
+
         let contextualized_pixels: Vec<PixelWithContext<SortingContext>> = clipped_segment_slice
             .par_chunks(image_layout.width_stride)
             .map(|pixel_data| Rgba([pixel_data[0], pixel_data[1], pixel_data[2], pixel_data[3]]))
@@ -866,6 +2518,8 @@ fn execute_prepared_pixel_sort_on_image_row<SortingContext>(
         sort_with_numeric_context_and_reapply_pixel_segment(
             contextualized_pixels,
             sorting_direction,
+            segment_arrangement,
+            stable_sort,
             clipped_segment_slice,
             image_layout,
         );
@@ -873,14 +2527,75 @@ fn execute_prepared_pixel_sort_on_image_row<SortingContext>(
 }
 
 
-#[allow(clippy::let_and_return)]
+/// Executes the prepared pixel sort, returning the sorted image.
+///
+/// If `output_only_segments` is set, all pixels that were not part of any sorted segment
+/// are made fully transparent, leaving only the sorted segments visible
+/// (e.g. for layering the result over the original image in an external editor).
+///
+/// If `stable_sort` is set, pixels with equal sorting contexts keep their original order
+/// within each segment, which is reproducible, but somewhat slower than the default
+/// unstable sort (which can reorder such pixels arbitrarily).
 pub fn execute_axis_aligned_prepared_pixel_sort<SortingContext>(
     prepared_pixel_sort: PreparedPixelSort<SortingContext>,
+    output_only_segments: bool,
+    stable_sort: bool,
 ) -> RgbaImage
 where
-    SortingContext: Send + num::Num + Copy + PartialOrd,
+    SortingContext: Send + Copy + PartialOrd,
+{
+    execute_cancellable_axis_aligned_prepared_pixel_sort(
+        prepared_pixel_sort,
+        output_only_segments,
+        stable_sort,
+        || false,
+        |_| {},
+    )
+    .expect("pixel sort can't be cancelled")
+}
+
+/// Executes the prepared pixel sort like [`execute_axis_aligned_prepared_pixel_sort`],
+/// but calls `is_cancelled` before sorting each row (possibly from multiple threads)
+/// and stops early once it returns `true`.
+///
+/// After each sorted row, `report_progress` is called with the fraction of rows
+/// sorted so far (from `0.0` to `1.0`). As rows are sorted in parallel, the calls
+/// can come from multiple threads and are not necessarily in increasing order.
+///
+/// Returns `None` if the sort was cancelled, in which case the partially sorted image
+/// is discarded.
+#[allow(clippy::let_and_return)]
+pub fn execute_cancellable_axis_aligned_prepared_pixel_sort<
+    SortingContext,
+    CancelledClosure,
+    ProgressClosure,
+>(
+    prepared_pixel_sort: PreparedPixelSort<SortingContext>,
+    output_only_segments: bool,
+    stable_sort: bool,
+    is_cancelled: CancelledClosure,
+    report_progress: ProgressClosure,
+) -> Option<RgbaImage>
+where
+    SortingContext: Send + Copy + PartialOrd,
+    CancelledClosure: Fn() -> bool + Sync,
+    ProgressClosure: Fn(f32) + Sync,
 {
-    match prepared_pixel_sort.image {
+    let total_rows = prepared_pixel_sort.prepared_row_data.len();
+    let sorted_rows = AtomicUsize::new(0);
+
+    let report_sorted_row = || {
+        let sorted_rows = sorted_rows.fetch_add(1, Ordering::Relaxed) + 1;
+        report_progress(sorted_rows as f32 / total_rows as f32);
+    };
+
+    let sorted_image = match prepared_pixel_sort.image {
+        // Images without any pixels are prepared without any rows (see `prepare_pixel_sort`).
+        PreparedPixelSortImage::PreparedHorizontal { image, .. }
+            if image.width() == 0 || image.height() == 0 =>
+        {
+            image
+        }
         PreparedPixelSortImage::PreparedHorizontal {
             mut image,
             direction,
@@ -904,14 +2619,25 @@ where
                 .as_mut_slice()
                 .par_chunks_mut(image_layout.height_stride)
                 .zip(prepared_pixel_sort.prepared_row_data)
-                .for_each(|(row_buffer, prepared_segments)| {
+                .try_for_each(|(row_buffer, prepared_segments)| {
+                    if is_cancelled() {
+                        return None;
+                    }
+
                     execute_prepared_pixel_sort_on_image_row(
                         row_buffer,
                         image_layout,
                         direction,
+                        prepared_pixel_sort.segment_arrangement,
                         prepared_segments,
+                        output_only_segments,
+                        stable_sort,
                     );
-                });
+
+                    report_sorted_row();
+
+                    Some(())
+                })?;
 
             image
         }
@@ -941,18 +2667,82 @@ where
                 .as_mut_slice()
                 .par_chunks_mut(image_layout.height_stride)
                 .zip(prepared_pixel_sort.prepared_row_data)
-                .for_each(|(row_buffer, prepared_segments)| {
+                .try_for_each(|(row_buffer, prepared_segments)| {
+                    if is_cancelled() {
+                        return None;
+                    }
+
                     execute_prepared_pixel_sort_on_image_row(
                         row_buffer,
                         image_layout,
                         direction,
+                        prepared_pixel_sort.segment_arrangement,
                         prepared_segments,
+                        output_only_segments,
+                        stable_sort,
                     );
-                });
+
+                    report_sorted_row();
+
+                    Some(())
+                })?;
 
             let inverse_rotated_image = image::imageops::rotate270(&rotated_image);
 
             inverse_rotated_image
         }
-    }
+        PreparedPixelSortImage::PreparedAngled {
+            mut line_image,
+            line_layout,
+            direction,
+        } => {
+            assert_eq!(
+                prepared_pixel_sort.prepared_row_data.len(),
+                line_image.height() as usize
+            );
+
+            // For performance reasons, we'll operate directly on the underlying RGBA8 image buffer.
+            // The rows of this buffer correspond to lines along the sorting angle
+            // in the original image (we'll scatter them back afterwards).
+            let mut flat_samples = line_image.as_flat_samples_mut();
+
+            // This is known to us, since we are expecting RGBA8.
+            // Still, we'll use the values from the `layout` struct directly from here on.
+            assert!(!flat_samples.has_aliased_samples());
+            assert!(flat_samples.layout.channel_stride == 1);
+            assert!(flat_samples.layout.channels == 4);
+
+            let image_layout = flat_samples.layout;
+
+            // The pixel sorting is performed here in parallel for each row of the image
+            // using `rayon`'s parallel iterators.
+            flat_samples
+                .as_mut_slice()
+                .par_chunks_mut(image_layout.height_stride)
+                .zip(prepared_pixel_sort.prepared_row_data)
+                .try_for_each(|(row_buffer, prepared_segments)| {
+                    if is_cancelled() {
+                        return None;
+                    }
+
+                    execute_prepared_pixel_sort_on_image_row(
+                        row_buffer,
+                        image_layout,
+                        direction,
+                        prepared_pixel_sort.segment_arrangement,
+                        prepared_segments,
+                        output_only_segments,
+                        stable_sort,
+                    );
+
+                    report_sorted_row();
+
+                    Some(())
+                })?;
+
+            line_layout.scatter_aligned_line_image(&line_image)
+        }
+    };
+
+    Some(sorted_image)
 }
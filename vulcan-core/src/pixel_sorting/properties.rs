@@ -1,20 +1,99 @@
 use std::ops::Rem;
 
-use image::Rgba;
+use image::{Luma, Primitive, Rgba, RgbaImage};
 use num::Zero;
+use serde::{Deserialize, Serialize};
 
 
-/// Converts a gamma-encoded `u8` (`0..=255`) sRGB value to a linear `f32` (`0.0..=1.0`) sRGB value.
+/// Converts a gamma-encoded `u8` (`0..=255`) sRGB value to a linear `f32` (`0.0..=1.0`) sRGB value,
+/// using the piecewise sRGB transfer function.
 ///
-/// See <https://en.wikipedia.org/wiki/Relative_luminance> for more information.
-///
-/// TODO This can be improved: ^2.2 does not fully match the gamma->linear conversion,
-///      see the transfer function here: <https://en.wikipedia.org/wiki/SRGB>
-///      and here <https://stackoverflow.com/questions/596216/formula-to-determine-perceived-brightness-of-rgb-color>.
+/// See <https://en.wikipedia.org/wiki/SRGB#Transfer_function_(%22gamma%22)> for more information.
 #[inline(always)]
-fn convert_gamma_encoded_srgb_u8_to_linear_f32(value: u8) -> f32 {
+pub fn convert_gamma_encoded_srgb_u8_to_linear_f32(value: u8) -> f32 {
     let input_value_as_f32 = value as f32 / u8::MAX as f32;
-    input_value_as_f32.powf(2.2)
+
+    if input_value_as_f32 <= 0.04045 {
+        input_value_as_f32 / 12.92
+    } else {
+        ((input_value_as_f32 + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Normalizes a gamma-encoded sRGB sample of any bit depth (e.g. `u8` or `u16`)
+/// to an `f32` in the range `0.0..=1.0`, without linearizing it.
+#[inline(always)]
+pub fn normalize_srgb_sample_to_f32<S>(value: S) -> f32
+where
+    S: Primitive,
+{
+    value.to_f32().unwrap_or_default() / S::DEFAULT_MAX_VALUE.to_f32().unwrap_or(1.0)
+}
+
+/// Converts a gamma-encoded sRGB sample of any bit depth (e.g. `u8` or `u16`)
+/// to a linear `f32` (`0.0..=1.0`) sRGB value, using the piecewise sRGB transfer function.
+///
+/// See [`convert_gamma_encoded_srgb_u8_to_linear_f32`] for more information.
+#[inline(always)]
+pub fn convert_gamma_encoded_srgb_sample_to_linear_f32<S>(value: S) -> f32
+where
+    S: Primitive,
+{
+    let input_value_as_f32 = normalize_srgb_sample_to_f32(value);
+
+    if input_value_as_f32 <= 0.04045 {
+        input_value_as_f32 / 12.92
+    } else {
+        ((input_value_as_f32 + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+
+/// The weights of the linear red, green and blue components when computing luminance
+/// (see [`compute_rgba_weighted_luminance`]).
+///
+/// The weights should sum up to `1.0`, otherwise luminances can end up outside of `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LuminanceWeights {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+}
+
+impl LuminanceWeights {
+    /// The ITU-R BT.709 (and sRGB) weights, used by [`compute_rgba_relative_luminance`].
+    pub const REC_709: Self = Self {
+        red: 0.2126,
+        green: 0.7152,
+        blue: 0.0722,
+    };
+
+    /// The ITU-R BT.601 weights, which weigh red more and green less than [`Self::REC_709`].
+    pub const REC_601: Self = Self {
+        red: 0.299,
+        green: 0.587,
+        blue: 0.114,
+    };
+}
+
+impl Default for LuminanceWeights {
+    fn default() -> Self {
+        Self::REC_709
+    }
+}
+
+
+/// How the distance between two colors is measured (see [`compute_rgba_color_distance`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DistanceMetric {
+    /// The Euclidean distance in linear sRGB (see [`compute_rgba_linear_color_distance`]).
+    #[default]
+    Rgb,
+
+    /// The Euclidean distance in OKLab, i.e. a perceptual ΔE
+    /// (see [`compute_rgba_oklab_color_distance`]), which follows how different two colors
+    /// look much more closely than [`Self::Rgb`].
+    OkLab,
 }
 
 
@@ -23,38 +102,87 @@ fn convert_gamma_encoded_srgb_u8_to_linear_f32(value: u8) -> f32 {
 ///
 ///
 /// [^relative-luminance]: See <https://www.w3.org/WAI/GL/wiki/Relative_luminance> for more information.
-#[allow(clippy::let_and_return)]
-pub fn compute_rgba_relative_luminance(pixel: &Rgba<u8>) -> f32 {
-    let linear_r = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[0]);
-    let linear_g = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[1]);
-    let linear_b = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[2]);
+pub fn compute_rgba_relative_luminance<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    compute_rgba_weighted_luminance(pixel, LuminanceWeights::REC_709)
+}
 
-    let relative_luminance_up_to_u8_range =
-        0.2126f32 * linear_r + 0.7152f32 * linear_g + 0.0722f32 * linear_b;
 
-    relative_luminance_up_to_u8_range
+/// Computes the relative luminance of a grayscale pixel as an `f32` in the range `0.0..=1.0`.
+///
+/// As the luminance weights sum up to `1.0`, this is simply the linearized value of
+/// the single channel, i.e. the same as [`compute_rgba_relative_luminance`]
+/// of an RGBA pixel with all three color components set to that value.
+pub fn compute_luma_relative_luminance(pixel: &Luma<u8>) -> f32 {
+    convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[0])
 }
 
 
+/// Computes the luminance of an RGBA pixel as the weighted sum of its linear sRGB components,
+/// as an `f32` in the range `0.0..=1.0` (if the `weights` sum up to `1.0`).
 #[allow(clippy::let_and_return)]
-pub fn compute_rgba_hsl_hue(pixel: &Rgba<u8>) -> f32 {
-    let linear_r = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[0]);
-    let linear_g = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[1]);
-    let linear_b = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[2]);
+pub fn compute_rgba_weighted_luminance<S>(pixel: &Rgba<S>, weights: LuminanceWeights) -> f32
+where
+    S: Primitive,
+{
+    let linear_r = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[0]);
+    let linear_g = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[1]);
+    let linear_b = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[2]);
+
+    let weighted_luminance =
+        weights.red * linear_r + weights.green * linear_g + weights.blue * linear_b;
+
+    weighted_luminance
+}
 
-    let max_value = linear_r.max(linear_g).max(linear_b);
-    let min_value = linear_r.min(linear_g).min(linear_b);
+
+/// Computes the HSL hue of an RGBA pixel from its *linearized* sRGB components,
+/// as an `f32` in the range `0.0..360.0`.
+///
+/// HSL hue is conventionally defined on gamma-encoded values, and linearizing them
+/// skews the hue angle; prefer [`compute_rgba_hsl_hue_gamma`] unless this is intended.
+pub fn compute_rgba_hsl_hue<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let linear_r = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[0]);
+    let linear_g = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[1]);
+    let linear_b = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[2]);
+
+    compute_hsl_hue(linear_r, linear_g, linear_b)
+}
+
+/// Computes the HSL hue of an RGBA pixel from its gamma-encoded sRGB components,
+/// as an `f32` in the range `0.0..360.0`.
+pub fn compute_rgba_hsl_hue_gamma<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let r = normalize_srgb_sample_to_f32(pixel.0[0]);
+    let g = normalize_srgb_sample_to_f32(pixel.0[1]);
+    let b = normalize_srgb_sample_to_f32(pixel.0[2]);
+
+    compute_hsl_hue(r, g, b)
+}
+
+/// Computes the HSL hue (`0.0..360.0`) of the given red, green and blue components (`0.0..=1.0`).
+#[allow(clippy::let_and_return)]
+fn compute_hsl_hue(red: f32, green: f32, blue: f32) -> f32 {
+    let max_value = red.max(green).max(blue);
+    let min_value = red.min(green).min(blue);
 
     let chroma = max_value - min_value;
 
     let hue_prime = if chroma.is_zero() {
         0f32
-    } else if max_value == linear_r {
-        ((linear_g - linear_b) / chroma).rem(6f32)
-    } else if max_value == linear_g {
-        ((linear_b - linear_r) / chroma) + 2f32
-    } else if max_value == linear_b {
-        ((linear_r - linear_g) / chroma) + 4f32
+    } else if max_value == red {
+        ((green - blue) / chroma).rem(6f32)
+    } else if max_value == green {
+        ((blue - red) / chroma) + 2f32
+    } else if max_value == blue {
+        ((red - green) / chroma) + 4f32
     } else {
         unreachable!();
     };
@@ -72,12 +200,27 @@ pub fn compute_rgba_hsl_hue(pixel: &Rgba<u8>) -> f32 {
     hue
 }
 
+/// Returns `true` if `hue` lies inside the hue range from `low` to `high` (both inclusive).
+///
+/// Hue is circular, so if `low` is larger than `high`, the range wraps around 360 degrees,
+/// e.g. `350.0` to `20.0` selects the reds on both sides of zero.
+pub fn is_hue_in_range(hue: f32, low: f32, high: f32) -> bool {
+    if low <= high {
+        (low..=high).contains(&hue)
+    } else {
+        hue >= low || hue <= high
+    }
+}
+
 
 #[allow(clippy::let_and_return)]
-pub fn compute_rgba_hsl_lightness(pixel: &Rgba<u8>) -> f32 {
-    let linear_r = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[0]);
-    let linear_g = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[1]);
-    let linear_b = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[2]);
+pub fn compute_rgba_hsl_lightness<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let linear_r = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[0]);
+    let linear_g = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[1]);
+    let linear_b = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[2]);
 
     let max_value = linear_r.max(linear_g).max(linear_b);
     let min_value = linear_r.min(linear_g).min(linear_b);
@@ -88,11 +231,146 @@ pub fn compute_rgba_hsl_lightness(pixel: &Rgba<u8>) -> f32 {
 }
 
 
+/// Computes the alpha of an RGBA pixel, as an `f32` in the range `0.0..=1.0`.
+pub fn compute_rgba_normalized_alpha<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    normalize_srgb_sample_to_f32(pixel.0[3])
+}
+
+
+/// Computes the Euclidean distance between the colors of two RGBA pixels in linear sRGB,
+/// normalized to an `f32` in the range `0.0..=1.0` (`1.0` being the distance between black
+/// and white). Alpha is ignored.
+pub fn compute_rgba_linear_color_distance<S>(pixel: &Rgba<S>, target: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let squared_distance: f32 = (0..3)
+        .map(|channel_index| {
+            let channel_difference =
+                convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[channel_index])
+                    - convert_gamma_encoded_srgb_sample_to_linear_f32(target.0[channel_index]);
+
+            channel_difference * channel_difference
+        })
+        .sum();
+
+    (squared_distance / 3f32).sqrt()
+}
+
+
+/// Converts an RGBA pixel to the OKLab[^oklab] color space (via linear sRGB),
+/// returning its `L` (`0.0..=1.0`), `a` and `b` components. Alpha is ignored.
+///
+///
+/// [^oklab]: See <https://bottosson.github.io/posts/oklab/> for more information.
+fn compute_rgba_oklab<S>(pixel: &Rgba<S>) -> (f32, f32, f32)
+where
+    S: Primitive,
+{
+    let linear_r = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[0]);
+    let linear_g = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[1]);
+    let linear_b = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[2]);
+
+    let long_cone =
+        (0.412_221_47 * linear_r + 0.536_332_55 * linear_g + 0.051_445_995 * linear_b).cbrt();
+    let medium_cone =
+        (0.211_903_5 * linear_r + 0.680_699_5 * linear_g + 0.107_396_96 * linear_b).cbrt();
+    let short_cone =
+        (0.088_302_46 * linear_r + 0.281_718_85 * linear_g + 0.629_978_7 * linear_b).cbrt();
+
+    let lightness =
+        0.210_454_26 * long_cone + 0.793_617_8 * medium_cone - 0.004_072_047 * short_cone;
+    let a = 1.977_998_5 * long_cone - 2.428_592_2 * medium_cone + 0.450_593_7 * short_cone;
+    let b = 0.025_904_037 * long_cone + 0.782_771_77 * medium_cone - 0.808_675_77 * short_cone;
+
+    (lightness, a, b)
+}
+
+/// Computes the OKLab lightness of an RGBA pixel, as an `f32` in the range `0.0..=1.0`.
+///
+/// Unlike HSL lightness, OKLab lightness is perceptually uniform.
+pub fn compute_rgba_oklab_lightness<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    compute_rgba_oklab(pixel).0
+}
+
+/// Computes the OKLab chroma (i.e. colorfulness) of an RGBA pixel, as an `f32`
+/// in the range `0.0..=0.33` (roughly; the most saturated sRGB colors end up around `0.32`).
+pub fn compute_rgba_oklab_chroma<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let (_, a, b) = compute_rgba_oklab(pixel);
+
+    a.hypot(b)
+}
+
+/// Computes the OKLab hue of an RGBA pixel, as an `f32` in the range `0.0..360.0`.
+///
+/// Achromatic pixels (greys) have no meaningful hue, and end up with a hue near `0.0`.
+pub fn compute_rgba_oklab_hue<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let (_, a, b) = compute_rgba_oklab(pixel);
+
+    let hue = b.atan2(a).to_degrees();
+
+    if hue < 0.0 { hue + 360.0 } else { hue }
+}
+
+/// Computes the perceptual difference (ΔE) between the colors of two RGBA pixels,
+/// as the Euclidean distance between them in OKLab. Alpha is ignored.
+///
+/// Like [`compute_rgba_linear_color_distance`], black and white are `1.0` apart,
+/// which is the largest distance between any two sRGB colors.
+pub fn compute_rgba_oklab_color_distance<S>(pixel: &Rgba<S>, target: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let (pixel_lightness, pixel_a, pixel_b) = compute_rgba_oklab(pixel);
+    let (target_lightness, target_a, target_b) = compute_rgba_oklab(target);
+
+    let lightness_difference = pixel_lightness - target_lightness;
+    let a_difference = pixel_a - target_a;
+    let b_difference = pixel_b - target_b;
+
+    (lightness_difference * lightness_difference
+        + a_difference * a_difference
+        + b_difference * b_difference)
+        .sqrt()
+}
+
+/// Computes the distance between the colors of two RGBA pixels as measured by `metric`,
+/// as an `f32` in the range `0.0..=1.0`. Alpha is ignored.
+pub fn compute_rgba_color_distance<S>(
+    pixel: &Rgba<S>,
+    target: &Rgba<S>,
+    metric: DistanceMetric,
+) -> f32
+where
+    S: Primitive,
+{
+    match metric {
+        DistanceMetric::Rgb => compute_rgba_linear_color_distance(pixel, target),
+        DistanceMetric::OkLab => compute_rgba_oklab_color_distance(pixel, target),
+    }
+}
+
+
 #[allow(clippy::let_and_return)]
-pub fn compute_rgba_hsl_saturation(pixel: &Rgba<u8>) -> f32 {
-    let linear_r = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[0]);
-    let linear_g = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[1]);
-    let linear_b = convert_gamma_encoded_srgb_u8_to_linear_f32(pixel.0[2]);
+pub fn compute_rgba_hsl_saturation<S>(pixel: &Rgba<S>) -> f32
+where
+    S: Primitive,
+{
+    let linear_r = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[0]);
+    let linear_g = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[1]);
+    let linear_b = convert_gamma_encoded_srgb_sample_to_linear_f32(pixel.0[2]);
 
     let max_value = linear_r.max(linear_g).max(linear_b);
     let min_value = linear_r.min(linear_g).min(linear_b);
@@ -108,3 +386,72 @@ pub fn compute_rgba_hsl_saturation(pixel: &Rgba<u8>) -> f32 {
 
     saturation
 }
+
+
+/// The number of equally wide bins of a [`LuminanceHistogram`].
+const LUMINANCE_HISTOGRAM_BIN_COUNT: usize = 1024;
+
+/// A histogram of the relative luminances (see [`compute_rgba_relative_luminance`])
+/// of an image, used to convert luminance percentiles into absolute luminances.
+pub struct LuminanceHistogram {
+    bin_counts: Vec<u64>,
+    total_count: u64,
+}
+
+impl LuminanceHistogram {
+    pub fn from_luminances<I>(luminances: I) -> Self
+    where
+        I: IntoIterator<Item = f32>,
+    {
+        let mut bin_counts = vec![0; LUMINANCE_HISTOGRAM_BIN_COUNT];
+        let mut total_count = 0;
+
+        for luminance in luminances {
+            let bin_index =
+                (luminance.clamp(0.0, 1.0) * LUMINANCE_HISTOGRAM_BIN_COUNT as f32) as usize;
+
+            bin_counts[bin_index.min(LUMINANCE_HISTOGRAM_BIN_COUNT - 1)] += 1;
+            total_count += 1;
+        }
+
+        Self {
+            bin_counts,
+            total_count,
+        }
+    }
+
+    pub fn from_image(image: &RgbaImage) -> Self {
+        Self::from_luminances(image.pixels().map(compute_rgba_relative_luminance))
+    }
+
+    /// Returns the index of the bin that contains the pixel at the given percentile.
+    fn bin_index_at_percentile(&self, percentile: f32) -> usize {
+        let target_count =
+            (percentile.clamp(0.0, 100.0) as f64 / 100.0 * self.total_count as f64) as u64;
+
+        let mut cumulative_count = 0;
+        for (bin_index, bin_count) in self.bin_counts.iter().enumerate() {
+            cumulative_count += bin_count;
+
+            if cumulative_count > target_count {
+                return bin_index;
+            }
+        }
+
+        LUMINANCE_HISTOGRAM_BIN_COUNT - 1
+    }
+
+    /// Converts the percentiles `low` and `high` (`0.0..=100.0`) of the luminance distribution
+    /// into an (inclusive) range of absolute relative luminances (`0.0..=1.0`).
+    ///
+    /// The range is widened to the edges of the bins the percentiles fall into,
+    /// so it is only as precise as the bins are wide.
+    pub fn luminance_range_at_percentiles(&self, low: f32, high: f32) -> (f32, f32) {
+        let bin_width = 1.0 / LUMINANCE_HISTOGRAM_BIN_COUNT as f32;
+
+        (
+            self.bin_index_at_percentile(low) as f32 * bin_width,
+            (self.bin_index_at_percentile(high) + 1) as f32 * bin_width,
+        )
+    }
+}
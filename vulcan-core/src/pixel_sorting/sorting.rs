@@ -1,28 +1,66 @@
 use std::cmp::Ordering;
 
-use image::flat::SampleLayout;
+use image::{Primitive, flat::SampleLayout};
 
 use crate::pixel_sorting::{
     PixelRefWithContext,
+    PixelSegmentArrangement,
     PixelSegmentSortDirection,
     PixelWithContext,
     copy_pixel_segment_onto_image,
 };
 
+/// Compares two numeric sorting contexts (e.g. `f32`s) for sorting.
+///
+/// Unlike `partial_cmp(..).unwrap_or(Ordering::Equal)`, this is a total order for single-number
+/// contexts: values that can't even be compared to themselves (i.e. NaN) are equal to each other
+/// and smaller than every other value. Sorting with an inconsistent comparator can otherwise
+/// scramble the segment nondeterministically. Contexts made of multiple numbers (e.g. tuples)
+/// can't be ordered totally this way, so they should not contain NaN in the first place.
+pub fn compare_numeric_sorting_contexts<C>(first: &C, second: &C) -> Ordering
+where
+    C: PartialOrd,
+{
+    match first.partial_cmp(second) {
+        Some(ordering) => ordering,
+        None => {
+            let first_is_nan = first.partial_cmp(first).is_none();
+            let second_is_nan = second.partial_cmp(second).is_none();
+
+            second_is_nan.cmp(&first_is_nan)
+        }
+    }
+}
+
+/// Sorts `items` with the given comparator, either stably (keeping the original order of items
+/// that compare as equal, e.g. pixels with the same context) or unstably, which is faster,
+/// but can reorder equal items arbitrarily.
+pub fn sort_by_with_stability<T, F>(items: &mut [T], stable: bool, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if stable {
+        items.sort_by(compare);
+    } else {
+        items.sort_unstable_by(compare);
+    }
+}
+
 /// Sorts the given contextualized `pixels` using the sorting closure,
-/// then copies the sorted pixels onto the target image, provided as a flat RGBA8 buffer
-/// (`target_image_contiguous_flat_buffer`).
+/// then copies the sorted pixels onto the target image, provided as a flat RGBA buffer
+/// (`target_image_contiguous_flat_buffer`, e.g. RGBA8 or RGBA16).
 ///
 /// # Panics
 /// The length of `target_image_contiguous_flat_buffer` must be precisely large
 /// enough to fit all the `source_pixels`; the function will otherwise panic.
-pub fn sort_with_closure_and_reapply_pixel_segment<C, S>(
-    mut pixels: Vec<PixelWithContext<C>>,
-    target_image_contiguous_flat_buffer: &mut [u8],
+pub fn sort_with_closure_and_reapply_pixel_segment<C, S, F>(
+    mut pixels: Vec<PixelWithContext<C, S>>,
+    target_image_contiguous_flat_buffer: &mut [S],
     target_image_layout: SampleLayout,
-    segment_sorting_closure: S,
+    segment_sorting_closure: F,
 ) where
-    S: FnOnce(&mut [PixelWithContext<C>]),
+    S: Primitive,
+    F: FnOnce(&mut [PixelWithContext<C, S>]),
 {
     segment_sorting_closure(&mut pixels);
 
@@ -65,7 +103,12 @@ pub fn sort_ref_with_closure_and_reapply_pixel_segment<C, S>(
 /// copies the sorted pixels onto the target image, provided as a flat RGBA8 buffer
 /// (`target_image_contiguous_flat_buffer`). This is a specialized version of
 /// [`sort_with_closure_and_reapply_pixel_segment`], for cases where the pixel context
-/// is a number, e.g. an `f32`.
+/// is a number, e.g. an `f32`, or a tuple of numbers (which is compared lexicographically).
+///
+/// The sorted pixels are laid out according to `arrangement` (see [`arrange_sorted_pixels`]),
+/// which is ignored for [`PixelSegmentSortDirection::Reverse`], as nothing is sorted there.
+/// If `stable_sort` is set, pixels with equal contexts keep their original order
+/// (see [`sort_by_with_stability`]).
 ///
 /// # Panics
 /// The length of `target_image_contiguous_flat_buffer` must be precisely large
@@ -73,10 +116,12 @@ pub fn sort_ref_with_closure_and_reapply_pixel_segment<C, S>(
 pub fn sort_with_numeric_context_and_reapply_pixel_segment<C>(
     mut pixels: Vec<PixelWithContext<C>>,
     sort_direction: PixelSegmentSortDirection,
+    arrangement: PixelSegmentArrangement,
+    stable_sort: bool,
     target_image_contiguous_flat_buffer: &mut [u8],
     target_image_layout: SampleLayout,
 ) where
-    C: num::Num + Copy + PartialOrd,
+    C: Copy + PartialOrd,
 {
     assert!(
         pixels.len() * target_image_layout.channel_stride * target_image_layout.channels as usize
@@ -86,23 +131,26 @@ pub fn sort_with_numeric_context_and_reapply_pixel_segment<C>(
     // Sort pixels.
     match sort_direction {
         PixelSegmentSortDirection::Ascending => {
-            pixels.sort_unstable_by(|first, second| {
-                first
-                    .context
-                    .partial_cmp(&second.context)
-                    .unwrap_or(Ordering::Equal)
+            sort_by_with_stability(&mut pixels, stable_sort, |first, second| {
+                compare_numeric_sorting_contexts(&first.context, &second.context)
             });
         }
         PixelSegmentSortDirection::Descending => {
-            pixels.sort_unstable_by(|first, second| {
-                second
-                    .context
-                    .partial_cmp(&first.context)
-                    .unwrap_or(Ordering::Equal)
+            sort_by_with_stability(&mut pixels, stable_sort, |first, second| {
+                compare_numeric_sorting_contexts(&second.context, &first.context)
             });
         }
+        PixelSegmentSortDirection::Reverse => {
+            pixels.reverse();
+        }
     }
 
+    let pixels = if sort_direction == PixelSegmentSortDirection::Reverse {
+        pixels
+    } else {
+        arrange_sorted_pixels(pixels, arrangement)
+    };
+
     // Reapply the sorted pixel segment back onto the image at the correct position.
     copy_pixel_segment_onto_image(
         pixels,
@@ -110,3 +158,64 @@ pub fn sort_with_numeric_context_and_reapply_pixel_segment<C>(
         target_image_layout,
     );
 }
+
+
+/// Lays out the already sorted `pixels` (ordered from first to last) according to `arrangement`.
+///
+/// For [`PixelSegmentArrangement::TowardCenter`], pixels are placed alternately at the start
+/// and at the end of the segment, moving inwards; [`PixelSegmentArrangement::AwayFromCenter`]
+/// does the same with the order of pixels reversed. For example, sorted values `1 2 3 4 5`
+/// are arranged into `1 3 5 4 2` and `5 3 1 2 4`, respectively.
+pub fn arrange_sorted_pixels<P>(pixels: Vec<P>, arrangement: PixelSegmentArrangement) -> Vec<P> {
+    let mut pixels = match arrangement {
+        PixelSegmentArrangement::Linear => return pixels,
+        PixelSegmentArrangement::TowardCenter | PixelSegmentArrangement::AwayFromCenter => pixels,
+    };
+
+    if arrangement == PixelSegmentArrangement::AwayFromCenter {
+        pixels.reverse();
+    }
+
+    let number_of_pixels = pixels.len();
+
+    let mut start_half = Vec::with_capacity(number_of_pixels.div_ceil(2));
+    let mut end_half = Vec::with_capacity(number_of_pixels / 2);
+
+    for (pixel_index, pixel) in pixels.into_iter().enumerate() {
+        if pixel_index.is_multiple_of(2) {
+            start_half.push(pixel);
+        } else {
+            end_half.push(pixel);
+        }
+    }
+
+    end_half.reverse();
+    start_half.extend(end_half);
+
+    start_half
+}
+
+
+/// Reverses the order of pixels in the given pixel segment, provided as a flat buffer
+/// (`segment_contiguous_flat_buffer`) of pixels that are each `pixel_stride` samples wide.
+///
+/// This is a cheaper alternative to [`sort_with_numeric_context_and_reapply_pixel_segment`]
+/// for [`PixelSegmentSortDirection::Reverse`], as no contextualized pixels need to be collected.
+pub fn reverse_pixel_segment_in_place(
+    segment_contiguous_flat_buffer: &mut [u8],
+    pixel_stride: usize,
+) {
+    assert!(
+        segment_contiguous_flat_buffer
+            .len()
+            .is_multiple_of(pixel_stride)
+    );
+
+    // Reversing all samples reverses the order of pixels, but also the order of channels
+    // within each pixel, so we restore the latter afterwards.
+    segment_contiguous_flat_buffer.reverse();
+
+    for pixel_samples in segment_contiguous_flat_buffer.chunks_exact_mut(pixel_stride) {
+        pixel_samples.reverse();
+    }
+}
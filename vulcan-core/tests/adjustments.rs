@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use image::{Rgba, RgbaImage};
+use vulcan_core::adjustments::{
+    AdjustmentError,
+    Axis,
+    adjust_brightness_contrast,
+    mirror_tile,
+    posterize,
+    swap_channels,
+};
+
+fn generate_gradient_image() -> RgbaImage {
+    let mut image = RgbaImage::new(256, 4);
+
+    for (column_index, _, pixel) in image.enumerate_pixels_mut() {
+        let value = column_index as u8;
+        *pixel = Rgba([value, u8::MAX - value, value / 2, u8::MAX]);
+    }
+
+    image
+}
+
+fn collect_distinct_channel_values(image: &RgbaImage) -> HashSet<u8> {
+    image
+        .pixels()
+        .flat_map(|pixel| [pixel.0[0], pixel.0[1], pixel.0[2]])
+        .collect()
+}
+
+#[test]
+fn posterize_with_single_level_collapses_channels_to_extremes() {
+    let mut image = generate_gradient_image();
+    posterize(&mut image, 1);
+
+    let distinct_values = collect_distinct_channel_values(&image);
+    assert_eq!(distinct_values, HashSet::from([0, u8::MAX]));
+
+    assert!(image.pixels().all(|pixel| pixel.0[3] == u8::MAX));
+}
+
+#[test]
+fn posterize_with_more_levels_preserves_more_values() {
+    let mut coarse_image = generate_gradient_image();
+    posterize(&mut coarse_image, 2);
+
+    let mut fine_image = generate_gradient_image();
+    posterize(&mut fine_image, 8);
+
+    let coarse_values = collect_distinct_channel_values(&coarse_image);
+    let fine_values = collect_distinct_channel_values(&fine_image);
+
+    assert_eq!(fine_values.len(), 8);
+    assert!(fine_values.len() > coarse_values.len());
+    assert!(fine_values.contains(&0) && fine_values.contains(&u8::MAX));
+}
+
+fn generate_coordinate_image(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([x as u8, y as u8, (x * 7 + y * 13) as u8, u8::MAX])
+    })
+}
+
+#[test]
+fn horizontal_mirror_tile_doubles_width_with_matching_seam() {
+    let image = generate_coordinate_image(5, 3);
+    let tiled_image = mirror_tile(&image, Axis::Horizontal);
+
+    assert_eq!(tiled_image.dimensions(), (10, 3));
+
+    for y in 0..3 {
+        assert_eq!(
+            tiled_image.get_pixel(4, y),
+            tiled_image.get_pixel(5, y)
+        );
+        assert_eq!(
+            tiled_image.get_pixel(0, y),
+            tiled_image.get_pixel(9, y)
+        );
+        assert_eq!(tiled_image.get_pixel(0, y), image.get_pixel(0, y));
+    }
+}
+
+#[test]
+fn vertical_mirror_tile_doubles_height_with_matching_seam() {
+    let image = generate_coordinate_image(3, 5);
+    let tiled_image = mirror_tile(&image, Axis::Vertical);
+
+    assert_eq!(tiled_image.dimensions(), (3, 10));
+
+    for x in 0..3 {
+        assert_eq!(
+            tiled_image.get_pixel(x, 4),
+            tiled_image.get_pixel(x, 5)
+        );
+        assert_eq!(
+            tiled_image.get_pixel(x, 0),
+            tiled_image.get_pixel(x, 9)
+        );
+        assert_eq!(tiled_image.get_pixel(x, 0), image.get_pixel(x, 0));
+    }
+}
+
+#[test]
+fn swap_channels_swaps_red_and_blue() {
+    let mut image = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 40]));
+
+    swap_channels(&mut image, [2, 1, 0, 3]).expect("mapping should be valid");
+
+    assert!(image.pixels().all(|pixel| *pixel == Rgba([30, 20, 10, 40])));
+}
+
+#[test]
+fn swap_channels_rejects_invalid_mapping() {
+    let mut image = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 40]));
+
+    let result = swap_channels(&mut image, [0, 1, 4, 3]);
+
+    assert!(matches!(
+        result,
+        Err(AdjustmentError::InvalidChannelMapping {
+            position: 2,
+            channel_index: 4
+        })
+    ));
+    assert!(image.pixels().all(|pixel| *pixel == Rgba([10, 20, 30, 40])));
+}
+
+#[test]
+fn positive_brightness_saturates_instead_of_wrapping() {
+    let mut image = RgbaImage::from_pixel(2, 2, Rgba([250, 250, 250, 200]));
+
+    adjust_brightness_contrast(&mut image, 10.0, 1.0);
+
+    assert!(
+        image
+            .pixels()
+            .all(|pixel| *pixel == Rgba([u8::MAX, u8::MAX, u8::MAX, 200]))
+    );
+}
+
+#[test]
+fn negative_brightness_saturates_instead_of_wrapping() {
+    let mut image = RgbaImage::from_pixel(2, 2, Rgba([5, 5, 5, 200]));
+
+    adjust_brightness_contrast(&mut image, -10.0, 1.0);
+
+    assert!(image.pixels().all(|pixel| *pixel == Rgba([0, 0, 0, 200])));
+}
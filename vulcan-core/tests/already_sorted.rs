@@ -0,0 +1,72 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+const IMAGE_WIDTH: u32 = 32;
+
+/// Generates an image with a horizontal grayscale gradient (dark on the left, bright on the right).
+fn generate_gradient_image() -> RgbaImage {
+    RgbaImage::from_fn(IMAGE_WIDTH, 4, |x, _| {
+        let value = (x * 8) as u8;
+        Rgba([value, value, value, u8::MAX])
+    })
+}
+
+fn sort_by_luminance(image: RgbaImage, direction: PixelSegmentSortDirection) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(direction),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+#[test]
+fn already_sorted_gradient_is_left_unchanged() {
+    let gradient_image = generate_gradient_image();
+
+    let sorted_image = sort_by_luminance(
+        gradient_image.clone(),
+        PixelSegmentSortDirection::Ascending,
+    );
+
+    assert_eq!(sorted_image, gradient_image);
+}
+
+#[test]
+fn gradient_sorted_in_opposite_direction_is_mirrored() {
+    let gradient_image = generate_gradient_image();
+
+    let sorted_image = sort_by_luminance(
+        gradient_image.clone(),
+        PixelSegmentSortDirection::Descending,
+    );
+
+    for (x, y, pixel) in sorted_image.enumerate_pixels() {
+        assert_eq!(
+            pixel,
+            gradient_image.get_pixel(IMAGE_WIDTH - 1 - x, y)
+        );
+    }
+
+    // Sorting the result back must restore the original gradient.
+    let resorted_image = sort_by_luminance(sorted_image, PixelSegmentSortDirection::Ascending);
+    assert_eq!(resorted_image, gradient_image);
+}
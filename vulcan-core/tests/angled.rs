@@ -0,0 +1,110 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    immediate::{ImmediateSegmentSelectionMode, PixelSortOptions, perform_pixel_sort},
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+/// Generates a non-square image with pseudo-random, but deterministic pixel colors.
+fn generate_noise_image() -> RgbaImage {
+    RgbaImage::from_fn(23, 17, |x, y| {
+        let value = (x * 7919 + y * 104_729).wrapping_mul(2_654_435_761);
+
+        Rgba([
+            (value >> 8) as u8,
+            (value >> 16) as u8,
+            (value >> 24) as u8,
+            u8::MAX,
+        ])
+    })
+}
+
+fn prepared_sort(direction: ImageSortingDirection) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        generate_noise_image(),
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.2,
+            high: 0.8,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        direction,
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+fn immediate_sort(direction: ImageSortingDirection) -> RgbaImage {
+    perform_pixel_sort(
+        generate_noise_image(),
+        ImmediateSegmentSelectionMode::LuminanceRange {
+            low: 0.2,
+            high: 0.8,
+        },
+        PixelSortOptions {
+            direction,
+            stable_sort: false,
+        },
+    )
+}
+
+fn angled(degrees: f32) -> ImageSortingDirection {
+    ImageSortingDirection::Angled {
+        degrees,
+        direction: PixelSegmentSortDirection::Ascending,
+    }
+}
+
+#[test]
+fn angled_sort_at_zero_degrees_matches_horizontal_sort() {
+    let horizontal = ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending);
+
+    assert_eq!(
+        prepared_sort(angled(0.0)),
+        prepared_sort(horizontal)
+    );
+    assert_eq!(
+        immediate_sort(angled(0.0)),
+        immediate_sort(horizontal)
+    );
+}
+
+#[test]
+fn angled_sort_at_ninety_degrees_matches_vertical_sort() {
+    let vertical = ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending);
+
+    assert_eq!(
+        prepared_sort(angled(90.0)),
+        prepared_sort(vertical)
+    );
+    assert_eq!(
+        immediate_sort(angled(90.0)),
+        immediate_sort(vertical)
+    );
+}
+
+#[test]
+fn angled_sort_at_arbitrary_angle_keeps_every_pixel() {
+    let mut original_pixels: Vec<[u8; 4]> = generate_noise_image().pixels().map(|p| p.0).collect();
+    original_pixels.sort_unstable();
+
+    for degrees in [30.0, 137.5, -60.0] {
+        let sorted_image = prepared_sort(angled(degrees));
+        assert_eq!(sorted_image.dimensions(), (23, 17));
+
+        let mut sorted_pixels: Vec<[u8; 4]> = sorted_image.pixels().map(|p| p.0).collect();
+        sorted_pixels.sort_unstable();
+
+        assert_eq!(sorted_pixels, original_pixels);
+        assert_ne!(sorted_image, generate_noise_image());
+    }
+}
@@ -0,0 +1,112 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentArrangement,
+    PixelSegmentSortDirection,
+    prepared::{
+        LARGEST_CANNY_EDGE_THRESHOLD,
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        SMALLEST_CANNY_EDGE_THRESHOLD,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+fn generate_checkerboard_image() -> RgbaImage {
+    RgbaImage::from_fn(32, 32, |x, y| {
+        if (x / 4 + y / 4) % 2 == 0 {
+            Rgba([0, 0, 0, u8::MAX])
+        } else {
+            Rgba([u8::MAX, u8::MAX, u8::MAX, u8::MAX])
+        }
+    })
+}
+
+fn sort_using_canny_edges(low: f32, high: f32) -> RgbaImage {
+    let image = generate_checkerboard_image();
+
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::CannyEdges {
+            low,
+            high,
+            segment_starts_on_image_edge: false,
+            segment_arrangement: PixelSegmentArrangement::Linear,
+            invert_edges: false,
+            blur_sigma: None,
+            dilation: 0,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+#[test]
+fn canny_edges_with_extreme_thresholds_do_not_panic() {
+    let extreme_threshold_pairs = [
+        (-1000.0, 1e9),
+        (f32::MIN, f32::MAX),
+        (1e9, -1e9),
+        (f32::NEG_INFINITY, f32::INFINITY),
+        (f32::NAN, f32::NAN),
+        (0.0, 0.0),
+        (5000.0, 5000.0),
+    ];
+
+    for (low, high) in extreme_threshold_pairs {
+        let sorted_image = sort_using_canny_edges(low, high);
+        assert_eq!(sorted_image.dimensions(), (32, 32));
+    }
+}
+
+#[test]
+fn canny_edges_with_out_of_range_thresholds_match_clamped_thresholds() {
+    assert_eq!(
+        sort_using_canny_edges(-50.0, 5000.0),
+        sort_using_canny_edges(
+            SMALLEST_CANNY_EDGE_THRESHOLD,
+            LARGEST_CANNY_EDGE_THRESHOLD
+        )
+    );
+}
+
+#[test]
+fn vertical_canny_edges_sort_keeps_original_orientation() {
+    const PIXEL_GRAY: Rgba<u8> = Rgba([128, 128, 128, u8::MAX]);
+    const PIXEL_RED: Rgba<u8> = Rgba([u8::MAX, 0, 0, u8::MAX]);
+
+    // A non-square image with a single marker pixel in the top-left corner. Canny never
+    // marks the outermost pixels as edges, so the marker itself is never sorted.
+    let mut image = RgbaImage::from_pixel(16, 8, PIXEL_GRAY);
+    image.put_pixel(0, 0, PIXEL_RED);
+
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::CannyEdges {
+            low: SMALLEST_CANNY_EDGE_THRESHOLD,
+            high: SMALLEST_CANNY_EDGE_THRESHOLD,
+            segment_starts_on_image_edge: false,
+            segment_arrangement: PixelSegmentArrangement::Linear,
+            invert_edges: false,
+            blur_sigma: None,
+            dilation: 0,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    let sorted_image = execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false);
+
+    assert_eq!(sorted_image.dimensions(), (16, 8));
+    assert_eq!(*sorted_image.get_pixel(0, 0), PIXEL_RED);
+    assert_eq!(*sorted_image.get_pixel(15, 7), PIXEL_GRAY);
+}
@@ -0,0 +1,44 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::channels::{merge_rgba, split_rgba};
+
+fn generate_coordinate_image(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([
+            (x * 10) as u8,
+            (y * 10) as u8,
+            (x + y) as u8,
+            (255 - x) as u8,
+        ])
+    })
+}
+
+#[test]
+fn split_channels_contain_individual_channel_values() {
+    let image = generate_coordinate_image(7, 5);
+    let channels = split_rgba(&image);
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        for (channel_index, channel) in channels.iter().enumerate() {
+            assert_eq!(
+                channel.get_pixel(x, y).0[0],
+                pixel.0[channel_index]
+            );
+        }
+    }
+}
+
+#[test]
+fn split_then_merge_round_trips() {
+    let image = generate_coordinate_image(7, 5);
+
+    assert_eq!(merge_rgba(split_rgba(&image)), image);
+}
+
+#[test]
+#[should_panic]
+fn merging_channels_of_different_sizes_panics() {
+    let [red, green, blue, _] = split_rgba(&generate_coordinate_image(7, 5));
+    let [_, _, _, alpha] = split_rgba(&generate_coordinate_image(5, 7));
+
+    merge_rgba([red, green, blue, alpha]);
+}
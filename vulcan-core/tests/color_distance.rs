@@ -0,0 +1,55 @@
+use image::Rgba;
+use vulcan_core::pixel_sorting::{
+    prepared::PreparedSegmentSortingMode,
+    properties::{DistanceMetric, compute_rgba_color_distance},
+};
+
+const TOLERANCE: f32 = 1e-3;
+
+const PASTEL_LEMON: Rgba<u8> = Rgba([255, 250, 205, u8::MAX]);
+const PASTEL_PEACH: Rgba<u8> = Rgba([255, 218, 185, u8::MAX]);
+const PASTEL_PINK: Rgba<u8> = Rgba([255, 209, 220, u8::MAX]);
+const PASTEL_MINT: Rgba<u8> = Rgba([189, 252, 201, u8::MAX]);
+
+/// Sorts the given pastel colors by their distance to [`PASTEL_LEMON`], as measured by `metric`.
+fn sort_pastels_by_distance_to_lemon(metric: DistanceMetric) -> Vec<Rgba<u8>> {
+    let sorting_mode = PreparedSegmentSortingMode::ColorDistance {
+        target: PASTEL_LEMON,
+        distance_metric: metric,
+    };
+
+    let mut pastels = vec![PASTEL_MINT, PASTEL_PINK, PASTEL_PEACH];
+    pastels.sort_by(|first, second| {
+        sorting_mode
+            .compute_sorting_value(first)
+            .total_cmp(&sorting_mode.compute_sorting_value(second))
+    });
+
+    pastels
+}
+
+#[test]
+fn distance_metrics_order_pastels_differently() {
+    // In linear RGB, the pink is closer to the lemon than the mint, as its red channel matches.
+    assert_eq!(
+        sort_pastels_by_distance_to_lemon(DistanceMetric::Rgb),
+        vec![PASTEL_PEACH, PASTEL_PINK, PASTEL_MINT]
+    );
+
+    // Perceptually, the (similarly light and yellowish) mint is closer than the pink.
+    assert_eq!(
+        sort_pastels_by_distance_to_lemon(DistanceMetric::OkLab),
+        vec![PASTEL_PEACH, PASTEL_MINT, PASTEL_PINK]
+    );
+}
+
+#[test]
+fn distance_metrics_share_their_range() {
+    let black = Rgba([0, 0, 0, u8::MAX]);
+    let white = Rgba([u8::MAX, u8::MAX, u8::MAX, u8::MAX]);
+
+    for metric in [DistanceMetric::Rgb, DistanceMetric::OkLab] {
+        assert!((compute_rgba_color_distance(&black, &white, metric) - 1.0).abs() < TOLERANCE);
+        assert!(compute_rgba_color_distance(&PASTEL_MINT, &PASTEL_MINT, metric).abs() < TOLERANCE);
+    }
+}
@@ -0,0 +1,120 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    immediate::{ImmediateSegmentSelectionMode, PixelSortOptions, perform_pixel_sort},
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+/// Generates an image of the given dimensions with a repeating grayscale pattern,
+/// so that no horizontal or vertical line of (more than two) pixels is already sorted.
+fn generate_unsorted_image(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        let value = ((x + y) * 37 % 256) as u8;
+
+        Rgba([value, value, value, u8::MAX])
+    })
+}
+
+fn directions() -> [ImageSortingDirection; 2] {
+    [
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending),
+    ]
+}
+
+fn prepared_sort(image: RgbaImage, direction: ImageSortingDirection) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        direction,
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+fn immediate_sort(image: RgbaImage, direction: ImageSortingDirection) -> RgbaImage {
+    perform_pixel_sort(
+        image,
+        ImmediateSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+        },
+        PixelSortOptions {
+            direction,
+            stable_sort: false,
+        },
+    )
+}
+
+/// Returns `true` if the luminance of the pixels is monotonic in row-major order,
+/// which for single-row or single-column images means the line was sorted.
+fn is_sorted(image: &RgbaImage) -> bool {
+    let values = image.pixels().map(|pixel| pixel.0[0]).collect::<Vec<_>>();
+
+    values.is_sorted() || values.iter().rev().is_sorted()
+}
+
+#[test]
+fn single_pixel_image_is_returned_unchanged() {
+    for direction in directions() {
+        let image = generate_unsorted_image(1, 1);
+
+        assert_eq!(prepared_sort(image.clone(), direction), image);
+        assert_eq!(immediate_sort(image.clone(), direction), image);
+    }
+}
+
+#[test]
+fn single_column_image_is_sorted_only_vertically() {
+    let image = generate_unsorted_image(1, 100);
+
+    let [horizontal, vertical] = directions();
+
+    assert_eq!(prepared_sort(image.clone(), horizontal), image);
+    assert_eq!(immediate_sort(image.clone(), horizontal), image);
+
+    assert!(is_sorted(&prepared_sort(image.clone(), vertical)));
+    assert!(is_sorted(&immediate_sort(image, vertical)));
+}
+
+#[test]
+fn single_row_image_is_sorted_only_horizontally() {
+    let image = generate_unsorted_image(100, 1);
+
+    let [horizontal, vertical] = directions();
+
+    let prepared_sorted_image = prepared_sort(image.clone(), horizontal);
+    assert!(is_sorted(&prepared_sorted_image));
+
+    let immediate_sorted_image = immediate_sort(image.clone(), horizontal);
+    assert!(is_sorted(&immediate_sorted_image));
+
+    assert_eq!(prepared_sort(image.clone(), vertical), image);
+    assert_eq!(immediate_sort(image.clone(), vertical), image);
+}
+
+#[test]
+fn empty_images_are_returned_unchanged() {
+    for (width, height) in [(0, 0), (0, 100), (100, 0)] {
+        for direction in directions() {
+            let image = RgbaImage::new(width, height);
+
+            assert_eq!(prepared_sort(image.clone(), direction), image);
+            assert_eq!(immediate_sort(image.clone(), direction), image);
+        }
+    }
+}
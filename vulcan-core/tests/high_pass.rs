@@ -0,0 +1,111 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        SelectionModeError,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+const IMAGE_WIDTH: u32 = 64;
+const IMAGE_HEIGHT: u32 = 4;
+const EDGE_COLUMN: u32 = 48;
+
+const PIXEL_BLACK: Rgba<u8> = Rgba([0, 0, 0, u8::MAX]);
+const PIXEL_WHITE: Rgba<u8> = Rgba([u8::MAX, u8::MAX, u8::MAX, u8::MAX]);
+
+/// Generates an image with a flat black region on the left
+/// and a sharp vertical black-to-white edge at [`EDGE_COLUMN`].
+fn generate_sharp_edge_image() -> RgbaImage {
+    RgbaImage::from_fn(IMAGE_WIDTH, IMAGE_HEIGHT, |x, _| {
+        if x < EDGE_COLUMN {
+            PIXEL_BLACK
+        } else {
+            PIXEL_WHITE
+        }
+    })
+}
+
+fn sort_using_high_pass_range(image: RgbaImage, direction: ImageSortingDirection) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::HighPassRange {
+            blur_sigma: 1.0,
+            low: 0.1,
+            high: 1.0,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        direction,
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+#[test]
+fn high_pass_range_sorts_only_around_sharp_edge() {
+    let original_image = generate_sharp_edge_image();
+
+    // The pixels around the edge go from black to white, so a descending sort must flip them.
+    let sorted_image = sort_using_high_pass_range(
+        original_image.clone(),
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Descending),
+    );
+
+    for y in 0..IMAGE_HEIGHT {
+        // The flat region must be left untouched.
+        for x in 0..(EDGE_COLUMN - 8) {
+            assert_eq!(
+                sorted_image.get_pixel(x, y),
+                original_image.get_pixel(x, y)
+            );
+        }
+
+        assert_eq!(
+            *sorted_image.get_pixel(EDGE_COLUMN - 1, y),
+            PIXEL_WHITE
+        );
+        assert_eq!(
+            *sorted_image.get_pixel(EDGE_COLUMN, y),
+            PIXEL_BLACK
+        );
+    }
+}
+
+#[test]
+fn high_pass_range_leaves_flat_image_untouched() {
+    let flat_image = RgbaImage::from_pixel(
+        IMAGE_WIDTH,
+        IMAGE_HEIGHT,
+        Rgba([40, 90, 200, 255]),
+    );
+
+    let sorted_image = sort_using_high_pass_range(
+        flat_image.clone(),
+        ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending),
+    );
+
+    assert_eq!(sorted_image, flat_image);
+}
+
+#[test]
+fn high_pass_range_rejects_non_positive_blur_sigma() {
+    for blur_sigma in [0.0, -1.0, f32::NAN] {
+        let selection_mode = PreparedSegmentSelectionMode::HighPassRange {
+            blur_sigma,
+            low: 0.1,
+            high: 1.0,
+        };
+
+        assert!(matches!(
+            selection_mode.validate_range(),
+            Err(SelectionModeError::NonPositiveBlurSigma { .. })
+        ));
+    }
+}
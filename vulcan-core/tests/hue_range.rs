@@ -0,0 +1,94 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::{
+    feedback::{
+        FeedbackSegmentSelectionMode,
+        PixelPropertyMaps,
+        ThresholdPreviewStyle,
+        render_threshold_preview,
+        render_threshold_preview_with_property_maps,
+    },
+    pixel_sorting::{
+        ImageSortingDirection,
+        PixelSegmentSortDirection,
+        prepared::{
+            PreparedSegmentSelectionMode,
+            PreparedSegmentSortingMode,
+            execute_axis_aligned_prepared_pixel_sort,
+            prepare_pixel_sort,
+        },
+        properties::{compute_rgba_hsl_hue_gamma, is_hue_in_range},
+    },
+};
+
+/// A bright red-orange pixel with a hue of roughly 5 degrees.
+const PIXEL_BRIGHT_RED: Rgba<u8> = Rgba([255, 21, 0, u8::MAX]);
+
+/// A darker version of [`PIXEL_BRIGHT_RED`] with (approximately) the same hue.
+const PIXEL_DARK_RED: Rgba<u8> = Rgba([128, 11, 0, u8::MAX]);
+
+#[test]
+fn wrapping_hue_range_contains_hue_past_zero() {
+    let hue = compute_rgba_hsl_hue_gamma(&PIXEL_BRIGHT_RED);
+    assert!((0.0..10.0).contains(&hue), "unexpected hue {hue}");
+
+    assert!(is_hue_in_range(hue, 350.0, 20.0));
+    assert!(is_hue_in_range(355.0, 350.0, 20.0));
+    assert!(!is_hue_in_range(120.0, 350.0, 20.0));
+    assert!(!is_hue_in_range(hue, 20.0, 350.0));
+}
+
+#[test]
+fn prepared_sort_selects_pixels_in_wrapping_hue_range() {
+    let image = RgbaImage::from_fn(2, 1, |x, _| {
+        if x == 0 {
+            PIXEL_BRIGHT_RED
+        } else {
+            PIXEL_DARK_RED
+        }
+    });
+
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::HueRange {
+            low: 350.0,
+            high: 20.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    let sorted_image = execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false);
+
+    assert_eq!(*sorted_image.get_pixel(0, 0), PIXEL_DARK_RED);
+    assert_eq!(*sorted_image.get_pixel(1, 0), PIXEL_BRIGHT_RED);
+}
+
+#[test]
+fn threshold_preview_keeps_pixels_in_wrapping_hue_range() {
+    let wrapping_hue_range = || FeedbackSegmentSelectionMode::HueRange {
+        low: 350.0,
+        high: 20.0,
+        invert: false,
+    };
+
+    let mut preview = RgbaImage::from_pixel(1, 1, PIXEL_BRIGHT_RED);
+    render_threshold_preview(
+        &mut preview,
+        wrapping_hue_range(),
+        ThresholdPreviewStyle::MaskBlack,
+    );
+    assert_eq!(*preview.get_pixel(0, 0), PIXEL_BRIGHT_RED);
+
+    let mut cached_preview = RgbaImage::from_pixel(1, 1, PIXEL_BRIGHT_RED);
+    render_threshold_preview_with_property_maps(
+        &mut cached_preview,
+        wrapping_hue_range(),
+        ThresholdPreviewStyle::MaskBlack,
+        &mut PixelPropertyMaps::new(),
+    );
+    assert_eq!(*cached_preview.get_pixel(0, 0), PIXEL_BRIGHT_RED);
+}
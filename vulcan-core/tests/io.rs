@@ -0,0 +1,185 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{DynamicImage, ImageEncoder, Rgb, RgbImage, RgbaImage, codecs::jpeg::JpegEncoder};
+use vulcan_core::io::{
+    ImageSaveError,
+    decode_image_from_memory,
+    normalize_output_file_path,
+    save_image_as_png,
+};
+
+/// The EXIF orientation value for "rotate 90 degrees clockwise to display".
+const EXIF_ORIENTATION_ROTATE_90: u16 = 6;
+
+/// Encodes a 16x8 JPEG whose left half is black and right half is white,
+/// tagged with the given EXIF `orientation`.
+fn encode_jpeg_with_exif_orientation(orientation: u16) -> Vec<u8> {
+    let image = RgbImage::from_fn(16, 8, |x, _| {
+        if x < 8 {
+            Rgb([0, 0, 0])
+        } else {
+            Rgb([u8::MAX, u8::MAX, u8::MAX])
+        }
+    });
+
+    let mut encoded_jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut encoded_jpeg, 100)
+        .write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            image::ExtendedColorType::Rgb8,
+        )
+        .expect("failed to encode JPEG");
+
+    // A minimal big-endian TIFF structure with a single IFD entry: the orientation tag.
+    let mut exif_payload = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+    exif_payload.extend_from_slice(&1u16.to_be_bytes());
+    exif_payload.extend_from_slice(&0x0112u16.to_be_bytes());
+    exif_payload.extend_from_slice(&3u16.to_be_bytes());
+    exif_payload.extend_from_slice(&1u32.to_be_bytes());
+    exif_payload.extend_from_slice(&orientation.to_be_bytes());
+    exif_payload.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+    // Insert an APP1 segment containing the EXIF payload right after the SOI marker.
+    let mut app1_segment = vec![0xFF, 0xE1];
+    app1_segment.extend_from_slice(&(exif_payload.len() as u16 + 2).to_be_bytes());
+    app1_segment.extend_from_slice(&exif_payload);
+
+    encoded_jpeg.splice(2..2, app1_segment);
+    encoded_jpeg
+}
+
+#[test]
+fn unknown_extension_is_coerced_to_png() {
+    assert_eq!(
+        normalize_output_file_path(Path::new("output/image.foo")),
+        PathBuf::from("output/image.png")
+    );
+}
+
+#[test]
+fn uppercase_non_png_extension_is_coerced_to_png() {
+    assert_eq!(
+        normalize_output_file_path(Path::new("output/image.JPG")),
+        PathBuf::from("output/image.png")
+    );
+}
+
+#[test]
+fn uppercase_png_extension_is_kept() {
+    assert_eq!(
+        normalize_output_file_path(Path::new("output/image.PNG")),
+        PathBuf::from("output/image.PNG")
+    );
+}
+
+#[test]
+fn missing_extension_is_set_to_png() {
+    assert_eq!(
+        normalize_output_file_path(Path::new("output/image")),
+        PathBuf::from("output/image.png")
+    );
+}
+
+#[test]
+fn exif_orientation_is_applied_when_respected() {
+    let encoded_jpeg = encode_jpeg_with_exif_orientation(EXIF_ORIENTATION_ROTATE_90);
+
+    let decoded_image =
+        decode_image_from_memory(&encoded_jpeg, true).expect("failed to decode JPEG");
+
+    // Rotating clockwise moves the black left half of the stored image to the top.
+    assert_eq!(decoded_image.image.dimensions(), (8, 16));
+    assert!(decoded_image.image.get_pixel(4, 2).0[0] < 64);
+    assert!(decoded_image.image.get_pixel(4, 13).0[0] > 192);
+}
+
+#[test]
+fn exif_orientation_is_ignored_when_not_respected() {
+    let encoded_jpeg = encode_jpeg_with_exif_orientation(EXIF_ORIENTATION_ROTATE_90);
+
+    let decoded_image =
+        decode_image_from_memory(&encoded_jpeg, false).expect("failed to decode JPEG");
+
+    assert_eq!(decoded_image.image.dimensions(), (16, 8));
+    assert!(decoded_image.image.get_pixel(2, 4).0[0] < 64);
+    assert!(decoded_image.image.get_pixel(13, 4).0[0] > 192);
+}
+
+fn generate_small_image() -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::new(4, 4))
+}
+
+#[test]
+fn saving_into_missing_directory_reports_missing_parent() {
+    let temporary_directory = tempfile::tempdir().expect("failed to create temporary directory");
+    let output_file_path = temporary_directory.path().join("missing").join("image.png");
+
+    let result = save_image_as_png(
+        &generate_small_image(),
+        &output_file_path,
+        false,
+        None,
+    );
+
+    assert!(matches!(
+        result,
+        Err(ImageSaveError::ParentDirectoryMissing { .. })
+    ));
+}
+
+#[test]
+fn saving_over_existing_file_without_overwriting_reports_conflict() {
+    let temporary_directory = tempfile::tempdir().expect("failed to create temporary directory");
+    let output_file_path = temporary_directory.path().join("image.png");
+    fs::write(&output_file_path, b"existing").expect("failed to create existing file");
+
+    let result = save_image_as_png(
+        &generate_small_image(),
+        &output_file_path,
+        false,
+        None,
+    );
+
+    assert!(matches!(
+        result,
+        Err(ImageSaveError::AlreadyExists { .. })
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn saving_into_read_only_directory_reports_permission_denied() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temporary_directory = tempfile::tempdir().expect("failed to create temporary directory");
+    let read_only_directory = temporary_directory.path().join("read-only");
+    fs::create_dir(&read_only_directory).expect("failed to create directory");
+    fs::set_permissions(
+        &read_only_directory,
+        fs::Permissions::from_mode(0o555),
+    )
+    .expect("failed to make directory read-only");
+
+    // Privileged users (e.g. root in a container) bypass permission checks,
+    // in which case there is nothing to test.
+    if fs::write(read_only_directory.join("probe"), b"probe").is_ok() {
+        return;
+    }
+
+    let result = save_image_as_png(
+        &generate_small_image(),
+        read_only_directory.join("image.png"),
+        false,
+        None,
+    );
+
+    assert!(matches!(
+        result,
+        Err(ImageSaveError::PermissionDenied { .. })
+    ));
+}
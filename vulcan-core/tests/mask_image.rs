@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use image::{GrayImage, Luma, Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+const IMAGE_SIZE: u32 = 8;
+
+/// The first column (or row) of the masked region, which extends to the edge of the image.
+const MASK_START: u32 = 5;
+
+fn gray_pixel(value: u32) -> Rgba<u8> {
+    let value = (value * 30) as u8;
+    Rgba([value, value, value, u8::MAX])
+}
+
+fn sort_using_mask(
+    image: RgbaImage,
+    mask: GrayImage,
+    direction: ImageSortingDirection,
+) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::MaskImage {
+            mask: Arc::new(mask),
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        direction,
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+#[test]
+fn mask_touching_right_edge_sorts_last_columns() {
+    // Each row gets darker to the right, so an ascending sort must flip the masked part.
+    let original_image = RgbaImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |x, _| {
+        gray_pixel(IMAGE_SIZE - x)
+    });
+    let mask = GrayImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |x, _| {
+        Luma([if x >= MASK_START { u8::MAX } else { 0 }])
+    });
+
+    let sorted_image = sort_using_mask(
+        original_image.clone(),
+        mask,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+    );
+
+    for y in 0..IMAGE_SIZE {
+        for x in 0..MASK_START {
+            assert_eq!(
+                sorted_image.get_pixel(x, y),
+                original_image.get_pixel(x, y)
+            );
+        }
+
+        for x in MASK_START..IMAGE_SIZE {
+            assert_eq!(
+                *sorted_image.get_pixel(x, y),
+                gray_pixel(x - MASK_START + 1)
+            );
+        }
+    }
+}
+
+#[test]
+fn mask_touching_bottom_edge_sorts_last_rows() {
+    // Vertical sorts run from the bottom of the image to its top, and each column gets brighter
+    // to the bottom, so an ascending sort must flip the masked part.
+    let original_image = RgbaImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |_, y| gray_pixel(y));
+    let mask = GrayImage::from_fn(IMAGE_SIZE, IMAGE_SIZE, |_, y| {
+        Luma([if y >= MASK_START { u8::MAX } else { 0 }])
+    });
+
+    let sorted_image = sort_using_mask(
+        original_image.clone(),
+        mask,
+        ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending),
+    );
+
+    for x in 0..IMAGE_SIZE {
+        for y in 0..MASK_START {
+            assert_eq!(
+                sorted_image.get_pixel(x, y),
+                original_image.get_pixel(x, y)
+            );
+        }
+
+        for y in MASK_START..IMAGE_SIZE {
+            assert_eq!(
+                *sorted_image.get_pixel(x, y),
+                gray_pixel(MASK_START + IMAGE_SIZE - 1 - y)
+            );
+        }
+    }
+}
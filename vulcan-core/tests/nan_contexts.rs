@@ -0,0 +1,122 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    PixelWithContext,
+    immediate::{perform_custom_pixel_sort, sort_array_of_numeric_contextual_pixels_by_direction},
+};
+
+/// The sorting contexts of a segment, where every third context is NaN.
+fn contexts_with_nans() -> Vec<f32> {
+    (0..30u8)
+        .map(|index| {
+            if index % 3 == 0 {
+                f32::NAN
+            } else {
+                (index * 7 % 11) as f32
+            }
+        })
+        .collect()
+}
+
+fn sort_contexts(contexts: &[f32], direction: PixelSegmentSortDirection) -> Vec<f32> {
+    let mut pixels = contexts
+        .iter()
+        .map(|&context| PixelWithContext::new(Rgba([0, 0, 0, u8::MAX]), context))
+        .collect::<Vec<_>>();
+
+    sort_array_of_numeric_contextual_pixels_by_direction(&mut pixels, direction, false);
+
+    pixels.into_iter().map(|pixel| pixel.context).collect()
+}
+
+/// Asserts that the given sorted contexts start with all NaNs, followed by
+/// the remaining contexts in ascending order.
+fn assert_nans_first_then_ascending(sorted_contexts: &[f32], number_of_nans: usize) {
+    let (nans, numbers) = sorted_contexts.split_at(number_of_nans);
+
+    assert!(nans.iter().all(|context| context.is_nan()));
+    assert!(numbers.iter().all(|context| !context.is_nan()));
+    assert!(numbers.is_sorted());
+}
+
+#[test]
+fn nan_contexts_sort_as_the_minimum() {
+    let contexts = contexts_with_nans();
+    let number_of_nans = contexts.iter().filter(|context| context.is_nan()).count();
+
+    let ascending = sort_contexts(&contexts, PixelSegmentSortDirection::Ascending);
+    assert_nans_first_then_ascending(&ascending, number_of_nans);
+
+    let mut descending = sort_contexts(&contexts, PixelSegmentSortDirection::Descending);
+    descending.reverse();
+    assert_nans_first_then_ascending(&descending, number_of_nans);
+}
+
+#[test]
+fn sorting_nan_contexts_does_not_depend_on_their_initial_order() {
+    let contexts = contexts_with_nans();
+    let expected = sort_contexts(&contexts, PixelSegmentSortDirection::Ascending);
+
+    for rotation in 1..contexts.len() {
+        let mut rotated_contexts = contexts.clone();
+        rotated_contexts.rotate_left(rotation);
+
+        let sorted = sort_contexts(
+            &rotated_contexts,
+            PixelSegmentSortDirection::Ascending,
+        );
+
+        // NaN never equals itself, so the contexts are compared by their bits.
+        assert_eq!(
+            sorted
+                .iter()
+                .map(|context| context.to_bits())
+                .collect::<Vec<_>>(),
+            expected
+                .iter()
+                .map(|context| context.to_bits())
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn custom_sort_with_nan_contexts_moves_nan_pixels_to_the_start_of_segments() {
+    // Pixels with a red value of zero get a NaN context.
+    let image = RgbaImage::from_fn(40, 3, |x, y| {
+        let value = if (x + y) % 4 == 0 {
+            0
+        } else {
+            ((x * 37 + y) % 200 + 50) as u8
+        };
+
+        Rgba([value, value, value, u8::MAX])
+    });
+
+    let sorted_image = perform_custom_pixel_sort(
+        image,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        |pixel: &Rgba<u8>| -> f32 {
+            if pixel.0[0] == 0 {
+                f32::NAN
+            } else {
+                pixel.0[0] as f32
+            }
+        },
+        |_: &PixelWithContext<f32>| true,
+        |pixels, direction| {
+            sort_array_of_numeric_contextual_pixels_by_direction(pixels, direction, false)
+        },
+    );
+
+    for row in sorted_image.rows() {
+        let values = row.map(|pixel| pixel.0[0]).collect::<Vec<_>>();
+        let number_of_nans = values.iter().filter(|&&value| value == 0).count();
+
+        let (nans, numbers) = values.split_at(number_of_nans);
+
+        assert!(nans.iter().all(|&value| value == 0));
+        assert!(numbers.is_sorted());
+    }
+}
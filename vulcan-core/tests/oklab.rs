@@ -0,0 +1,63 @@
+use image::Rgba;
+use vulcan_core::pixel_sorting::{
+    prepared::PreparedSegmentSortingMode,
+    properties::{compute_rgba_oklab_chroma, compute_rgba_oklab_hue, compute_rgba_oklab_lightness},
+};
+
+/// Reference values are taken from the OKLab reference implementation
+/// (see <https://bottosson.github.io/posts/oklab/>).
+const TOLERANCE: f32 = 1e-3;
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < TOLERANCE,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn oklab_of_white_and_black() {
+    let white = Rgba([255, 255, 255, u8::MAX]);
+    assert_close(compute_rgba_oklab_lightness(&white), 1.0);
+    assert_close(compute_rgba_oklab_chroma(&white), 0.0);
+
+    let black = Rgba([0, 0, 0, u8::MAX]);
+    assert_close(compute_rgba_oklab_lightness(&black), 0.0);
+    assert_close(compute_rgba_oklab_chroma(&black), 0.0);
+}
+
+#[test]
+fn oklab_of_primary_colors() {
+    let red = Rgba([255, 0, 0, u8::MAX]);
+    assert_close(compute_rgba_oklab_lightness(&red), 0.627_955);
+    assert_close(compute_rgba_oklab_chroma(&red), 0.257_683);
+    assert!((compute_rgba_oklab_hue(&red) - 29.234).abs() < 0.1);
+
+    let green = Rgba([0, 255, 0, u8::MAX]);
+    assert_close(compute_rgba_oklab_lightness(&green), 0.866_440);
+    assert_close(compute_rgba_oklab_chroma(&green), 0.294_827);
+    assert!((compute_rgba_oklab_hue(&green) - 142.495).abs() < 0.1);
+
+    let blue = Rgba([0, 0, 255, u8::MAX]);
+    assert_close(compute_rgba_oklab_lightness(&blue), 0.452_014);
+    assert_close(compute_rgba_oklab_chroma(&blue), 0.313_214);
+    assert!((compute_rgba_oklab_hue(&blue) - 264.052).abs() < 0.1);
+}
+
+#[test]
+fn oklab_sorting_modes_use_oklab_properties() {
+    let orange = Rgba([255, 128, 0, u8::MAX]);
+
+    assert_eq!(
+        PreparedSegmentSortingMode::OklabLightness.compute_sorting_value(&orange),
+        compute_rgba_oklab_lightness(&orange)
+    );
+    assert_eq!(
+        PreparedSegmentSortingMode::OklabChroma.compute_sorting_value(&orange),
+        compute_rgba_oklab_chroma(&orange)
+    );
+    assert_eq!(
+        PreparedSegmentSortingMode::OklabHue.compute_sorting_value(&orange),
+        compute_rgba_oklab_hue(&orange)
+    );
+}
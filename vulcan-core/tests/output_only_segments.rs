@@ -0,0 +1,66 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+const PIXEL_DARK: Rgba<u8> = Rgba([10, 10, 10, u8::MAX]);
+const PIXEL_BRIGHT: Rgba<u8> = Rgba([240, 240, 240, u8::MAX]);
+
+/// Generates an image whose left half is dark and whose right half is bright.
+fn generate_half_bright_image() -> RgbaImage {
+    RgbaImage::from_fn(16, 4, |x, _| {
+        if x < 8 { PIXEL_DARK } else { PIXEL_BRIGHT }
+    })
+}
+
+fn sort_bright_pixels(image: RgbaImage, output_only_segments: bool) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.5,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, output_only_segments, false)
+}
+
+#[test]
+fn output_only_segments_makes_non_segment_pixels_transparent() {
+    let sorted_image = sort_bright_pixels(generate_half_bright_image(), true);
+
+    for (x, _, pixel) in sorted_image.enumerate_pixels() {
+        if x < 8 {
+            assert_eq!(
+                pixel.0[3], 0,
+                "non-segment pixel at column {x} should be transparent"
+            );
+        } else {
+            assert_eq!(
+                *pixel, PIXEL_BRIGHT,
+                "segment pixel at column {x} should be kept"
+            );
+        }
+    }
+}
+
+#[test]
+fn output_with_all_pixels_keeps_non_segment_pixels_opaque() {
+    let original_image = generate_half_bright_image();
+    let sorted_image = sort_bright_pixels(original_image.clone(), false);
+
+    assert_eq!(sorted_image, original_image);
+}
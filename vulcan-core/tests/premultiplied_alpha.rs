@@ -0,0 +1,116 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+    },
+};
+
+const IMAGE_WIDTH: u32 = 16;
+
+/// Generates a single-row gradient that gets brighter to the right, but also more transparent,
+/// ending in a fully transparent pixel.
+fn generate_semi_transparent_gradient_image() -> RgbaImage {
+    RgbaImage::from_fn(IMAGE_WIDTH, 1, |x, _| {
+        let value = (x * 17) as u8;
+        Rgba([value, value, value, u8::MAX - value])
+    })
+}
+
+fn sort_by_luminance(image: RgbaImage, premultiply_alpha: bool) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        premultiply_alpha,
+    )
+    .expect("selection mode should be valid");
+
+    execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false)
+}
+
+#[test]
+fn straight_alpha_sort_ignores_transparency() {
+    let original_image = generate_semi_transparent_gradient_image();
+
+    // The straight colors are already in ascending order of luminance.
+    let sorted_image = sort_by_luminance(original_image.clone(), false);
+
+    assert_eq!(sorted_image, original_image);
+}
+
+#[test]
+fn premultiplied_alpha_sort_accounts_for_transparency() {
+    let original_image = generate_semi_transparent_gradient_image();
+
+    let straight_image = sort_by_luminance(original_image.clone(), false);
+    let premultiplied_image = sort_by_luminance(original_image.clone(), true);
+
+    assert_ne!(premultiplied_image, straight_image);
+
+    // Once premultiplied, the fully transparent (and brightest) pixel is black, so it must be
+    // moved to the start of the row, next to the opaque black pixel (their order is unspecified).
+    // Only the sorting is affected by premultiplication, so it keeps its straight color.
+    let mut darkest_pixels = [
+        premultiplied_image.get_pixel(0, 0).0,
+        premultiplied_image.get_pixel(1, 0).0,
+    ];
+    darkest_pixels.sort_unstable_by_key(|pixel| pixel[3]);
+
+    assert_eq!(
+        darkest_pixels,
+        [[u8::MAX, u8::MAX, u8::MAX, 0], [0, 0, 0, u8::MAX]]
+    );
+
+    // The sorted row must consist of exactly the original (straight) pixels.
+    let mut original_pixels: Vec<[u8; 4]> = original_image.pixels().map(|pixel| pixel.0).collect();
+    let mut sorted_pixels: Vec<[u8; 4]> =
+        premultiplied_image.pixels().map(|pixel| pixel.0).collect();
+    original_pixels.sort_unstable();
+    sorted_pixels.sort_unstable();
+
+    assert_eq!(sorted_pixels, original_pixels);
+}
+
+#[test]
+fn premultiplied_alpha_sort_leaves_unselected_pixels_unchanged() {
+    // A nearly transparent pixel whose color would not survive a premultiplication round trip,
+    // followed by opaque pixels that are selected and sorted.
+    let unselected_pixel = Rgba([0, 77, 13, 3]);
+    let original_image = RgbaImage::from_fn(IMAGE_WIDTH, 1, |x, _| {
+        if x == 0 {
+            unselected_pixel
+        } else {
+            let value = u8::MAX - (x * 8) as u8;
+            Rgba([value, value, value, u8::MAX])
+        }
+    });
+
+    let prepared_sort = prepare_pixel_sort(
+        original_image.clone(),
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.1,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        true,
+    )
+    .expect("selection mode should be valid");
+    let sorted_image = execute_axis_aligned_prepared_pixel_sort(prepared_sort, false, false);
+
+    assert_ne!(sorted_image, original_image);
+    assert_eq!(*sorted_image.get_pixel(0, 0), unselected_pixel);
+}
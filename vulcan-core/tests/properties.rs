@@ -0,0 +1,40 @@
+use image::Rgba;
+use vulcan_core::pixel_sorting::properties::{
+    compute_rgba_hsl_hue_gamma,
+    convert_gamma_encoded_srgb_u8_to_linear_f32,
+};
+
+#[test]
+fn srgb_transfer_function_maps_extremes_exactly() {
+    assert_eq!(
+        convert_gamma_encoded_srgb_u8_to_linear_f32(0),
+        0.0
+    );
+    assert_eq!(
+        convert_gamma_encoded_srgb_u8_to_linear_f32(u8::MAX),
+        1.0
+    );
+}
+
+#[test]
+fn srgb_transfer_function_maps_perceptual_mid_gray_to_half_linear_intensity() {
+    let linear_mid_gray = convert_gamma_encoded_srgb_u8_to_linear_f32(188);
+
+    assert!(
+        (linear_mid_gray - 0.5).abs() < 0.01,
+        "unexpected linear value {linear_mid_gray}"
+    );
+}
+
+#[test]
+fn gamma_hue_of_primary_colors_matches_their_hue_angle() {
+    let primary_colors_with_hues = [
+        (Rgba([u8::MAX, 0, 0, u8::MAX]), 0.0),
+        (Rgba([0, u8::MAX, 0, u8::MAX]), 120.0),
+        (Rgba([0, 0, u8::MAX, u8::MAX]), 240.0),
+    ];
+
+    for (pixel, expected_hue) in primary_colors_with_hues {
+        assert_eq!(compute_rgba_hsl_hue_gamma(&pixel), expected_hue);
+    }
+}
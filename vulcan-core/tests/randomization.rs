@@ -0,0 +1,128 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        PreparedSegmentSpan,
+        SegmentRandomizationMode,
+        execute_axis_aligned_prepared_pixel_sort,
+        prepare_pixel_sort,
+        randomize_prepared_segments,
+    },
+};
+
+const IMAGE_WIDTH: u32 = 64;
+const IMAGE_HEIGHT: u32 = 8;
+
+/// Generates an image whose red channel holds the column index of each pixel,
+/// while the remaining channels are filled with `blue`.
+fn generate_column_indexed_image(blue: u8) -> RgbaImage {
+    RgbaImage::from_fn(IMAGE_WIDTH, IMAGE_HEIGHT, |x, y| {
+        Rgba([x as u8, (y * 20) as u8, blue, u8::MAX])
+    })
+}
+
+/// Selects every pixel (so each row is a single segment), randomly splits the segments
+/// and reverses each split segment, which makes the split offsets visible in the red channel.
+fn reverse_randomized_segments(image: RgbaImage, seed: u64) -> RgbaImage {
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Reverse),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    let randomized_sort = randomize_prepared_segments(
+        prepared_sort,
+        SegmentRandomizationMode::Uniform {
+            low_inclusive: 2,
+            high_inclusive: 12,
+        },
+        Some(seed),
+    );
+
+    execute_axis_aligned_prepared_pixel_sort(randomized_sort, false, false)
+}
+
+/// Randomly splits the (full-row) segments of a column-indexed image
+/// and returns the resulting segment partition.
+fn randomized_segment_spans(seed: u64) -> Vec<Vec<PreparedSegmentSpan>> {
+    let prepared_sort = prepare_pixel_sort(
+        generate_column_indexed_image(0),
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .expect("selection mode should be valid");
+
+    randomize_prepared_segments(
+        prepared_sort,
+        SegmentRandomizationMode::Uniform {
+            low_inclusive: 2,
+            high_inclusive: 12,
+        },
+        Some(seed),
+    )
+    .segment_spans()
+}
+
+fn extract_red_channel(image: &RgbaImage) -> Vec<u8> {
+    image.pixels().map(|pixel| pixel.0[0]).collect()
+}
+
+#[test]
+fn same_seed_splits_different_images_at_same_offsets() {
+    let first_image = reverse_randomized_segments(generate_column_indexed_image(0), 1234);
+    let second_image = reverse_randomized_segments(generate_column_indexed_image(200), 1234);
+
+    assert_eq!(
+        extract_red_channel(&first_image),
+        extract_red_channel(&second_image)
+    );
+
+    // The segments must actually have been split, otherwise each row would be fully reversed.
+    let fully_reversed_row: Vec<u8> = (0..IMAGE_WIDTH as u8).rev().collect();
+    assert_ne!(
+        extract_red_channel(&first_image)[..IMAGE_WIDTH as usize],
+        fully_reversed_row[..]
+    );
+}
+
+#[test]
+fn rows_are_split_differently_from_each_other() {
+    let image = reverse_randomized_segments(generate_column_indexed_image(0), 1234);
+    let red_channel = extract_red_channel(&image);
+
+    let rows: Vec<&[u8]> = red_channel.chunks(IMAGE_WIDTH as usize).collect();
+    assert!(
+        rows.windows(2)
+            .any(|neighbouring_rows| neighbouring_rows[0] != neighbouring_rows[1])
+    );
+}
+
+#[test]
+fn same_seed_produces_identical_segment_partitions() {
+    assert_eq!(
+        randomized_segment_spans(42),
+        randomized_segment_spans(42)
+    );
+    assert_ne!(
+        randomized_segment_spans(42),
+        randomized_segment_spans(43)
+    );
+}
@@ -0,0 +1,63 @@
+use image::{Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    builder::{PixelSortBuilder, PixelSortError},
+    prepared::{
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        PreparedSegmentSpan,
+        prepare_pixel_sort,
+        split_prepared_segments_at_max_length,
+    },
+};
+
+#[test]
+fn segments_longer_than_maximum_length_are_split_into_consecutive_segments() {
+    let image = RgbaImage::from_pixel(1000, 1, Rgba([128, 128, 128, u8::MAX]));
+
+    let prepared_sort = prepare_pixel_sort(
+        image,
+        PreparedSegmentSelectionMode::LuminanceRange {
+            low: 0.0,
+            high: 1.0,
+            invert: false,
+        },
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .expect("selection mode should be valid");
+    assert_eq!(
+        prepared_sort.segment_spans(),
+        vec![vec![PreparedSegmentSpan {
+            start_index: 0,
+            length: 1000,
+        }]]
+    );
+
+    let split_sort = split_prepared_segments_at_max_length(prepared_sort, 256);
+
+    let expected_spans = [(0, 256), (256, 256), (512, 256), (768, 232)]
+        .into_iter()
+        .map(|(start_index, length)| PreparedSegmentSpan {
+            start_index,
+            length,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(split_sort.segment_spans(), vec![expected_spans]);
+}
+
+#[test]
+fn zero_maximum_segment_length_is_rejected_by_builder() {
+    let image = RgbaImage::from_pixel(16, 1, Rgba([128, 128, 128, u8::MAX]));
+
+    let result = PixelSortBuilder::new().max_segment_length(0).run(image);
+
+    assert!(matches!(
+        result,
+        Err(PixelSortError::ZeroMaximumSegmentLength)
+    ));
+}
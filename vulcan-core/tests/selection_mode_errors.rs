@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use image::{GrayImage, Rgba, RgbaImage};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    builder::PixelSortBuilder,
+    config::PixelSortConfig,
+    prepared::{
+        BooleanOp,
+        PreparedSegmentSelectionMode,
+        PreparedSegmentSortingMode,
+        SelectionModeError,
+        prepare_pixel_sort,
+    },
+};
+
+const IMAGE_SIZE: u32 = 8;
+
+fn prepare_gray_image_sort(
+    selection_mode: PreparedSegmentSelectionMode,
+) -> Result<(), SelectionModeError> {
+    let image = RgbaImage::from_pixel(
+        IMAGE_SIZE,
+        IMAGE_SIZE,
+        Rgba([128, 128, 128, u8::MAX]),
+    );
+
+    prepare_pixel_sort(
+        image,
+        selection_mode,
+        PreparedSegmentSortingMode::Luminance,
+        None,
+        ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending),
+        false,
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn inverted_range_is_rejected_instead_of_panicking() {
+    let result = prepare_gray_image_sort(PreparedSegmentSelectionMode::LuminanceRange {
+        low: 0.75,
+        high: 0.25,
+        invert: false,
+    });
+
+    assert!(matches!(
+        result,
+        Err(SelectionModeError::InvertedRange { .. })
+    ));
+}
+
+#[test]
+fn mask_of_different_size_is_rejected() {
+    let result = prepare_gray_image_sort(PreparedSegmentSelectionMode::MaskImage {
+        mask: Arc::new(GrayImage::new(IMAGE_SIZE / 2, IMAGE_SIZE)),
+    });
+
+    assert!(matches!(
+        result,
+        Err(SelectionModeError::MaskDimensionMismatch {
+            mask_width: 4,
+            mask_height: 8,
+            image_width: 8,
+            image_height: 8,
+        })
+    ));
+}
+
+#[test]
+fn mask_nested_in_decoded_share_code_is_rejected_as_empty() {
+    let config = PixelSortBuilder::new()
+        .selection(PreparedSegmentSelectionMode::Combined {
+            first: Box::new(PreparedSegmentSelectionMode::LuminanceRange {
+                low: 0.25,
+                high: 0.75,
+                invert: false,
+            }),
+            second: Box::new(PreparedSegmentSelectionMode::MaskImage {
+                mask: Arc::new(GrayImage::new(IMAGE_SIZE, IMAGE_SIZE)),
+            }),
+            op: BooleanOp::And,
+        })
+        .into_config();
+
+    // Masks are not part of share codes, so the decoded mask is empty.
+    let decoded_config = PixelSortConfig::from_share_code(&config.to_share_code())
+        .expect("share code should be valid");
+
+    assert!(matches!(
+        prepare_gray_image_sort(decoded_config.selection_mode),
+        Err(SelectionModeError::EmptyMask)
+    ));
+}
@@ -22,4 +22,9 @@ impl CancellationToken {
     pub fn cancel(&self) {
         self.token.store(true, Ordering::SeqCst);
     }
+
+    /// Clears the cancellation, so that the token can be reused for the next operation.
+    pub fn reset(&self) {
+        self.token.store(false, Ordering::SeqCst);
+    }
 }
@@ -0,0 +1,241 @@
+use std::{num::NonZeroUsize, path::PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use vulcan_core::pixel_sorting::{
+    ImageSortingDirection,
+    PixelSegmentSortDirection,
+    prepared::{PreparedSegmentSelectionMode, PreparedSegmentSortingMode},
+    properties::LuminanceWeights,
+};
+
+#[derive(Parser)]
+#[command(name = "vulcan", version, about = "A pixel sorting tool.")]
+pub struct CliArgs {
+    /// What to do. Opens the graphical interface if not specified.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Opens the graphical interface.
+    Gui,
+
+    /// Pixel sorts an image without opening the graphical interface (e.g. for scripting).
+    Generate(GenerateArgs),
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Path to the image to sort, or `-` to read it from standard input.
+    #[arg(
+        short = 'i',
+        long = "input",
+        required_unless_present = "input_directory_path",
+        conflicts_with = "input_directory_path",
+        requires = "output_image_path"
+    )]
+    pub input_image_path: Option<PathBuf>,
+
+    /// Path to save the sorted image to. The format is picked based on the extension
+    /// (PNG, JPEG, WebP, BMP or TIFF), falling back to PNG.
+    /// Use `-` to write it to standard output (always as PNG) instead.
+    #[arg(
+        short = 'o',
+        long = "output",
+        conflicts_with_all = ["input_directory_path", "output_directory_path"]
+    )]
+    pub output_image_path: Option<PathBuf>,
+
+    /// Sort all images in this directory instead of a single image (see `--output-dir`).
+    /// Files that aren't images are skipped.
+    #[arg(long = "input-dir", requires = "output_directory_path")]
+    pub input_directory_path: Option<PathBuf>,
+
+    /// The directory to save the sorted images from `--input-dir` to. Each sorted image is
+    /// named after its input image, with `_sorted` appended to the file stem.
+    #[arg(long = "output-dir")]
+    pub output_directory_path: Option<PathBuf>,
+
+    /// Also save the structure of the sorted segments (where each segment of every row starts
+    /// and how long it is) to this path as JSON, e.g. for analysis. The image is then always
+    /// sorted as RGBA8, as only that sorting path has segments to save.
+    #[arg(long = "dump-segments", conflicts_with = "input_directory_path")]
+    pub dump_segments_path: Option<PathBuf>,
+
+    /// Also save the red, green, blue and alpha channels of each sorted image as separate
+    /// grayscale images (e.g. for compositing), named after the output image with `_red`,
+    /// `_green`, `_blue` and `_alpha` appended to its file stem.
+    #[arg(long)]
+    pub split_channels: bool,
+
+    /// Overwrite output images (and the `--dump-segments` file) if they already exist.
+    #[arg(long)]
+    pub overwrite: bool,
+
+    /// The pixel property that decides which pixels are part of the sorted segments.
+    #[arg(long, value_enum, default_value_t = CliSelectionMode::Luminance)]
+    pub selection: CliSelectionMode,
+
+    /// The lower bound of the selected range (inclusive).
+    /// Hue is in degrees (`0..=360`), all other properties are normalized (`0..=1`).
+    #[arg(long, default_value_t = 0.25)]
+    pub low: f32,
+
+    /// The upper bound of the selected range (inclusive), see `--low`.
+    #[arg(long, default_value_t = 0.75)]
+    pub high: f32,
+
+    /// The pixel property that the segments are sorted by.
+    #[arg(long, value_enum, default_value_t = CliSortingMode::Luminance)]
+    pub sorting: CliSortingMode,
+
+    /// The weights of the red, green and blue channels when selecting or sorting by luminance.
+    #[arg(long, value_enum, default_value_t = CliLuminanceWeights::Rec709)]
+    pub luminance_weights: CliLuminanceWeights,
+
+    /// The direction in which the pixels are sorted.
+    #[arg(long, value_enum, default_value_t = CliSortingDirection::Horizontal)]
+    pub direction: CliSortingDirection,
+
+    /// The angle (in degrees, counter-clockwise) when sorting in the `angled` direction.
+    #[arg(long, default_value_t = 45.0)]
+    pub angle: f32,
+
+    /// How the pixels of each segment are ordered.
+    #[arg(long, value_enum, default_value_t = CliSortingOrder::Ascending)]
+    pub order: CliSortingOrder,
+
+    /// Keep pixels with equal sorting keys in their original order (slightly slower,
+    /// but reproducible across runs and platforms).
+    #[arg(long)]
+    pub stable_sort: bool,
+
+    /// The largest number of threads to sort with (including when sorting a directory).
+    /// Uses all available threads if not specified.
+    #[arg(long)]
+    pub threads: Option<NonZeroUsize>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliSelectionMode {
+    Luminance,
+    Hue,
+    Saturation,
+    Alpha,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliSortingMode {
+    Luminance,
+    Hue,
+    Saturation,
+    Lightness,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    OklabLightness,
+    OklabChroma,
+    OklabHue,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliLuminanceWeights {
+    Rec709,
+    Rec601,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliSortingDirection {
+    Horizontal,
+    Vertical,
+    Angled,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CliSortingOrder {
+    Ascending,
+    Descending,
+    Reverse,
+}
+
+impl GenerateArgs {
+    fn luminance_weights(&self) -> LuminanceWeights {
+        match self.luminance_weights {
+            CliLuminanceWeights::Rec709 => LuminanceWeights::REC_709,
+            CliLuminanceWeights::Rec601 => LuminanceWeights::REC_601,
+        }
+    }
+
+    pub fn selection_mode(&self) -> PreparedSegmentSelectionMode {
+        let (low, high) = (self.low, self.high);
+
+        match self.selection {
+            CliSelectionMode::Luminance => match self.luminance_weights {
+                CliLuminanceWeights::Rec709 => PreparedSegmentSelectionMode::LuminanceRange {
+                    low,
+                    high,
+                    invert: false,
+                },
+                CliLuminanceWeights::Rec601 => {
+                    PreparedSegmentSelectionMode::WeightedLuminanceRange {
+                        weights: self.luminance_weights(),
+                        low,
+                        high,
+                        invert: false,
+                    }
+                }
+            },
+            CliSelectionMode::Hue => PreparedSegmentSelectionMode::HueRange {
+                low,
+                high,
+                invert: false,
+            },
+            CliSelectionMode::Saturation => PreparedSegmentSelectionMode::SaturationRange {
+                low,
+                high,
+                invert: false,
+            },
+            CliSelectionMode::Alpha => PreparedSegmentSelectionMode::AlphaRange { low, high },
+        }
+    }
+
+    pub fn sorting_mode(&self) -> PreparedSegmentSortingMode {
+        match self.sorting {
+            CliSortingMode::Luminance => match self.luminance_weights {
+                CliLuminanceWeights::Rec709 => PreparedSegmentSortingMode::Luminance,
+                CliLuminanceWeights::Rec601 => PreparedSegmentSortingMode::WeightedLuminance {
+                    weights: self.luminance_weights(),
+                },
+            },
+            CliSortingMode::Hue => PreparedSegmentSortingMode::Hue,
+            CliSortingMode::Saturation => PreparedSegmentSortingMode::Saturation,
+            CliSortingMode::Lightness => PreparedSegmentSortingMode::Lightness,
+            CliSortingMode::Red => PreparedSegmentSortingMode::Red,
+            CliSortingMode::Green => PreparedSegmentSortingMode::Green,
+            CliSortingMode::Blue => PreparedSegmentSortingMode::Blue,
+            CliSortingMode::Alpha => PreparedSegmentSortingMode::Alpha,
+            CliSortingMode::OklabLightness => PreparedSegmentSortingMode::OklabLightness,
+            CliSortingMode::OklabChroma => PreparedSegmentSortingMode::OklabChroma,
+            CliSortingMode::OklabHue => PreparedSegmentSortingMode::OklabHue,
+        }
+    }
+
+    pub fn sorting_direction(&self) -> ImageSortingDirection {
+        let segment_direction = match self.order {
+            CliSortingOrder::Ascending => PixelSegmentSortDirection::Ascending,
+            CliSortingOrder::Descending => PixelSegmentSortDirection::Descending,
+            CliSortingOrder::Reverse => PixelSegmentSortDirection::Reverse,
+        };
+
+        match self.direction {
+            CliSortingDirection::Horizontal => ImageSortingDirection::Horizontal(segment_direction),
+            CliSortingDirection::Vertical => ImageSortingDirection::Vertical(segment_direction),
+            CliSortingDirection::Angled => ImageSortingDirection::Angled {
+                degrees: self.angle,
+                direction: segment_direction,
+            },
+        }
+    }
+}
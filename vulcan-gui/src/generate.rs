@@ -0,0 +1,528 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use miette::{Context, IntoDiagnostic, miette};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use tracing::{info, warn};
+use vulcan_core::{
+    channels::split_rgba,
+    export::segments_to_json,
+    io::{
+        DecodedPixels,
+        ExtendablePath,
+        ImageSaveOptions,
+        OutputFormat,
+        PngCompression,
+        decode_image_from_memory_preserving_precision,
+        normalize_output_file_path_for_format,
+        save_image,
+        write_image_as_png,
+    },
+    parallelism::build_sorting_thread_pool,
+    pixel_sorting::{
+        builder::PixelSortBuilder,
+        grayscale::perform_luma_pixel_sort,
+        immediate::{ImmediateSegmentSelectionMode, PixelSortOptions, perform_pixel_sort},
+        prepared::{
+            PreparedSegmentSelectionMode,
+            PreparedSegmentSortingMode,
+            execute_axis_aligned_prepared_pixel_sort,
+        },
+    },
+};
+
+use crate::cli::GenerateArgs;
+
+/// The suffix appended to the file stems of images sorted in batch mode.
+const BATCH_OUTPUT_FILE_STEM_SUFFIX: &str = "_sorted";
+
+/// The path that stands for standard input (as `--input`) or standard output (as `--output`).
+const STANDARD_STREAM_PATH: &str = "-";
+
+/// The suffixes appended to the file stem of the output image for each of its channels
+/// (in the order returned by [`split_rgba`]), see `--split-channels`.
+const CHANNEL_FILE_STEM_SUFFIXES: [&str; 4] = ["_red", "_green", "_blue", "_alpha"];
+
+/// Pixel sorts the input image (or all images in the input directory) according to
+/// the given arguments and saves the result, without starting the graphical interface.
+pub fn cmd_generate(args: GenerateArgs) -> miette::Result<()> {
+    let Some(thread_count) = args.threads else {
+        return sort_images(&args);
+    };
+
+    build_sorting_thread_pool(thread_count)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to start {thread_count} sorting threads."))?
+        .install(|| sort_images(&args))
+}
+
+/// Sorts the input image (or the images in the input directory), see [`cmd_generate`].
+fn sort_images(args: &GenerateArgs) -> miette::Result<()> {
+    match (
+        &args.input_image_path,
+        &args.output_image_path,
+        &args.input_directory_path,
+        &args.output_directory_path,
+    ) {
+        (Some(input_image_path), Some(output_image_path), None, None) => {
+            if args.split_channels && output_image_path == Path::new(STANDARD_STREAM_PATH) {
+                return Err(miette!(
+                    "--split-channels names the channel images after the output image, \
+                    so it can't be used when writing to standard output."
+                ));
+            }
+
+            let encoded_input_image = if input_image_path == Path::new(STANDARD_STREAM_PATH) {
+                read_standard_input()?
+            } else {
+                read_input_image_file(input_image_path)?
+            };
+
+            let (sorted_image, icc_profile) = sort_encoded_image(args, &encoded_input_image)?;
+
+            if output_image_path == Path::new(STANDARD_STREAM_PATH) {
+                return write_image_to_standard_output(&sorted_image, icc_profile.as_deref());
+            }
+
+            let output_image_path = save_sorted_image(
+                args,
+                &sorted_image,
+                icc_profile.as_deref(),
+                output_image_path,
+            )?;
+            info!(
+                "Saved sorted image to {}.",
+                output_image_path.display()
+            );
+
+            Ok(())
+        }
+        (None, None, Some(input_directory_path), Some(output_directory_path)) => {
+            sort_image_directory(args, input_directory_path, output_directory_path)
+        }
+        // Other combinations are rejected by the argument parser.
+        _ => Err(miette!(
+            "Specify either --input and --output, or --input-dir and --output-dir."
+        )),
+    }
+}
+
+/// Sorts every image directly inside `input_directory_path` (in parallel),
+/// saving the results into `output_directory_path` and printing a line per image.
+fn sort_image_directory(
+    args: &GenerateArgs,
+    input_directory_path: &Path,
+    output_directory_path: &Path,
+) -> miette::Result<()> {
+    let mut input_image_paths = Vec::new();
+
+    for directory_entry in fs::read_dir(input_directory_path)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to read input directory."))?
+    {
+        let file_path = directory_entry
+            .into_diagnostic()
+            .wrap_err_with(|| miette!("Failed to read input directory entry."))?
+            .path();
+
+        let is_readable_image = file_path.is_file()
+            && ImageFormat::from_path(&file_path).is_ok_and(|format| format.reading_enabled());
+
+        if is_readable_image {
+            input_image_paths.push(file_path);
+        } else {
+            println!(
+                "{}: skipped (not a supported image)",
+                file_path.display()
+            );
+        }
+    }
+
+    input_image_paths.sort();
+
+    fs::create_dir_all(output_directory_path)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to create output directory."))?;
+
+    let number_of_failed_images = input_image_paths
+        .par_iter()
+        .filter(|input_image_path| {
+            let sort_result = input_image_path
+                .file_name()
+                .and_then(|file_name| {
+                    output_directory_path
+                        .join(file_name)
+                        .with_suffix_to_stem(BATCH_OUTPUT_FILE_STEM_SUFFIX)
+                })
+                .ok_or_else(|| miette!("Failed to construct output path."))
+                .and_then(|output_image_path| {
+                    sort_image_file(args, input_image_path, &output_image_path)
+                });
+
+            match sort_result {
+                Ok(output_image_path) => {
+                    println!(
+                        "{}: sorted into {}",
+                        input_image_path.display(),
+                        output_image_path.display()
+                    );
+                    false
+                }
+                Err(error) => {
+                    println!(
+                        "{}: failed: {error:?}",
+                        input_image_path.display()
+                    );
+                    true
+                }
+            }
+        })
+        .count();
+
+    if number_of_failed_images > 0 {
+        return Err(miette!(
+            "Failed to sort {number_of_failed_images} of {} images.",
+            input_image_paths.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the immediate segment selection mode equivalent to the given prepared modes,
+/// if there is one. The immediate sorting path (which, unlike the prepared one, also works on
+/// images with 16 bits per channel) always sorts segments by the property they were selected by.
+fn immediate_selection_mode_sorting_by_selected_property(
+    selection_mode: &PreparedSegmentSelectionMode,
+    sorting_mode: &PreparedSegmentSortingMode,
+) -> Option<ImmediateSegmentSelectionMode> {
+    match (selection_mode, sorting_mode) {
+        (
+            &PreparedSegmentSelectionMode::LuminanceRange {
+                low,
+                high,
+                invert: false,
+            },
+            PreparedSegmentSortingMode::Luminance,
+        ) => Some(ImmediateSegmentSelectionMode::LuminanceRange { low, high }),
+        (
+            &PreparedSegmentSelectionMode::HueRange {
+                low,
+                high,
+                invert: false,
+            },
+            PreparedSegmentSortingMode::Hue,
+        ) => Some(ImmediateSegmentSelectionMode::HueRange { low, high }),
+        (
+            &PreparedSegmentSelectionMode::SaturationRange {
+                low,
+                high,
+                invert: false,
+            },
+            PreparedSegmentSortingMode::Saturation,
+        ) => Some(ImmediateSegmentSelectionMode::SaturationRange { low, high }),
+        _ => None,
+    }
+}
+
+/// Sorts the given RGBA8 image using the prepared sorting path,
+/// saving its segments first if requested (see `--dump-segments`).
+fn sort_rgba8_image(
+    args: &GenerateArgs,
+    rgba_image: RgbaImage,
+    selection_mode: PreparedSegmentSelectionMode,
+    sorting_mode: PreparedSegmentSortingMode,
+) -> miette::Result<DynamicImage> {
+    let pixel_sort = PixelSortBuilder::new()
+        .selection(selection_mode)
+        .sorting(sorting_mode)
+        .direction(args.sorting_direction())
+        .stable_sort(args.stable_sort);
+
+    let prepared_sort = pixel_sort
+        .prepare(rgba_image)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to prepare pixel sort (see --low and --high)."))?;
+
+    if let Some(dump_segments_path) = &args.dump_segments_path {
+        save_segments_file(
+            args,
+            &segments_to_json(&prepared_sort),
+            dump_segments_path,
+        )?;
+    }
+
+    Ok(DynamicImage::ImageRgba8(
+        execute_axis_aligned_prepared_pixel_sort(
+            prepared_sort,
+            pixel_sort.config().output_only_segments,
+            pixel_sort.config().stable_sort,
+        ),
+    ))
+}
+
+/// Saves the JSON-serialized segments of a prepared sort to `dump_segments_path`.
+fn save_segments_file(
+    args: &GenerateArgs,
+    segments_json: &str,
+    dump_segments_path: &Path,
+) -> miette::Result<()> {
+    let mut open_options = OpenOptions::new();
+    open_options.write(true);
+
+    if args.overwrite {
+        open_options.create(true).truncate(true);
+    } else {
+        open_options.create_new(true);
+    }
+
+    open_options
+        .open(dump_segments_path)
+        .and_then(|mut segments_file| segments_file.write_all(segments_json.as_bytes()))
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to save segments file."))
+}
+
+/// Reads the (still encoded) image piped into standard input.
+fn read_standard_input() -> miette::Result<Vec<u8>> {
+    let mut encoded_input_image = Vec::new();
+
+    io::stdin()
+        .lock()
+        .read_to_end(&mut encoded_input_image)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to read input image from standard input."))?;
+
+    Ok(encoded_input_image)
+}
+
+/// Reads the (still encoded) image at `input_image_path`.
+fn read_input_image_file(input_image_path: &Path) -> miette::Result<Vec<u8>> {
+    fs::read(input_image_path)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to read input image."))
+}
+
+/// Encodes the sorted image as PNG and writes it to standard output.
+fn write_image_to_standard_output(
+    sorted_image: &DynamicImage,
+    icc_profile: Option<&[u8]>,
+) -> miette::Result<()> {
+    let compression = PngCompression::default();
+    let mut standard_output = BufWriter::new(io::stdout().lock());
+
+    write_image_as_png(
+        sorted_image,
+        &mut standard_output,
+        compression.compression_type(),
+        compression.filter_type(),
+        icc_profile,
+    )
+    .into_diagnostic()
+    .wrap_err_with(|| miette!("Failed to write sorted image to standard output."))?;
+
+    standard_output
+        .flush()
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to write sorted image to standard output."))
+}
+
+/// Sorts the image at `input_image_path` and saves it to `output_image_path` (with its extension
+/// adjusted to the output format, if needed), returning the path the image was saved to.
+fn sort_image_file(
+    args: &GenerateArgs,
+    input_image_path: &Path,
+    output_image_path: &Path,
+) -> miette::Result<PathBuf> {
+    let encoded_input_image = read_input_image_file(input_image_path)?;
+
+    let (sorted_image, icc_profile) = sort_encoded_image(args, &encoded_input_image)?;
+
+    save_sorted_image(
+        args,
+        &sorted_image,
+        icc_profile.as_deref(),
+        output_image_path,
+    )
+}
+
+/// Decodes and sorts the given encoded image, returning the sorted image
+/// along with the ICC profile that still applies to it (if any).
+fn sort_encoded_image(
+    args: &GenerateArgs,
+    encoded_input_image: &[u8],
+) -> miette::Result<(DynamicImage, Option<Vec<u8>>)> {
+    let input_image = decode_image_from_memory_preserving_precision(encoded_input_image, true)
+        .into_diagnostic()
+        .wrap_err_with(|| miette!("Failed to decode input image."))?;
+
+    let selection_mode = args.selection_mode();
+    let sorting_mode = args.sorting_mode();
+
+    // Grayscale images sorted by luminance take the specialized (and much lighter) Luma8 path,
+    // and images with more than 8 bits per channel keep their precision where the modes allow it
+    // (see `immediate_selection_mode_sorting_by_selected_property`).
+    // Everything else, as well as any image whose segments are to be saved, is sorted as RGBA8.
+    // Invalid selection ranges are left to the RGBA8 path as well, which rejects them.
+    let (sorted_image, icc_profile) = match (input_image.image, selection_mode, sorting_mode) {
+        (
+            DecodedPixels::Luma8(gray_image),
+            PreparedSegmentSelectionMode::LuminanceRange {
+                low,
+                high,
+                invert: false,
+            },
+            PreparedSegmentSortingMode::Luminance,
+        ) if args.dump_segments_path.is_none() && low <= high => (
+            DynamicImage::ImageLuma8(perform_luma_pixel_sort(
+                gray_image,
+                low,
+                high,
+                args.sorting_direction(),
+            )),
+            input_image.icc_profile,
+        ),
+        (DecodedPixels::Rgba16(rgba16_image), selection_mode, sorting_mode)
+            if args.dump_segments_path.is_none() && selection_mode.validate_range().is_ok() =>
+        {
+            match immediate_selection_mode_sorting_by_selected_property(
+                &selection_mode,
+                &sorting_mode,
+            ) {
+                Some(immediate_selection_mode) => (
+                    DynamicImage::ImageRgba16(perform_pixel_sort(
+                        rgba16_image,
+                        immediate_selection_mode,
+                        PixelSortOptions {
+                            direction: args.sorting_direction(),
+                            stable_sort: args.stable_sort,
+                        },
+                    )),
+                    input_image.icc_profile,
+                ),
+                None => {
+                    warn!(
+                        "The selected segment selection and sorting modes are not supported \
+                        on images with more than 8 bits per channel, sorting as RGBA8."
+                    );
+
+                    (
+                        sort_rgba8_image(
+                            args,
+                            DynamicImage::ImageRgba16(rgba16_image).into_rgba8(),
+                            selection_mode,
+                            sorting_mode,
+                        )?,
+                        input_image.icc_profile,
+                    )
+                }
+            }
+        }
+        (decoded_pixels, selection_mode, sorting_mode) => {
+            let (rgba_image, icc_profile) = match decoded_pixels {
+                DecodedPixels::Rgba8(rgba_image) => (rgba_image, input_image.icc_profile),
+                DecodedPixels::Rgba16(rgba16_image) => (
+                    DynamicImage::ImageRgba16(rgba16_image).into_rgba8(),
+                    input_image.icc_profile,
+                ),
+                // A grayscale ICC profile no longer applies once the image is converted to RGBA8.
+                DecodedPixels::Luma8(gray_image) => (
+                    DynamicImage::ImageLuma8(gray_image).into_rgba8(),
+                    None,
+                ),
+            };
+
+            (
+                sort_rgba8_image(args, rgba_image, selection_mode, sorting_mode)?,
+                icc_profile,
+            )
+        }
+    };
+
+    Ok((sorted_image, icc_profile))
+}
+
+/// Saves the sorted image to `output_image_path` (with its extension adjusted to the output
+/// format, if needed), returning the path the image was saved to.
+fn save_sorted_image(
+    args: &GenerateArgs,
+    sorted_image: &DynamicImage,
+    icc_profile: Option<&[u8]>,
+    output_image_path: &Path,
+) -> miette::Result<PathBuf> {
+    // The format is picked based on the extension, falling back to PNG for unknown extensions.
+    let output_format = output_image_path
+        .extension()
+        .and_then(OutputFormat::from_extension)
+        .unwrap_or(OutputFormat::Png {
+            compression: PngCompression::default(),
+        });
+
+    let output_image_path = normalize_output_file_path_for_format(output_image_path, output_format);
+
+    save_image(
+        sorted_image,
+        &output_image_path,
+        output_format,
+        ImageSaveOptions {
+            overwrite_existing: args.overwrite,
+            icc_profile,
+        },
+    )
+    .into_diagnostic()
+    .wrap_err_with(|| miette!("Failed to save sorted image."))?;
+
+    if args.split_channels {
+        save_channel_images(
+            args,
+            sorted_image,
+            &output_image_path,
+            output_format,
+        )?;
+    }
+
+    Ok(output_image_path)
+}
+
+/// Saves each channel of the sorted image as a separate grayscale image next to
+/// `output_image_path`, in the same format (see `--split-channels`).
+fn save_channel_images(
+    args: &GenerateArgs,
+    sorted_image: &DynamicImage,
+    output_image_path: &Path,
+    output_format: OutputFormat,
+) -> miette::Result<()> {
+    let channel_images = split_rgba(&sorted_image.to_rgba8());
+
+    for (channel_image, file_stem_suffix) in
+        channel_images.into_iter().zip(CHANNEL_FILE_STEM_SUFFIXES)
+    {
+        let channel_image_path = output_image_path
+            .with_suffix_to_stem(file_stem_suffix)
+            .ok_or_else(|| miette!("Failed to construct channel image path."))?;
+
+        // The color profile of the sorted image doesn't apply to its individual channels.
+        save_image(
+            &DynamicImage::ImageLuma8(channel_image),
+            &channel_image_path,
+            output_format,
+            ImageSaveOptions {
+                overwrite_existing: args.overwrite,
+                icc_profile: None,
+            },
+        )
+        .into_diagnostic()
+        .wrap_err_with(|| {
+            miette!(
+                "Failed to save channel image {}.",
+                channel_image_path.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
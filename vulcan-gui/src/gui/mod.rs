@@ -1,4 +1,9 @@
-use std::{path::PathBuf, sync::Arc, time::Instant};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 use eframe::App;
 use egui::{
@@ -6,7 +11,11 @@ use egui::{
     CentralPanel,
     ColorImage,
     Direction,
+    DroppedFile,
     ImageData,
+    Key,
+    KeyboardShortcut,
+    Modifiers,
     Pos2,
     TextureId,
     TextureOptions,
@@ -16,12 +25,19 @@ use egui::{
     mutex::RwLock,
 };
 use egui_taffy::{TuiBuilderLogic, taffy};
-use image::RgbaImage;
-use vulcan_core::io::ImageSaveError;
+use image::{GrayImage, RgbaImage};
+use vulcan_core::{
+    io::ImageSaveError,
+    pixel_sorting::{
+        builder::PixelSortError,
+        config::RecordedOperation,
+        prepared::PreparedSegmentSelectionMode,
+    },
+};
 
 use crate::{
     gui::panels::{center::CentralView, right::RightSidebar},
-    worker::{ImageLoadError, WorkerHandle, WorkerResponse},
+    worker::{ImageFileInfo, ImageLoadError, WorkerHandle, WorkerRequest, WorkerResponse},
 };
 
 mod panels;
@@ -29,14 +45,37 @@ mod panels;
 pub struct SourceImage {
     file_path: PathBuf,
     image: Arc<RgbaImage>,
+
+    /// The ICC color profile of the source image, if any. Embedded into saved images.
+    icc_profile: Option<Arc<Vec<u8>>>,
+
+    /// The size and format of the file the image was opened from.
+    file_info: ImageFileInfo,
+
     image_aspect_ratio: f32,
     image_texture: SizedTexture,
+
+    /// A downscaled copy of the image used in proxy mode, if one has been generated.
+    proxy: Option<ProxyImage>,
+}
+
+/// A downscaled copy of a [`SourceImage`] that is sorted (and previewed) in place of
+/// the full-size image while in proxy mode, so that tweaking parameters stays responsive.
+pub struct ProxyImage {
+    image: Arc<RgbaImage>,
+
+    /// The largest width and height the proxy was generated for.
+    max_size: u32,
 }
 
 pub struct ProcessedImage {
     image: Arc<RgbaImage>,
     image_aspect_ratio: f32,
     image_texture: SizedTexture,
+
+    /// The pixel sorts that produced this image from the source image, in the order
+    /// they were applied in. Adjustments (e.g. posterization) are not recorded.
+    recorded_operations: Vec<RecordedOperation>,
 }
 
 pub struct ThresholdPreview {
@@ -48,40 +87,109 @@ pub struct ThresholdPreview {
 pub struct ProcessedImageHistoryEntry {
     image: Arc<RgbaImage>,
     image_aspect_ratio: f32,
+
+    /// See [`ProcessedImage::recorded_operations`].
+    recorded_operations: Vec<RecordedOperation>,
 }
 
+/// An image that has recently been opened, see [`SharedState::recent_files`].
+pub struct RecentFile {
+    file_path: PathBuf,
+
+    /// `None` until the background worker has generated the thumbnail (or if it failed to).
+    thumbnail: Option<SizedTexture>,
+}
+
+/// A grayscale image loaded by the user to select which pixels are sorted
+/// (see [`vulcan_core::pixel_sorting::prepared::PreparedSegmentSelectionMode::MaskImage`]).
+pub struct SelectionMask {
+    file_path: PathBuf,
+    mask: Arc<GrayImage>,
+}
+
+/// How many recently opened files are remembered (and persisted between runs).
+const MAX_RECENT_FILES: usize = 8;
+
+/// File extensions (lowercase) of the image files that can be opened as source images.
+pub(crate) const SUPPORTED_INPUT_IMAGE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// The key under which the paths of recently opened files are persisted in eframe's storage.
+const RECENT_FILES_STORAGE_KEY: &str = "recent-files";
+
+/// The key under which [`SharedState::last_used_directory`] is persisted in eframe's storage.
+const LAST_USED_DIRECTORY_STORAGE_KEY: &str = "last-used-directory";
+
 pub struct SharedState {
     source_image: Option<SourceImage>,
 
+    /// Recently opened files, most recent first. At most [`MAX_RECENT_FILES`] long.
+    recent_files: Vec<RecentFile>,
+
+    /// The directory of the most recently opened or saved file, which file dialogs start in.
+    last_used_directory: Option<PathBuf>,
+
     /// Represents the history stack of the processing. Separated from the last image,
     /// as the history stack doesn't have an allocated texture.
     processed_image_history_stack: Vec<ProcessedImageHistoryEntry>,
 
+    /// Processed images that have been undone, most recently undone last.
+    /// Cleared whenever a new processed image arrives.
+    processed_image_redo_stack: Vec<ProcessedImageHistoryEntry>,
+
     /// The last (current) state of the processed image. In contrast to `processed_image_history_stack`, this
     processed_image_last: Option<ProcessedImage>,
 
     threshold_preview: Option<ThresholdPreview>,
     is_waiting_for_updated_preview: bool,
 
+    /// The region of the image that pixel sorting is restricted to, if any (drawn over
+    /// the central image). Normalized to `0.0..=1.0` of the image's width and height,
+    /// so it also applies to processed images of different sizes.
+    sorting_region: Option<egui::Rect>,
+
+    /// The mask used by the mask image selection mode, if one has been loaded.
+    selection_mask: Option<SelectionMask>,
+
     last_threshold_hover_time: Instant,
 
     is_loading_image: bool,
+    is_loading_selection_mask: bool,
+    is_generating_proxy_image: bool,
     is_processing_image: bool,
+
+    /// The progress of the running pixel sort (from `0.0` to `1.0`), if it has reported any.
+    processing_progress: Option<f32>,
+
+    /// The remaining operations of the recipe that is being applied, if any.
+    /// The next one is sent to the worker once the previous one has produced an image.
+    pending_recipe_operations: VecDeque<RecordedOperation>,
+
     is_saving_image: bool,
+    is_copying_image: bool,
 }
 
 impl SharedState {
     pub fn new() -> Self {
         Self {
             source_image: None,
+            recent_files: Vec::new(),
+            last_used_directory: None,
             processed_image_history_stack: Vec::new(),
+            processed_image_redo_stack: Vec::new(),
             processed_image_last: None,
             threshold_preview: None,
+            sorting_region: None,
+            selection_mask: None,
             last_threshold_hover_time: Instant::now(),
             is_waiting_for_updated_preview: false,
             is_loading_image: false,
+            is_loading_selection_mask: false,
+            is_generating_proxy_image: false,
             is_processing_image: false,
+            processing_progress: None,
+            pending_recipe_operations: VecDeque::new(),
             is_saving_image: false,
+            is_copying_image: false,
         }
     }
 }
@@ -144,6 +252,277 @@ pub(crate) fn free_texture(texture_manager: &RwLock<TextureManager>, texture_id:
     locked_texture_manager.free(texture_id);
 }
 
+/// Steps back by one entry in the processing history, moving the current processed image
+/// onto the redo stack. Does nothing if there is no history to step back to.
+pub(crate) fn undo_processed_image(state: &mut SharedState, ctx: &egui::Context) {
+    if state.processed_image_last.is_none() {
+        return;
+    }
+
+    let Some(last_history_entry) = state.processed_image_history_stack.pop() else {
+        return;
+    };
+
+    let current_last_processed = state
+        .processed_image_last
+        .take()
+        .expect("checked above that there is a last processed image");
+
+    let texture_manager = ctx.tex_manager();
+    free_texture(
+        &texture_manager,
+        current_last_processed.image_texture.id,
+    );
+
+    let allocated_texture =
+        allocate_texture_for_rgba8_image(&last_history_entry.image, &texture_manager);
+
+    state
+        .processed_image_redo_stack
+        .push(ProcessedImageHistoryEntry {
+            image: current_last_processed.image,
+            image_aspect_ratio: current_last_processed.image_aspect_ratio,
+            recorded_operations: current_last_processed.recorded_operations,
+        });
+
+    state.processed_image_last = Some(ProcessedImage {
+        image: last_history_entry.image,
+        image_aspect_ratio: last_history_entry.image_aspect_ratio,
+        image_texture: allocated_texture,
+        recorded_operations: last_history_entry.recorded_operations,
+    });
+}
+
+/// Reverses the last [`undo_processed_image`], moving the current processed image (if any)
+/// back onto the history stack. Does nothing if nothing has been undone.
+pub(crate) fn redo_processed_image(state: &mut SharedState, ctx: &egui::Context) {
+    let Some(last_redo_entry) = state.processed_image_redo_stack.pop() else {
+        return;
+    };
+
+    let texture_manager = ctx.tex_manager();
+
+    // The processed image may have been reset to the source image in the meantime,
+    // in which case there is nothing to put back onto the history stack.
+    if let Some(current_last_processed) = state.processed_image_last.take() {
+        free_texture(
+            &texture_manager,
+            current_last_processed.image_texture.id,
+        );
+
+        state
+            .processed_image_history_stack
+            .push(ProcessedImageHistoryEntry {
+                image: current_last_processed.image,
+                image_aspect_ratio: current_last_processed.image_aspect_ratio,
+                recorded_operations: current_last_processed.recorded_operations,
+            });
+    }
+
+    let allocated_texture =
+        allocate_texture_for_rgba8_image(&last_redo_entry.image, &texture_manager);
+
+    state.processed_image_last = Some(ProcessedImage {
+        image: last_redo_entry.image,
+        image_aspect_ratio: last_redo_entry.image_aspect_ratio,
+        image_texture: allocated_texture,
+        recorded_operations: last_redo_entry.recorded_operations,
+    });
+}
+
+/// Replaces every empty selection mask of `selection_mode` (including those nested inside
+/// combined selection modes) with `mask`. Other selection modes are left unchanged.
+fn fill_empty_selection_masks(
+    selection_mode: &mut PreparedSegmentSelectionMode,
+    mask: &Arc<GrayImage>,
+) {
+    match selection_mode {
+        PreparedSegmentSelectionMode::MaskImage { mask: empty_mask } if empty_mask.is_empty() => {
+            *empty_mask = mask.clone();
+        }
+        PreparedSegmentSelectionMode::Combined { first, second, .. } => {
+            fill_empty_selection_masks(first, mask);
+            fill_empty_selection_masks(second, mask);
+        }
+        _ => {}
+    }
+}
+
+/// Asks the worker to apply a recorded pixel sort (e.g. from a recipe) to `image`.
+fn send_recorded_operation(
+    state: &mut SharedState,
+    worker: &WorkerHandle,
+    image: Arc<RgbaImage>,
+    operation: RecordedOperation,
+) {
+    let RecordedOperation {
+        mut config,
+        region_of_interest,
+    } = operation;
+
+    // Selection masks are not saved in recipes, so the currently loaded mask is used instead.
+    if let Some(selection_mask) = &state.selection_mask {
+        fill_empty_selection_masks(&mut config.selection_mode, &selection_mask.mask);
+    }
+
+    let _ = worker
+        .sender()
+        .send(WorkerRequest::PerformPreparedPixelSorting {
+            image,
+            segment_selection_mode: config.selection_mode,
+            segment_randomization_mode: config.randomization_mode,
+            segment_randomization_seed: config.randomization_seed,
+            minimum_segment_length: config.minimum_segment_length,
+            maximum_segment_length: config.maximum_segment_length,
+            sorting_mode: config.sorting_mode,
+            tiebreak_mode: config.tiebreak_mode,
+            sorting_direction: config.direction,
+            premultiply_alpha: config.premultiply_alpha,
+            output_only_segments: config.output_only_segments,
+            stable_sort: config.stable_sort,
+            region_of_interest,
+        });
+
+    state.is_processing_image = true;
+}
+
+/// Moves `file_path` to the front of the recent files list (adding it if necessary,
+/// in which case a thumbnail is requested from the worker), then evicts the oldest
+/// entries beyond [`MAX_RECENT_FILES`].
+fn remember_recent_file(
+    state: &mut SharedState,
+    worker: &WorkerHandle,
+    file_path: PathBuf,
+    ctx: &egui::Context,
+) {
+    let existing_entry_index = state
+        .recent_files
+        .iter()
+        .position(|recent_file| recent_file.file_path == file_path);
+
+    let recent_file = if let Some(existing_entry_index) = existing_entry_index {
+        state.recent_files.remove(existing_entry_index)
+    } else {
+        let _ = worker.sender().send(WorkerRequest::GenerateThumbnail {
+            file_path: file_path.clone(),
+        });
+
+        RecentFile {
+            file_path,
+            thumbnail: None,
+        }
+    };
+
+    state.recent_files.insert(0, recent_file);
+
+    while state.recent_files.len() > MAX_RECENT_FILES {
+        if let Some(evicted_recent_file) = state.recent_files.pop()
+            && let Some(thumbnail) = evicted_recent_file.thumbnail
+        {
+            free_texture(&ctx.tex_manager(), thumbnail.id);
+        }
+    }
+}
+
+/// Remembers the directory containing `file_path` as the one file dialogs start in.
+fn remember_last_used_directory(state: &mut SharedState, file_path: &Path) {
+    if let Some(directory) = file_path.parent()
+        && !directory.as_os_str().is_empty()
+    {
+        state.last_used_directory = Some(directory.to_path_buf());
+    }
+}
+
+/// Creates a file dialog that starts in the last used directory (if it still exists).
+fn new_file_dialog(state: &SharedState) -> rfd::FileDialog {
+    let file_dialog = rfd::FileDialog::new();
+
+    match &state.last_used_directory {
+        Some(directory) if directory.is_dir() => file_dialog.set_directory(directory),
+        _ => file_dialog,
+    }
+}
+
+/// An action triggered by a keyboard shortcut, see [`read_keyboard_shortcut_action`].
+enum KeyboardShortcutAction {
+    Undo,
+    Redo,
+    OpenFile,
+    SaveFile,
+}
+
+/// Consumes and returns the keyboard shortcut pressed this frame, if any.
+///
+/// Shortcuts are ignored while a widget (e.g. a text field or slider) has keyboard focus,
+/// so that they don't interfere with editing its value.
+fn read_keyboard_shortcut_action(ctx: &egui::Context) -> Option<KeyboardShortcutAction> {
+    if ctx.memory(|memory| memory.focused().is_some()) {
+        return None;
+    }
+
+    // Shortcuts with more modifiers must be checked first, as e.g. Ctrl+Z
+    // would otherwise also match (and consume) Ctrl+Shift+Z.
+    let shortcuts = [
+        (
+            KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z),
+            KeyboardShortcutAction::Redo,
+        ),
+        (
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::Y),
+            KeyboardShortcutAction::Redo,
+        ),
+        (
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::Z),
+            KeyboardShortcutAction::Undo,
+        ),
+        (
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::O),
+            KeyboardShortcutAction::OpenFile,
+        ),
+        (
+            KeyboardShortcut::new(Modifiers::COMMAND, Key::S),
+            KeyboardShortcutAction::SaveFile,
+        ),
+    ];
+
+    ctx.input_mut(|input| {
+        shortcuts
+            .into_iter()
+            .find(|(shortcut, _)| input.consume_shortcut(shortcut))
+            .map(|(_, action)| action)
+    })
+}
+
+/// Returns the path of the first dropped file that can be opened as a source image,
+/// or a user-facing explanation of why none of them can be.
+fn select_dropped_image_file(dropped_files: &[DroppedFile]) -> Result<PathBuf, String> {
+    let dropped_file_paths: Vec<&PathBuf> = dropped_files
+        .iter()
+        .filter_map(|dropped_file| dropped_file.path.as_ref())
+        .collect();
+
+    if dropped_file_paths.is_empty() {
+        return Err("The dropped item is not a file on disk and cannot be opened.".to_string());
+    }
+
+    dropped_file_paths
+        .into_iter()
+        .find(|file_path| {
+            file_path.extension().is_some_and(|extension| {
+                SUPPORTED_INPUT_IMAGE_EXTENSIONS
+                    .iter()
+                    .any(|supported_extension| extension.eq_ignore_ascii_case(supported_extension))
+            })
+        })
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Unsupported file type. Drop an image with one of these extensions: {}.",
+                SUPPORTED_INPUT_IMAGE_EXTENSIONS.join(", ")
+            )
+        })
+}
+
 pub struct VulcanGui {
     state: SharedState,
 
@@ -163,9 +542,29 @@ pub struct VulcanGui {
 }
 
 impl VulcanGui {
-    pub fn new(worker: WorkerHandle) -> Self {
+    pub fn new(worker: WorkerHandle, storage: Option<&dyn eframe::Storage>) -> Self {
+        let mut state = SharedState::new();
+
+        let persisted_recent_files: Vec<PathBuf> = storage
+            .and_then(|storage| eframe::get_value(storage, RECENT_FILES_STORAGE_KEY))
+            .unwrap_or_default();
+
+        for file_path in persisted_recent_files.into_iter().take(MAX_RECENT_FILES) {
+            let _ = worker.sender().send(WorkerRequest::GenerateThumbnail {
+                file_path: file_path.clone(),
+            });
+
+            state.recent_files.push(RecentFile {
+                file_path,
+                thumbnail: None,
+            });
+        }
+
+        state.last_used_directory =
+            storage.and_then(|storage| eframe::get_value(storage, LAST_USED_DIRECTORY_STORAGE_KEY));
+
         Self {
-            state: SharedState::new(),
+            state,
             worker,
             central_view: CentralView::new(),
             right_sidebar: RightSidebar::new(),
@@ -181,6 +580,29 @@ impl VulcanGui {
 }
 
 impl App for VulcanGui {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let recent_file_paths: Vec<&PathBuf> = self
+            .state
+            .recent_files
+            .iter()
+            .map(|recent_file| &recent_file.file_path)
+            .collect();
+
+        eframe::set_value(
+            storage,
+            RECENT_FILES_STORAGE_KEY,
+            &recent_file_paths,
+        );
+
+        if let Some(last_used_directory) = &self.state.last_used_directory {
+            eframe::set_value(
+                storage,
+                LAST_USED_DIRECTORY_STORAGE_KEY,
+                last_used_directory,
+            );
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         let mut toasts = egui_toast::Toasts::new()
             .anchor(Align2::LEFT_TOP, Pos2::new(10.0, 10.0))
@@ -189,7 +611,12 @@ impl App for VulcanGui {
         let worker_receiver = self.worker.receiver();
         while let Ok(response) = worker_receiver.try_recv() {
             match response {
-                WorkerResponse::OpenedSourceImage { image, file_path } => {
+                WorkerResponse::OpenedSourceImage {
+                    image,
+                    file_path,
+                    icc_profile,
+                    file_info,
+                } => {
                     if let Some(previous_source_image) = self.state.source_image.take() {
                         let texture_manager = ctx.tex_manager();
                         let mut locked_texture_manager = texture_manager.write();
@@ -202,14 +629,26 @@ impl App for VulcanGui {
 
                     let image_aspect_ratio = image.width() as f32 / image.height() as f32;
 
+                    remember_recent_file(
+                        &mut self.state,
+                        &self.worker,
+                        file_path.clone(),
+                        ctx,
+                    );
+
                     self.state.source_image = Some(SourceImage {
                         file_path,
                         image: Arc::new(image),
+                        icc_profile: icc_profile.map(Arc::new),
+                        file_info,
                         image_aspect_ratio,
                         image_texture,
+                        proxy: None,
                     });
 
                     self.state.processed_image_history_stack.clear();
+                    self.state.processed_image_redo_stack.clear();
+                    self.state.pending_recipe_operations.clear();
 
                     if let Some(previous_processed_image) = self.state.processed_image_last.take() {
                         let texture_manager = ctx.tex_manager();
@@ -246,32 +685,144 @@ impl App for VulcanGui {
 
                     self.state.is_loading_image = false;
                 }
-                WorkerResponse::ProcessedImage { image } => {
+                WorkerResponse::OpenedSelectionMask { file_path, mask } => {
+                    self.state.selection_mask = Some(SelectionMask {
+                        file_path,
+                        mask: Arc::new(mask),
+                    });
+
+                    self.state.is_loading_selection_mask = false;
+                }
+                WorkerResponse::FailedToOpenSelectionMask { error } => {
+                    let error_text = match error {
+                        ImageLoadError::FileReadError { error } => {
+                            format!("Failed to read mask file.\n\nContext: {error}")
+                        }
+                        ImageLoadError::ImageParseError { error } => {
+                            format!(
+                                "Failed to parse mask file. Maybe not in a valid format?\n\nContext: {error}"
+                            )
+                        }
+                    };
+
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text(error_text)
+                            .kind(egui_toast::ToastKind::Error)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration(None)
+                                    .show_progress(false)
+                                    .show_icon(true),
+                            ),
+                    );
+
+                    self.state.is_loading_selection_mask = false;
+                }
+                WorkerResponse::ProcessedImage { image, operation } => {
+                    self.state.processed_image_redo_stack.clear();
+
+                    // Images are processed from the last processed image if there is one,
+                    // and from the source image otherwise (i.e. without prior operations).
+                    let mut recorded_operations = Vec::new();
+
                     if let Some(previous_processed_image) = self.state.processed_image_last.take() {
                         let texture_manager = ctx.tex_manager();
                         let mut locked_texture_manager = texture_manager.write();
 
                         locked_texture_manager.free(previous_processed_image.image_texture.id);
 
+                        recorded_operations = previous_processed_image.recorded_operations.clone();
+
                         self.state
                             .processed_image_history_stack
                             .push(ProcessedImageHistoryEntry {
                                 image: previous_processed_image.image,
                                 image_aspect_ratio: previous_processed_image.image_aspect_ratio,
+                                recorded_operations: previous_processed_image.recorded_operations,
                             });
                     }
 
+                    recorded_operations.extend(operation);
+
                     let image_texture =
                         allocate_texture_for_rgba8_image(&image, &ctx.tex_manager());
 
                     let image_aspect_ratio = image.width() as f32 / image.height() as f32;
 
+                    let image = Arc::new(image);
+
                     self.state.processed_image_last = Some(ProcessedImage {
-                        image: Arc::new(image),
+                        image: image.clone(),
                         image_aspect_ratio,
                         image_texture,
+                        recorded_operations,
                     });
 
+                    self.state.is_processing_image = false;
+                    self.state.processing_progress = None;
+
+                    if let Some(next_operation) = self.state.pending_recipe_operations.pop_front() {
+                        send_recorded_operation(
+                            &mut self.state,
+                            &self.worker,
+                            image,
+                            next_operation,
+                        );
+                    }
+                }
+                WorkerResponse::ProcessingProgress { fraction } => {
+                    if self.state.is_processing_image {
+                        self.state.processing_progress = Some(fraction);
+                    }
+                }
+                WorkerResponse::CancelledProcessing => {
+                    // The previous processed image (if any) is left as it was.
+                    self.state.is_processing_image = false;
+                    self.state.processing_progress = None;
+                    self.state.pending_recipe_operations.clear();
+                }
+                WorkerResponse::FailedToSortImage { error } => {
+                    let error_text = match error {
+                        PixelSortError::InvalidSelectionMode { error } => {
+                            format!("Failed to select segments to sort.\n\nContext: {error}")
+                        }
+                        PixelSortError::ZeroMaximumSegmentLength => {
+                            format!("Failed to sort image.\n\nContext: {error}")
+                        }
+                    };
+
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text(error_text)
+                            .kind(egui_toast::ToastKind::Error)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration(None)
+                                    .show_progress(false)
+                                    .show_icon(true),
+                            ),
+                    );
+
+                    self.state.is_processing_image = false;
+                    self.state.processing_progress = None;
+                    self.state.pending_recipe_operations.clear();
+                }
+                WorkerResponse::FailedToAdjustImage { error } => {
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text(format!(
+                                "Failed to adjust image.\n\nContext: {error}"
+                            ))
+                            .kind(egui_toast::ToastKind::Error)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration(None)
+                                    .show_progress(false)
+                                    .show_icon(true),
+                            ),
+                    );
+
                     self.state.is_processing_image = false;
                 }
                 WorkerResponse::ProcessedThresholdPreview {
@@ -289,6 +840,9 @@ impl App for VulcanGui {
                                 previous_preview.image_texture.id,
                             );
 
+                            // Live sort previews are downscaled, so the size can change.
+                            previous_preview.image_texture.size =
+                                egui::vec2(image.width() as f32, image.height() as f32);
                             previous_preview.image_aspect_ratio =
                                 (image.width() as f32) / (image.height() as f32);
                             previous_preview.last_redraw = requested_at;
@@ -305,6 +859,57 @@ impl App for VulcanGui {
                         }
                     }
                 }
+                WorkerResponse::GeneratedProxyImage {
+                    source_image,
+                    max_size,
+                    proxy_image,
+                } => {
+                    // A different source image might have been opened in the meantime.
+                    if let Some(current_source_image) = self.state.source_image.as_mut()
+                        && Arc::ptr_eq(&current_source_image.image, &source_image)
+                    {
+                        current_source_image.proxy = Some(ProxyImage {
+                            image: Arc::new(proxy_image),
+                            max_size,
+                        });
+                    }
+
+                    self.state.is_generating_proxy_image = false;
+                }
+                WorkerResponse::GeneratedThumbnail {
+                    file_path,
+                    thumbnail,
+                } => {
+                    // The entry might have been evicted in the meantime.
+                    if let Some(recent_file) = self
+                        .state
+                        .recent_files
+                        .iter_mut()
+                        .find(|recent_file| recent_file.file_path == file_path)
+                    {
+                        let texture_manager = ctx.tex_manager();
+
+                        if let Some(previous_thumbnail) = recent_file.thumbnail.take() {
+                            free_texture(&texture_manager, previous_thumbnail.id);
+                        }
+
+                        recent_file.thumbnail = Some(allocate_texture_for_rgba8_image(
+                            &thumbnail,
+                            &texture_manager,
+                        ));
+                    }
+                }
+                WorkerResponse::FailedToGenerateThumbnail { file_path, error } => {
+                    // Most likely the file has been moved or deleted since, so we forget it.
+                    tracing::warn!(
+                        "Failed to generate thumbnail for recent file {}, forgetting it: {error}",
+                        file_path.display()
+                    );
+
+                    self.state
+                        .recent_files
+                        .retain(|recent_file| recent_file.file_path != file_path);
+                }
                 WorkerResponse::SavedImage { output_file_path } => {
                     toasts.add(
                         egui_toast::Toast::default()
@@ -325,6 +930,25 @@ impl App for VulcanGui {
                 }
                 WorkerResponse::FailedToSaveImage { error } => {
                     let error_text = match error {
+                        ImageSaveError::PermissionDenied { error } => {
+                            format!(
+                                "Not allowed to write to the chosen location. \
+                                Try saving into a folder you own, e.g. your Pictures folder.\n\n\
+                                Context: {error}"
+                            )
+                        }
+                        ImageSaveError::ParentDirectoryMissing { error } => {
+                            format!(
+                                "The chosen folder no longer exists. \
+                                Pick an existing folder and try again.\n\nContext: {error}"
+                            )
+                        }
+                        ImageSaveError::AlreadyExists { error } => {
+                            format!(
+                                "A file with this name already exists. \
+                                Pick a different name and try again.\n\nContext: {error}"
+                            )
+                        }
                         ImageSaveError::FileOpenError { error } => {
                             format!("Failed to open output file.\n\nContext: {error}")
                         }
@@ -350,7 +974,104 @@ impl App for VulcanGui {
 
                     self.state.is_saving_image = false;
                 }
+                WorkerResponse::CopiedImageToClipboard => {
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text("Image successfully copied to the clipboard.")
+                            .kind(egui_toast::ToastKind::Success)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration_in_seconds(5.0)
+                                    .show_progress(true)
+                                    .show_icon(true),
+                            ),
+                    );
+
+                    self.state.is_copying_image = false;
+                }
+                WorkerResponse::FailedToCopyImageToClipboard { error } => {
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text(format!(
+                                "Failed to copy image to the clipboard.\n\nContext: {error}"
+                            ))
+                            .kind(egui_toast::ToastKind::Error)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration(None)
+                                    .show_progress(false)
+                                    .show_icon(true),
+                            ),
+                    );
+
+                    self.state.is_copying_image = false;
+                }
+                WorkerResponse::OperationPanicked { message } => {
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text(format!(
+                                "An operation failed unexpectedly (this is a bug).\n\n\
+                                Context: {message}"
+                            ))
+                            .kind(egui_toast::ToastKind::Error)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration(None)
+                                    .show_progress(false)
+                                    .show_icon(true),
+                            ),
+                    );
+
+                    // The response to the panicked request will never arrive,
+                    // so nothing should keep waiting for it.
+                    self.state.is_loading_image = false;
+                    self.state.is_loading_selection_mask = false;
+                    self.state.is_generating_proxy_image = false;
+                    self.state.is_processing_image = false;
+                    self.state.processing_progress = None;
+                    self.state.pending_recipe_operations.clear();
+                    self.state.is_waiting_for_updated_preview = false;
+                    self.state.is_saving_image = false;
+                    self.state.is_copying_image = false;
+                }
+            }
+        }
+
+        let dropped_files = ctx.input(|input| input.raw.dropped_files.clone());
+        if !dropped_files.is_empty() {
+            match select_dropped_image_file(&dropped_files) {
+                Ok(file_path) => {
+                    self.right_sidebar
+                        .open_file(&self.worker, &mut self.state, file_path);
+                }
+                Err(error_text) => {
+                    toasts.add(
+                        egui_toast::Toast::default()
+                            .text(error_text)
+                            .kind(egui_toast::ToastKind::Error)
+                            .options(
+                                egui_toast::ToastOptions::default()
+                                    .duration(None)
+                                    .show_progress(false)
+                                    .show_icon(true),
+                            ),
+                    );
+                }
+            }
+        }
+
+        match read_keyboard_shortcut_action(ctx) {
+            Some(KeyboardShortcutAction::Undo) => undo_processed_image(&mut self.state, ctx),
+            Some(KeyboardShortcutAction::Redo) => redo_processed_image(&mut self.state, ctx),
+            Some(KeyboardShortcutAction::OpenFile) => {
+                self.right_sidebar
+                    .open_file_dialog(&self.worker, &mut self.state);
+            }
+            Some(KeyboardShortcutAction::SaveFile) => {
+                self.right_sidebar
+                    .save_file_dialog(&self.worker, &mut self.state);
             }
+            None => {}
         }
 
         CentralPanel::default().show(ctx, |ui| {
@@ -1,12 +1,41 @@
+use egui::{Color32, Mesh, Pos2, Rect, Sense, Shape, Stroke, StrokeKind, Vec2, load::SizedTexture};
 use egui_taffy::{Tui, TuiBuilderLogic, taffy};
 
-use crate::{gui::SharedState, utilities::select_first_some_3};
+use crate::{
+    gui::SharedState,
+    utilities::{select_first_some, select_first_some_3},
+};
 
-pub struct CentralView {}
+pub struct CentralView {
+    /// Whether to show the source image next to the processed image (or threshold preview).
+    compare_with_source: bool,
+
+    /// Whether dragging over the image draws the sorting region (see
+    /// [`SharedState::sorting_region`]).
+    is_drawing_sorting_region: bool,
+
+    /// Where the current sorting region drag started (normalized, like the region itself).
+    sorting_region_drag_start: Option<Pos2>,
+}
+
+/// The color of the sorting region outline drawn over the image.
+const SORTING_REGION_STROKE_COLOR: Color32 = Color32::from_rgb(255, 200, 0);
+
+/// The size (in points) of a single square of the checkerboard drawn behind the image,
+/// which makes its transparent parts visible.
+const CHECKERBOARD_SQUARE_SIZE: f32 = 8.0;
+
+/// The colors of the light and dark squares of the checkerboard drawn behind the image.
+const CHECKERBOARD_LIGHT_COLOR: Color32 = Color32::from_gray(204);
+const CHECKERBOARD_DARK_COLOR: Color32 = Color32::from_gray(153);
 
 impl CentralView {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            compare_with_source: false,
+            is_drawing_sorting_region: false,
+            sorting_region_drag_start: None,
+        }
     }
 
     #[allow(clippy::manual_map)]
@@ -26,41 +55,133 @@ impl CentralView {
                 ..Default::default()
             })
             .add(|taffy_ui| {
-                let image_context = select_first_some_3(
-                    state
-                        .threshold_preview
-                        .as_ref()
-                        .map(|preview| (preview.image_texture, preview.image_aspect_ratio)),
-                    state
-                        .processed_image_last
-                        .as_ref()
-                        .map(|last| (last.image_texture, last.image_aspect_ratio)),
-                    state
-                        .source_image
-                        .as_ref()
-                        .map(|source| (source.image_texture, source.image_aspect_ratio)),
-                );
+                let threshold_preview_context = state
+                    .threshold_preview
+                    .as_ref()
+                    .map(|preview| (preview.image_texture, preview.image_aspect_ratio));
+                let processed_image_context = state
+                    .processed_image_last
+                    .as_ref()
+                    .map(|last| (last.image_texture, last.image_aspect_ratio));
+                let source_image_context = state
+                    .source_image
+                    .as_ref()
+                    .map(|source| (source.image_texture, source.image_aspect_ratio));
 
-                if let Some((sized_texture, aspect_ratio)) = image_context {
+                let compared_image_context =
+                    select_first_some(threshold_preview_context, processed_image_context);
+
+                taffy_ui
+                    .style(taffy::Style {
+                        margin: taffy::Rect {
+                            left: taffy::LengthPercentageAuto::Length(0.0),
+                            right: taffy::LengthPercentageAuto::Length(0.0),
+                            top: taffy::LengthPercentageAuto::Length(0.0),
+                            bottom: taffy::LengthPercentageAuto::Length(6.0),
+                        },
+                        ..Default::default()
+                    })
+                    .ui(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.add_enabled(
+                                source_image_context.is_some() && compared_image_context.is_some(),
+                                egui::Checkbox::new(
+                                    &mut self.compare_with_source,
+                                    "Compare with source",
+                                ),
+                            )
+                            .on_hover_text(
+                                "Shows the source image on the left and the processed image \
+                                (or threshold preview) on the right.",
+                            )
+                            .on_disabled_hover_text("Nothing to compare: no processed image yet.");
+
+                            ui.add_enabled(
+                                source_image_context.is_some(),
+                                egui::Checkbox::new(
+                                    &mut self.is_drawing_sorting_region,
+                                    "Draw sorting region",
+                                ),
+                            )
+                            .on_hover_text(
+                                "Drag over the image to restrict pixel sorting to a rectangle. \
+                                Pixels outside of it are left unchanged.",
+                            );
+
+                            if ui
+                                .add_enabled(
+                                    state.sorting_region.is_some(),
+                                    egui::Button::new("Clear region"),
+                                )
+                                .clicked()
+                            {
+                                state.sorting_region = None;
+                            }
+                        })
+                    });
+
+                if self.compare_with_source
+                    && let Some(source_image_context) = source_image_context
+                    && let Some(compared_image_context) = compared_image_context
+                {
                     taffy_ui
                         .style(taffy::Style {
-                            flex_grow: 1.0,
-                            flex_basis: taffy::Dimension::Percent(1.0),
+                            display: taffy::Display::Flex,
+                            flex_direction: taffy::FlexDirection::Row,
+                            align_items: Some(taffy::AlignItems::Start),
+                            gap: taffy::Size {
+                                width: taffy::LengthPercentage::Length(8.0),
+                                height: taffy::LengthPercentage::Length(0.0),
+                            },
                             size: taffy::Size {
                                 width: taffy::Dimension::Percent(1.0),
                                 height: taffy::Dimension::Auto,
                             },
-                            aspect_ratio: Some(aspect_ratio),
                             ..Default::default()
                         })
-                        .ui(|ui| {
-                            let available_size = ui.available_size();
+                        .add(|taffy_ui| {
+                            for (sized_texture, aspect_ratio) in
+                                [source_image_context, compared_image_context]
+                            {
+                                taffy_ui
+                                    .style(taffy::Style {
+                                        display: taffy::Display::Flex,
+                                        flex_direction: taffy::FlexDirection::Column,
+                                        flex_grow: 1.0,
+                                        flex_basis: taffy::Dimension::Length(0.0),
+                                        ..Default::default()
+                                    })
+                                    .add(|taffy_ui| {
+                                        add_image_view(
+                                            taffy_ui,
+                                            sized_texture,
+                                            aspect_ratio,
+                                            self.is_drawing_sorting_region,
+                                            &mut self.sorting_region_drag_start,
+                                            &mut state.sorting_region,
+                                        );
+                                    });
+                            }
+                        });
 
-                            let image_widget =
-                                egui::Image::from_texture(sized_texture).max_size(available_size);
+                    return;
+                }
 
-                            ui.add_sized(available_size, image_widget)
-                        });
+                let image_context = select_first_some_3(
+                    threshold_preview_context,
+                    processed_image_context,
+                    source_image_context,
+                );
+
+                if let Some((sized_texture, aspect_ratio)) = image_context {
+                    add_image_view(
+                        taffy_ui,
+                        sized_texture,
+                        aspect_ratio,
+                        self.is_drawing_sorting_region,
+                        &mut self.sorting_region_drag_start,
+                        &mut state.sorting_region,
+                    );
                 } else {
                     taffy_ui.add_empty();
                 }
@@ -69,3 +190,124 @@ impl CentralView {
             });
     }
 }
+
+/// Adds an image that fills the available width while keeping its aspect ratio,
+/// on top of a checkerboard (see [`build_checkerboard_shape`]) and with the sorting region
+/// (if any) outlined on top of it.
+///
+/// If `is_drawing_sorting_region` is set, dragging over the image replaces the sorting region.
+fn add_image_view(
+    taffy_ui: &mut Tui,
+    sized_texture: SizedTexture,
+    aspect_ratio: f32,
+    is_drawing_sorting_region: bool,
+    sorting_region_drag_start: &mut Option<Pos2>,
+    sorting_region: &mut Option<Rect>,
+) {
+    taffy_ui
+        .style(taffy::Style {
+            flex_grow: 1.0,
+            flex_basis: taffy::Dimension::Percent(1.0),
+            size: taffy::Size {
+                width: taffy::Dimension::Percent(1.0),
+                height: taffy::Dimension::Auto,
+            },
+            aspect_ratio: Some(aspect_ratio),
+            ..Default::default()
+        })
+        .ui(|ui| {
+            let available_size = ui.available_size();
+
+            let image_widget = egui::Image::from_texture(sized_texture)
+                .max_size(available_size)
+                .sense(
+                    if is_drawing_sorting_region {
+                        Sense::drag()
+                    } else {
+                        Sense::hover()
+                    },
+                );
+
+            // The image rect is only known once the image has been added,
+            // so we reserve a place for the checkerboard behind it.
+            let checkerboard_shape_index = ui.painter().add(Shape::Noop);
+
+            let image_response = ui.add_sized(available_size, image_widget);
+            let image_rect = image_response.rect;
+
+            ui.painter().set(
+                checkerboard_shape_index,
+                build_checkerboard_shape(image_rect),
+            );
+
+            let to_normalized_position = |position: Pos2| {
+                ((position - image_rect.min) / image_rect.size())
+                    .to_pos2()
+                    .clamp(Pos2::ZERO, Pos2::new(1.0, 1.0))
+            };
+
+            if is_drawing_sorting_region {
+                if image_response.drag_started() {
+                    *sorting_region_drag_start = image_response
+                        .interact_pointer_pos()
+                        .map(to_normalized_position);
+                }
+
+                if image_response.dragged()
+                    && let Some(drag_start) = *sorting_region_drag_start
+                    && let Some(pointer_position) = image_response.interact_pointer_pos()
+                {
+                    *sorting_region = Some(Rect::from_two_pos(
+                        drag_start,
+                        to_normalized_position(pointer_position),
+                    ));
+                }
+
+                if image_response.drag_stopped() {
+                    *sorting_region_drag_start = None;
+                }
+            }
+
+            if let Some(sorting_region) = *sorting_region {
+                let region_on_screen = Rect::from_min_max(
+                    image_rect.lerp_inside(sorting_region.min.to_vec2()),
+                    image_rect.lerp_inside(sorting_region.max.to_vec2()),
+                );
+
+                ui.painter().rect_stroke(
+                    region_on_screen,
+                    0.0,
+                    Stroke::new(2.0, SORTING_REGION_STROKE_COLOR),
+                    StrokeKind::Middle,
+                );
+            }
+
+            image_response
+        });
+}
+
+/// Builds a gray checkerboard that fills the given `rect`, drawn behind images
+/// so that their transparent parts can be told apart from the panel background.
+fn build_checkerboard_shape(rect: Rect) -> Shape {
+    let mut mesh = Mesh::default();
+    mesh.add_colored_rect(rect, CHECKERBOARD_LIGHT_COLOR);
+
+    let number_of_columns = (rect.width() / CHECKERBOARD_SQUARE_SIZE).ceil() as usize;
+    let number_of_rows = (rect.height() / CHECKERBOARD_SQUARE_SIZE).ceil() as usize;
+
+    // Only the dark squares are added on top of the light background,
+    // clipped to the `rect` on the right and bottom edges.
+    for row_index in 0..number_of_rows {
+        for column_index in ((row_index % 2)..number_of_columns).step_by(2) {
+            let square_min = rect.min
+                + Vec2::new(column_index as f32, row_index as f32) * CHECKERBOARD_SQUARE_SIZE;
+
+            let square = Rect::from_min_size(square_min, Vec2::splat(CHECKERBOARD_SQUARE_SIZE))
+                .intersect(rect);
+
+            mesh.add_colored_rect(square, CHECKERBOARD_DARK_COLOR);
+        }
+    }
+
+    Shape::mesh(mesh)
+}
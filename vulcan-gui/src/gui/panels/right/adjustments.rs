@@ -0,0 +1,531 @@
+use std::sync::Arc;
+
+use egui_taffy::{Tui, TuiBuilderLogic, taffy};
+use image::RgbaImage;
+use vulcan_core::adjustments::{Axis, GeometricOperation};
+
+use crate::{
+    gui::{SharedState, panels::ConditionalDisabledTuiBuilder},
+    worker::{WorkerHandle, WorkerRequest},
+};
+
+
+const DEFAULT_POSTERIZATION_LEVELS_PER_CHANNEL: u8 = 4;
+
+/// The brightness and contrast that leave the image unchanged.
+const NEUTRAL_BRIGHTNESS: i32 = 0;
+const NEUTRAL_CONTRAST: f32 = 1.0;
+
+const CHANNEL_NAMES: [&str; 4] = ["R", "G", "B", "A"];
+
+
+#[rustfmt::skip]
+fn mirror_axis_label(axis: Axis) -> &'static str {
+    match axis {
+        Axis::Horizontal => "horizontal (double width)",
+        Axis::Vertical => "vertical (double height)",
+    }
+}
+
+
+const GEOMETRIC_OPERATIONS: [GeometricOperation; 4] = [
+    GeometricOperation::FlipHorizontal,
+    GeometricOperation::FlipVertical,
+    GeometricOperation::RotateCounterClockwise,
+    GeometricOperation::RotateClockwise,
+];
+
+fn geometric_operation_icon(operation: GeometricOperation) -> &'static str {
+    match operation {
+        GeometricOperation::FlipHorizontal => egui_phosphor::regular::FLIP_HORIZONTAL,
+        GeometricOperation::FlipVertical => egui_phosphor::regular::FLIP_VERTICAL,
+        GeometricOperation::RotateClockwise => egui_phosphor::regular::ARROW_CLOCKWISE,
+        GeometricOperation::RotateCounterClockwise => {
+            egui_phosphor::regular::ARROW_COUNTER_CLOCKWISE
+        }
+    }
+}
+
+#[rustfmt::skip]
+fn geometric_operation_label(operation: GeometricOperation) -> &'static str {
+    match operation {
+        GeometricOperation::FlipHorizontal => "Flip horizontally",
+        GeometricOperation::FlipVertical => "Flip vertically",
+        GeometricOperation::RotateClockwise => "Rotate 90° clockwise",
+        GeometricOperation::RotateCounterClockwise => "Rotate 90° counter-clockwise",
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UiChannelSwapPreset {
+    RgbaToBgra,
+    RgbaToGrba,
+    Custom,
+}
+
+impl UiChannelSwapPreset {
+    pub fn presets() -> [Self; 3] {
+        [Self::RgbaToBgra, Self::RgbaToGrba, Self::Custom]
+    }
+
+    #[rustfmt::skip]
+    pub fn label(&self) -> &'static str {
+        match self {
+            UiChannelSwapPreset::RgbaToBgra => "RGBA → BGRA",
+            UiChannelSwapPreset::RgbaToGrba => "RGBA → GRBA",
+            UiChannelSwapPreset::Custom => "custom",
+        }
+    }
+
+    /// Returns the channel mapping of this preset, or `None` for [`Self::Custom`].
+    pub fn mapping(&self) -> Option<[usize; 4]> {
+        match self {
+            UiChannelSwapPreset::RgbaToBgra => Some([2, 1, 0, 3]),
+            UiChannelSwapPreset::RgbaToGrba => Some([1, 0, 2, 3]),
+            UiChannelSwapPreset::Custom => None,
+        }
+    }
+}
+
+
+/// Returns the image adjustments should be applied to: the last processed image if there is one,
+/// otherwise the source image.
+#[allow(clippy::manual_map)]
+fn select_image_to_adjust(state: &SharedState) -> Option<Arc<RgbaImage>> {
+    if let Some(processed_image_state) = &state.processed_image_last {
+        Some(processed_image_state.image.clone())
+    } else if let Some(source_image_state) = &state.source_image {
+        Some(source_image_state.image.clone())
+    } else {
+        None
+    }
+}
+
+
+fn adjustment_button_style() -> taffy::Style {
+    taffy::Style {
+        min_size: taffy::Size {
+            width: taffy::Dimension::Length(150.0),
+            height: taffy::Dimension::Length(24.0),
+        },
+        max_size: taffy::Size {
+            width: taffy::Dimension::Auto,
+            height: taffy::Dimension::Length(32.0),
+        },
+        margin: taffy::Rect {
+            left: taffy::LengthPercentageAuto::Length(0.0),
+            right: taffy::LengthPercentageAuto::Length(0.0),
+            top: taffy::LengthPercentageAuto::Length(4.0),
+            bottom: taffy::LengthPercentageAuto::Length(2.0),
+        },
+        ..Default::default()
+    }
+}
+
+
+pub struct ImageAdjustmentsSection {
+    posterization_levels_per_channel: u8,
+    brightness: i32,
+    contrast: f32,
+    mirror_tile_axis: Axis,
+    channel_swap_preset: UiChannelSwapPreset,
+    custom_channel_mapping: [usize; 4],
+}
+
+impl ImageAdjustmentsSection {
+    pub fn new() -> Self {
+        Self {
+            posterization_levels_per_channel: DEFAULT_POSTERIZATION_LEVELS_PER_CHANNEL,
+            brightness: NEUTRAL_BRIGHTNESS,
+            contrast: NEUTRAL_CONTRAST,
+            mirror_tile_axis: Axis::Horizontal,
+            channel_swap_preset: UiChannelSwapPreset::RgbaToBgra,
+            custom_channel_mapping: [0, 1, 2, 3],
+        }
+    }
+
+    fn update_posterization_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(4.0),
+                    bottom: taffy::LengthPercentageAuto::Length(4.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.posterization_levels_per_channel, 1..=64)
+                        .text("Levels per channel"),
+                )
+                .on_hover_text(
+                    "Number of distinct values each color channel is quantized to. \
+                    Posterizing before sorting creates banded segments.",
+                )
+            });
+
+        let posterize_button = taffy_ui
+            .style(adjustment_button_style())
+            .disabled_if(state.source_image.is_none() || state.is_processing_image)
+            .ui_add(egui::Button::new("Apply posterization"));
+
+        if posterize_button.clicked()
+            && let Some(image_to_adjust) = select_image_to_adjust(state)
+        {
+            let _ = worker.sender().send(WorkerRequest::ApplyPosterization {
+                image: image_to_adjust,
+                levels_per_channel: self.posterization_levels_per_channel,
+            });
+
+            state.is_processing_image = true;
+        }
+    }
+
+    fn update_brightness_contrast_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(14.0),
+                    bottom: taffy::LengthPercentageAuto::Length(4.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.add(egui::Slider::new(&mut self.brightness, -255..=255).text("Brightness"));
+                ui.add(
+                    egui::Slider::new(&mut self.contrast, 0.0..=3.0)
+                        .step_by(0.01)
+                        .text("Contrast"),
+                )
+                .on_hover_text(
+                    "Raising the contrast before sorting spreads out luminances, \
+                    which changes which pixels fall into a luminance range.",
+                );
+
+                if ui.button("Reset").clicked() {
+                    self.brightness = NEUTRAL_BRIGHTNESS;
+                    self.contrast = NEUTRAL_CONTRAST;
+                }
+            });
+
+        let brightness_contrast_button = taffy_ui
+            .style(adjustment_button_style())
+            .disabled_if(state.source_image.is_none() || state.is_processing_image)
+            .ui_add(egui::Button::new("Apply brightness/contrast"));
+
+        if brightness_contrast_button.clicked()
+            && let Some(image_to_adjust) = select_image_to_adjust(state)
+        {
+            let _ = worker
+                .sender()
+                .send(WorkerRequest::AdjustBrightnessContrast {
+                    image: image_to_adjust,
+                    brightness: self.brightness,
+                    contrast: self.contrast,
+                });
+
+            state.is_processing_image = true;
+        }
+    }
+
+    fn update_mirror_tile_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(14.0),
+                    bottom: taffy::LengthPercentageAuto::Length(4.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                egui::ComboBox::from_label("Mirror tile axis")
+                    .selected_text(mirror_axis_label(self.mirror_tile_axis))
+                    .show_ui(ui, |ui| {
+                        for axis in [Axis::Horizontal, Axis::Vertical] {
+                            ui.selectable_value(
+                                &mut self.mirror_tile_axis,
+                                axis,
+                                mirror_axis_label(axis),
+                            );
+                        }
+                    })
+            });
+
+        let mirror_tile_button = taffy_ui
+            .style(adjustment_button_style())
+            .disabled_if(state.source_image.is_none() || state.is_processing_image)
+            .ui_add(egui::Button::new("Mirror tile"))
+            .on_hover_text(
+                "Appends a mirrored copy of the image, producing a seamlessly tileable strip.",
+            );
+
+        if mirror_tile_button.clicked()
+            && let Some(image_to_adjust) = select_image_to_adjust(state)
+        {
+            let _ = worker.sender().send(WorkerRequest::ApplyMirrorTile {
+                image: image_to_adjust,
+                axis: self.mirror_tile_axis,
+            });
+
+            state.is_processing_image = true;
+        }
+    }
+
+    fn update_geometric_operations_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        let clicked_operation = taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(14.0),
+                    bottom: taffy::LengthPercentageAuto::Length(4.0),
+                },
+                ..Default::default()
+            })
+            .disabled_if(state.source_image.is_none() || state.is_processing_image)
+            .ui(|ui| {
+                ui.horizontal(|ui| {
+                    let mut clicked_operation = None;
+
+                    for operation in GEOMETRIC_OPERATIONS {
+                        let operation_button = ui
+                            .button(
+                                egui::RichText::new(geometric_operation_icon(operation)).size(16.0),
+                            )
+                            .on_hover_text(geometric_operation_label(operation));
+
+                        if operation_button.clicked() {
+                            clicked_operation = Some(operation);
+                        }
+                    }
+
+                    ui.label("Flip / rotate");
+
+                    clicked_operation
+                })
+                .inner
+            });
+
+        if let Some(operation) = clicked_operation
+            && let Some(image_to_adjust) = select_image_to_adjust(state)
+        {
+            let _ = worker.sender().send(WorkerRequest::ApplyGeometricOp {
+                image: image_to_adjust,
+                op: operation,
+            });
+
+            state.is_processing_image = true;
+        }
+    }
+
+    fn update_channel_swap_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(14.0),
+                    bottom: taffy::LengthPercentageAuto::Length(4.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                egui::ComboBox::from_label("Channel swap")
+                    .selected_text(self.channel_swap_preset.label())
+                    .show_ui(ui, |ui| {
+                        for preset in UiChannelSwapPreset::presets() {
+                            ui.selectable_value(
+                                &mut self.channel_swap_preset,
+                                preset,
+                                preset.label(),
+                            );
+                        }
+                    })
+            });
+
+        if self.channel_swap_preset == UiChannelSwapPreset::Custom {
+            taffy_ui
+                .style(taffy::Style {
+                    margin: taffy::Rect {
+                        left: taffy::LengthPercentageAuto::Length(0.0),
+                        right: taffy::LengthPercentageAuto::Length(0.0),
+                        top: taffy::LengthPercentageAuto::Length(4.0),
+                        bottom: taffy::LengthPercentageAuto::Length(4.0),
+                    },
+                    ..Default::default()
+                })
+                .ui(|ui| {
+                    ui.horizontal(|ui| {
+                        for (output_channel_name, source_channel_index) in CHANNEL_NAMES
+                            .iter()
+                            .zip(self.custom_channel_mapping.iter_mut())
+                        {
+                            egui::ComboBox::from_id_salt(format!(
+                                "custom-channel-mapping-{output_channel_name}"
+                            ))
+                            .width(40.0)
+                            .selected_text(CHANNEL_NAMES[*source_channel_index])
+                            .show_ui(ui, |ui| {
+                                for (channel_index, channel_name) in
+                                    CHANNEL_NAMES.iter().enumerate()
+                                {
+                                    ui.selectable_value(
+                                        source_channel_index,
+                                        channel_index,
+                                        *channel_name,
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(format!(
+                                "Source channel for the output {output_channel_name} channel."
+                            ));
+                        }
+                    })
+                });
+        }
+
+        let channel_swap_button = taffy_ui
+            .style(adjustment_button_style())
+            .disabled_if(state.source_image.is_none() || state.is_processing_image)
+            .ui_add(egui::Button::new("Swap channels"));
+
+        if channel_swap_button.clicked()
+            && let Some(image_to_adjust) = select_image_to_adjust(state)
+        {
+            let mapping = self
+                .channel_swap_preset
+                .mapping()
+                .unwrap_or(self.custom_channel_mapping);
+
+            let _ = worker.sender().send(WorkerRequest::ApplyChannelSwap {
+                image: image_to_adjust,
+                mapping,
+            });
+
+            state.is_processing_image = true;
+        }
+    }
+
+    fn update_invert_colors_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        let invert_colors_button = taffy_ui
+            .style(adjustment_button_style())
+            .disabled_if(state.source_image.is_none() || state.is_processing_image)
+            .ui_add(egui::Button::new("Invert colors"))
+            .on_hover_text("Inverts the color channels of the image, leaving alpha untouched.");
+
+        if invert_colors_button.clicked()
+            && let Some(image_to_adjust) = select_image_to_adjust(state)
+        {
+            let _ = worker.sender().send(WorkerRequest::InvertColors {
+                image: image_to_adjust,
+            });
+
+            state.is_processing_image = true;
+        }
+    }
+
+    pub(super) fn update(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(10.0),
+                    bottom: taffy::LengthPercentageAuto::Length(10.0),
+                },
+                ..Default::default()
+            })
+            .separator();
+
+        taffy_ui
+            .style(taffy::Style {
+                display: taffy::Display::Flex,
+                flex_direction: taffy::FlexDirection::Column,
+                justify_content: Some(taffy::JustifyContent::Start),
+                justify_items: Some(taffy::JustifyItems::Start),
+                align_content: Some(taffy::AlignContent::Start),
+                align_items: Some(taffy::AlignItems::Center),
+                margin: taffy::Rect::length(8.0),
+                min_size: taffy::Size {
+                    width: taffy::Dimension::Percent(1.0),
+                    height: taffy::Dimension::Auto,
+                },
+                ..Default::default()
+            })
+            .add(|taffy_ui| {
+                taffy_ui
+                    .style(taffy::Style {
+                        min_size: taffy::Size {
+                            width: taffy::Dimension::Percent(1.0),
+                            height: taffy::Dimension::Auto,
+                        },
+                        margin: taffy::Rect {
+                            left: taffy::LengthPercentageAuto::Length(0.0),
+                            right: taffy::LengthPercentageAuto::Length(0.0),
+                            top: taffy::LengthPercentageAuto::Length(6.0),
+                            bottom: taffy::LengthPercentageAuto::Length(12.0),
+                        },
+                        ..Default::default()
+                    })
+                    .ui(|ui| {
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(format!(
+                                "{} Adjustments",
+                                egui_phosphor::regular::SLIDERS_HORIZONTAL,
+                            ))
+                            .size(16.0),
+                        ))
+                    });
+
+                self.update_posterization_ui(taffy_ui, worker, state);
+                self.update_brightness_contrast_ui(taffy_ui, worker, state);
+                self.update_mirror_tile_ui(taffy_ui, worker, state);
+                self.update_geometric_operations_ui(taffy_ui, worker, state);
+                self.update_channel_swap_ui(taffy_ui, worker, state);
+                self.update_invert_colors_ui(taffy_ui, worker, state);
+            });
+    }
+}
@@ -1,16 +1,33 @@
+use std::path::PathBuf;
+
 use egui::Vec2;
 use egui_taffy::{Tui, TuiBuilderLogic, taffy};
+use image::ImageFormat;
 
 use crate::{
-    gui::SharedState,
+    gui::{
+        SUPPORTED_INPUT_IMAGE_EXTENSIONS,
+        SharedState,
+        new_file_dialog,
+        remember_last_used_directory,
+    },
     worker::{WorkerHandle, WorkerRequest},
 };
 
-pub struct ImageLoadSection {}
+/// The size (in points) at which thumbnails are shown in the recent files list.
+const RECENT_FILE_THUMBNAIL_DISPLAY_SIZE: f32 = 40.0;
+
+pub struct ImageLoadSection {
+    /// Whether opened images are rotated/flipped into their display orientation
+    /// according to their EXIF metadata.
+    respect_orientation: bool,
+}
 
 impl ImageLoadSection {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            respect_orientation: true,
+        }
     }
 
     pub(super) fn update(
@@ -95,18 +112,7 @@ impl ImageLoadSection {
                             ));
 
                         if file_picker_button.clicked() {
-                            let optionally_picked_file = rfd::FileDialog::new()
-                                .set_title("Open file")
-                                .add_filter("Image", &["png", "jpg", "jpeg"])
-                                .pick_file();
-
-                            if let Some(picked_file) = optionally_picked_file {
-                                let _ = worker.sender().send(WorkerRequest::OpenSourceImage {
-                                    input_file_path: picked_file,
-                                });
-
-                                state.is_loading_image = true;
-                            }
+                            self.open_file_dialog(worker, state);
                         }
 
                         if state.is_loading_image {
@@ -148,6 +154,182 @@ impl ImageLoadSection {
                                 });
                         }
                     });
+
+                if let Some(source_image) = &state.source_image {
+                    let format_name = source_image
+                        .file_info
+                        .format
+                        .map(format_image_format_name)
+                        .unwrap_or_else(|| "Unknown format".to_string());
+
+                    let image_info = format!(
+                        "{} {} × {} px · {} · {}",
+                        egui_phosphor::regular::INFO,
+                        source_image.image.width(),
+                        source_image.image.height(),
+                        format_name,
+                        format_file_size(source_image.file_info.file_size),
+                    );
+
+                    taffy_ui
+                        .style(taffy::Style {
+                            margin: taffy::Rect {
+                                left: taffy::LengthPercentageAuto::Length(0.0),
+                                right: taffy::LengthPercentageAuto::Length(0.0),
+                                top: taffy::LengthPercentageAuto::Length(4.0),
+                                bottom: taffy::LengthPercentageAuto::Length(2.0),
+                            },
+                            ..Default::default()
+                        })
+                        .ui(|ui| {
+                            ui.label(egui::RichText::new(image_info).weak().small())
+                                .on_hover_text(source_image.file_path.to_string_lossy().to_string())
+                        });
+                }
+
+                taffy_ui
+                    .style(taffy::Style {
+                        margin: taffy::Rect {
+                            left: taffy::LengthPercentageAuto::Length(0.0),
+                            right: taffy::LengthPercentageAuto::Length(0.0),
+                            top: taffy::LengthPercentageAuto::Length(6.0),
+                            bottom: taffy::LengthPercentageAuto::Length(2.0),
+                        },
+                        ..Default::default()
+                    })
+                    .ui(|ui| {
+                        ui.checkbox(
+                            &mut self.respect_orientation,
+                            "Respect EXIF orientation",
+                        )
+                        .on_hover_text(
+                            "Rotates opened photos into the orientation they are displayed in, \
+                                so that horizontal and vertical sorting match what you see.",
+                        )
+                    });
+
+                if !state.recent_files.is_empty() {
+                    self.update_recent_files_ui(taffy_ui, worker, state);
+                }
             });
     }
+
+    /// Lets the user pick an image file and asks the worker to open it.
+    pub(super) fn open_file_dialog(&self, worker: &WorkerHandle, state: &mut SharedState) {
+        let optionally_picked_file = new_file_dialog(state)
+            .set_title("Open file")
+            .add_filter("Image", &SUPPORTED_INPUT_IMAGE_EXTENSIONS)
+            .pick_file();
+
+        if let Some(picked_file) = optionally_picked_file {
+            self.open_file(worker, state, picked_file);
+        }
+    }
+
+    /// Asks the worker to open the given image file as the new source image.
+    pub(super) fn open_file(
+        &self,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+        input_file_path: PathBuf,
+    ) {
+        remember_last_used_directory(state, &input_file_path);
+
+        let _ = worker.sender().send(WorkerRequest::OpenSourceImage {
+            input_file_path,
+            respect_orientation: self.respect_orientation,
+        });
+
+        state.is_loading_image = true;
+    }
+
+    fn update_recent_files_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        let mut clicked_file_path: Option<PathBuf> = None;
+
+        taffy_ui
+            .style(taffy::Style {
+                display: taffy::Display::Flex,
+                flex_direction: taffy::FlexDirection::Column,
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(12.0),
+                    bottom: taffy::LengthPercentageAuto::Length(2.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.label("Recent files");
+
+                let thumbnail_size = Vec2::splat(RECENT_FILE_THUMBNAIL_DISPLAY_SIZE);
+
+                for recent_file in &state.recent_files {
+                    ui.horizontal(|ui| {
+                        if let Some(thumbnail) = recent_file.thumbnail {
+                            ui.add_sized(
+                                thumbnail_size,
+                                egui::Image::from_texture(thumbnail).max_size(thumbnail_size),
+                            );
+                        } else {
+                            ui.add_sized(thumbnail_size, egui::Spinner::new());
+                        }
+
+                        let file_name = recent_file
+                            .file_path
+                            .file_name()
+                            .unwrap_or(recent_file.file_path.as_os_str())
+                            .to_string_lossy()
+                            .to_string();
+
+                        let open_button = ui
+                            .add_enabled(
+                                !state.is_loading_image,
+                                egui::Button::new(file_name),
+                            )
+                            .on_hover_text(recent_file.file_path.to_string_lossy().to_string());
+
+                        if open_button.clicked() {
+                            clicked_file_path = Some(recent_file.file_path.clone());
+                        }
+                    });
+                }
+            });
+
+        if let Some(clicked_file_path) = clicked_file_path {
+            self.open_file(worker, state, clicked_file_path);
+        }
+    }
+}
+
+/// Formats a file size in bytes into a human-readable string (e.g. `1.2 MiB`).
+fn format_file_size(file_size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if file_size < 1024 {
+        return format!("{file_size} B");
+    }
+
+    let mut size = file_size as f64 / 1024.0;
+    let mut unit_index = 0;
+
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit_index])
+}
+
+/// Returns the name an image format is commonly known by (e.g. `PNG`).
+fn format_image_format_name(format: ImageFormat) -> String {
+    format
+        .extensions_str()
+        .first()
+        .map(|extension| extension.to_uppercase())
+        .unwrap_or_else(|| format!("{format:?}"))
 }
@@ -1,9 +1,12 @@
+use std::path::PathBuf;
+
 use egui_taffy::{Tui, TuiBuilderLogic, taffy};
 
 use crate::{
     gui::{
         SharedState,
         panels::right::{
+            adjustments::ImageAdjustmentsSection,
             loading::ImageLoadSection,
             processing::ImageProcessingSection,
             saving::ImageSaveSection,
@@ -12,6 +15,7 @@ use crate::{
     worker::WorkerHandle,
 };
 
+mod adjustments;
 mod loading;
 mod processing;
 mod saving;
@@ -19,6 +23,7 @@ mod saving;
 pub struct RightSidebar {
     image_load_section: ImageLoadSection,
     image_processing_section: ImageProcessingSection,
+    image_adjustments_section: ImageAdjustmentsSection,
     image_save_section: ImageSaveSection,
 }
 
@@ -27,10 +32,26 @@ impl RightSidebar {
         Self {
             image_load_section: ImageLoadSection::new(),
             image_processing_section: ImageProcessingSection::new(),
+            image_adjustments_section: ImageAdjustmentsSection::new(),
             image_save_section: ImageSaveSection::new(),
         }
     }
 
+    /// Opens the same file picker as the "Open file" button.
+    pub fn open_file_dialog(&self, worker: &WorkerHandle, state: &mut SharedState) {
+        self.image_load_section.open_file_dialog(worker, state);
+    }
+
+    /// Opens the given image file, respecting the options of the "Input file" section.
+    pub fn open_file(&self, worker: &WorkerHandle, state: &mut SharedState, file_path: PathBuf) {
+        self.image_load_section.open_file(worker, state, file_path);
+    }
+
+    /// Opens the same save dialog as the "Save file" button.
+    pub fn save_file_dialog(&self, worker: &WorkerHandle, state: &mut SharedState) {
+        self.image_save_section.save_file_dialog(state, worker);
+    }
+
     pub fn update(
         &mut self,
         taffy_ui: &mut Tui,
@@ -58,6 +79,8 @@ impl RightSidebar {
                 self.image_load_section.update(taffy_ui, worker, state);
                 self.image_processing_section
                     .update(taffy_ui, worker, ctx, state);
+                self.image_adjustments_section
+                    .update(taffy_ui, worker, state);
                 self.image_save_section.update(taffy_ui, state, worker);
             });
     }
@@ -1,61 +1,97 @@
-use std::{ops::RangeInclusive, time::Instant};
+use std::{fs, num::NonZeroUsize, ops::RangeInclusive, sync::Arc, thread, time::Instant};
 
 use egui::Color32;
 use egui_taffy::{Tui, TuiBuilderLogic, taffy};
+use image::{Rgba, RgbaImage};
 use vulcan_core::{
-    feedback::FeedbackSegmentSelectionMode,
+    feedback::{FeedbackSegmentSelectionMode, ThresholdPreviewStyle, compute_otsu_threshold},
     pixel_sorting::{
+        ImageRegion,
         ImageSortingDirection,
+        PixelSegmentArrangement,
         PixelSegmentSortDirection,
+        config::{PixelSortConfig, ProcessingPreset, ProcessingRecipe},
         prepared::{
+            LARGEST_CANNY_EDGE_THRESHOLD,
+            LARGEST_SOBEL_GRADIENT_MAGNITUDE,
             PreparedSegmentSelectionMode,
             PreparedSegmentSortingMode,
+            SMALLEST_CANNY_EDGE_THRESHOLD,
             SegmentRandomizationMode,
         },
+        properties::{DistanceMetric, LuminanceWeights},
     },
 };
 
 use crate::{
     gui::{
-        ProcessedImage,
+        SUPPORTED_INPUT_IMAGE_EXTENSIONS,
         SharedState,
-        allocate_texture_for_rgba8_image,
         free_texture,
+        new_file_dialog,
         panels::ConditionalDisabledTuiBuilder,
+        redo_processed_image,
+        remember_last_used_directory,
+        send_recorded_operation,
+        undo_processed_image,
     },
     utilities::select_first_some,
-    worker::{WorkerHandle, WorkerRequest},
+    worker::{LIVE_SORT_PREVIEW_MAX_SIZE, WorkerHandle, WorkerRequest, resize_selection_mask},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UiImageSortingDirection {
     HorizontalAscending,
     HorizontalDescending,
+    HorizontalReverse,
     VerticalAscending,
     VerticalDescending,
+    VerticalReverse,
+    AngledAscending,
+    AngledDescending,
+    AngledReverse,
 }
 
 impl UiImageSortingDirection {
-    pub fn directions() -> [Self; 4] {
+    pub fn directions() -> [Self; 9] {
         [
             Self::HorizontalAscending,
             Self::HorizontalDescending,
+            Self::HorizontalReverse,
             Self::VerticalAscending,
             Self::VerticalDescending,
+            Self::VerticalReverse,
+            Self::AngledAscending,
+            Self::AngledDescending,
+            Self::AngledReverse,
         ]
     }
 
+    pub fn is_angled(self) -> bool {
+        matches!(
+            self,
+            Self::AngledAscending | Self::AngledDescending | Self::AngledReverse
+        )
+    }
+
     #[rustfmt::skip]
     pub fn label(self) -> &'static str {
         match self {
             UiImageSortingDirection::HorizontalAscending => "horizontal, ascending",
             UiImageSortingDirection::HorizontalDescending => "horizontal, descending",
+            UiImageSortingDirection::HorizontalReverse => "horizontal, reverse (mirror)",
             UiImageSortingDirection::VerticalAscending => "vertical, ascending",
-            UiImageSortingDirection::VerticalDescending => "vertical, descending"
+            UiImageSortingDirection::VerticalDescending => "vertical, descending",
+            UiImageSortingDirection::VerticalReverse => "vertical, reverse (mirror)",
+            UiImageSortingDirection::AngledAscending => "angled, ascending",
+            UiImageSortingDirection::AngledDescending => "angled, descending",
+            UiImageSortingDirection::AngledReverse => "angled, reverse (mirror)",
         }
     }
 
-    pub fn to_image_sorting_direction(self) -> ImageSortingDirection {
+    /// Converts this direction into an [`ImageSortingDirection`]. The `angle_degrees`
+    /// are only used by the angled directions.
+    pub fn to_image_sorting_direction(self, angle_degrees: f32) -> ImageSortingDirection {
         match self {
             UiImageSortingDirection::HorizontalAscending => {
                 ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending)
@@ -63,12 +99,65 @@ impl UiImageSortingDirection {
             UiImageSortingDirection::HorizontalDescending => {
                 ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Descending)
             }
+            UiImageSortingDirection::HorizontalReverse => {
+                ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Reverse)
+            }
             UiImageSortingDirection::VerticalAscending => {
                 ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending)
             }
             UiImageSortingDirection::VerticalDescending => {
                 ImageSortingDirection::Vertical(PixelSegmentSortDirection::Descending)
             }
+            UiImageSortingDirection::VerticalReverse => {
+                ImageSortingDirection::Vertical(PixelSegmentSortDirection::Reverse)
+            }
+            UiImageSortingDirection::AngledAscending => ImageSortingDirection::Angled {
+                degrees: angle_degrees,
+                direction: PixelSegmentSortDirection::Ascending,
+            },
+            UiImageSortingDirection::AngledDescending => ImageSortingDirection::Angled {
+                degrees: angle_degrees,
+                direction: PixelSegmentSortDirection::Descending,
+            },
+            UiImageSortingDirection::AngledReverse => ImageSortingDirection::Angled {
+                degrees: angle_degrees,
+                direction: PixelSegmentSortDirection::Reverse,
+            },
+        }
+    }
+
+    pub fn from_image_sorting_direction(direction: ImageSortingDirection) -> Self {
+        match direction {
+            ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Ascending) => {
+                UiImageSortingDirection::HorizontalAscending
+            }
+            ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Descending) => {
+                UiImageSortingDirection::HorizontalDescending
+            }
+            ImageSortingDirection::Horizontal(PixelSegmentSortDirection::Reverse) => {
+                UiImageSortingDirection::HorizontalReverse
+            }
+            ImageSortingDirection::Vertical(PixelSegmentSortDirection::Ascending) => {
+                UiImageSortingDirection::VerticalAscending
+            }
+            ImageSortingDirection::Vertical(PixelSegmentSortDirection::Descending) => {
+                UiImageSortingDirection::VerticalDescending
+            }
+            ImageSortingDirection::Vertical(PixelSegmentSortDirection::Reverse) => {
+                UiImageSortingDirection::VerticalReverse
+            }
+            ImageSortingDirection::Angled {
+                direction: PixelSegmentSortDirection::Ascending,
+                ..
+            } => UiImageSortingDirection::AngledAscending,
+            ImageSortingDirection::Angled {
+                direction: PixelSegmentSortDirection::Descending,
+                ..
+            } => UiImageSortingDirection::AngledDescending,
+            ImageSortingDirection::Angled {
+                direction: PixelSegmentSortDirection::Reverse,
+                ..
+            } => UiImageSortingDirection::AngledReverse,
         }
     }
 }
@@ -79,16 +168,26 @@ pub enum UiSegmentSelectionMode {
     LuminanceRange,
     HueRange,
     SaturationRange,
+    AlphaRange,
+    ColorDistanceRange,
     CannyEdges,
+    SobelEdges,
+    HighPassRange,
+    MaskImage,
 }
 
 impl UiSegmentSelectionMode {
-    pub fn modes() -> [Self; 4] {
+    pub fn modes() -> [Self; 9] {
         [
             Self::LuminanceRange,
             Self::HueRange,
             Self::SaturationRange,
+            Self::AlphaRange,
+            Self::ColorDistanceRange,
             Self::CannyEdges,
+            Self::SobelEdges,
+            Self::HighPassRange,
+            Self::MaskImage,
         ]
     }
 
@@ -98,7 +197,12 @@ impl UiSegmentSelectionMode {
             UiSegmentSelectionMode::LuminanceRange => "relative luminance range",
             UiSegmentSelectionMode::HueRange => "hue range",
             UiSegmentSelectionMode::SaturationRange => "saturation range",
+            UiSegmentSelectionMode::AlphaRange => "alpha (transparency) range",
+            UiSegmentSelectionMode::ColorDistanceRange => "distance to color",
             UiSegmentSelectionMode::CannyEdges => "edge-to-edge (canny)",
+            UiSegmentSelectionMode::SobelEdges => "thick edges (sobel)",
+            UiSegmentSelectionMode::HighPassRange => "detail (high-pass range)",
+            UiSegmentSelectionMode::MaskImage => "mask image",
         }
     }
 }
@@ -109,11 +213,33 @@ pub enum UiSortingMode {
     Luminance,
     Hue,
     Saturation,
+    Lightness,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    ColorDistance,
+    OklabLightness,
+    OklabChroma,
+    OklabHue,
 }
 
 impl UiSortingMode {
-    pub fn modes() -> [Self; 3] {
-        [Self::Luminance, Self::Hue, Self::Saturation]
+    pub fn modes() -> [Self; 12] {
+        [
+            Self::Luminance,
+            Self::Hue,
+            Self::Saturation,
+            Self::Lightness,
+            Self::Red,
+            Self::Green,
+            Self::Blue,
+            Self::Alpha,
+            Self::ColorDistance,
+            Self::OklabLightness,
+            Self::OklabChroma,
+            Self::OklabHue,
+        ]
     }
 
     #[rustfmt::skip]
@@ -122,38 +248,208 @@ impl UiSortingMode {
             Self::Luminance => "luminance",
             Self::Hue => "hue",
             Self::Saturation => "saturation",
+            Self::Lightness => "lightness",
+            Self::Red => "red channel",
+            Self::Green => "green channel",
+            Self::Blue => "blue channel",
+            Self::Alpha => "alpha",
+            Self::ColorDistance => "distance to color",
+            Self::OklabLightness => "lightness (OKLab)",
+            Self::OklabChroma => "chroma (OKLab)",
+            Self::OklabHue => "hue (OKLab)",
         }
     }
 
-    pub fn to_prepared_sorting_mode(self) -> PreparedSegmentSortingMode {
+    /// Converts this sorting mode into its core counterpart. The `color_distance_target`
+    /// and `color_distance_metric` are only used by [`Self::ColorDistance`],
+    /// and the `luminance_weights` only by [`Self::Luminance`].
+    pub fn to_prepared_sorting_mode(
+        self,
+        color_distance_target: Rgba<u8>,
+        color_distance_metric: DistanceMetric,
+        luminance_weights: LuminanceWeights,
+    ) -> PreparedSegmentSortingMode {
         match self {
-            Self::Luminance => PreparedSegmentSortingMode::Luminance,
+            Self::Luminance if luminance_weights == LuminanceWeights::REC_709 => {
+                PreparedSegmentSortingMode::Luminance
+            }
+            Self::Luminance => PreparedSegmentSortingMode::WeightedLuminance {
+                weights: luminance_weights,
+            },
             Self::Hue => PreparedSegmentSortingMode::Hue,
             Self::Saturation => PreparedSegmentSortingMode::Saturation,
+            Self::Lightness => PreparedSegmentSortingMode::Lightness,
+            Self::Red => PreparedSegmentSortingMode::Red,
+            Self::Green => PreparedSegmentSortingMode::Green,
+            Self::Blue => PreparedSegmentSortingMode::Blue,
+            Self::Alpha => PreparedSegmentSortingMode::Alpha,
+            Self::ColorDistance => PreparedSegmentSortingMode::ColorDistance {
+                target: color_distance_target,
+                distance_metric: color_distance_metric,
+            },
+            Self::OklabLightness => PreparedSegmentSortingMode::OklabLightness,
+            Self::OklabChroma => PreparedSegmentSortingMode::OklabChroma,
+            Self::OklabHue => PreparedSegmentSortingMode::OklabHue,
+        }
+    }
+
+    pub fn from_prepared_sorting_mode(sorting_mode: PreparedSegmentSortingMode) -> Self {
+        match sorting_mode {
+            PreparedSegmentSortingMode::Luminance
+            | PreparedSegmentSortingMode::WeightedLuminance { .. } => Self::Luminance,
+            PreparedSegmentSortingMode::Hue => Self::Hue,
+            PreparedSegmentSortingMode::Saturation => Self::Saturation,
+            PreparedSegmentSortingMode::Lightness => Self::Lightness,
+            PreparedSegmentSortingMode::Red => Self::Red,
+            PreparedSegmentSortingMode::Green => Self::Green,
+            PreparedSegmentSortingMode::Blue => Self::Blue,
+            PreparedSegmentSortingMode::Alpha => Self::Alpha,
+            PreparedSegmentSortingMode::ColorDistance { .. } => Self::ColorDistance,
+            PreparedSegmentSortingMode::OklabLightness => Self::OklabLightness,
+            PreparedSegmentSortingMode::OklabChroma => Self::OklabChroma,
+            PreparedSegmentSortingMode::OklabHue => Self::OklabHue,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiLuminanceWeights {
+    Rec709,
+    Rec601,
+    Custom,
+}
+
+impl UiLuminanceWeights {
+    pub fn weights() -> [Self; 3] {
+        [Self::Rec709, Self::Rec601, Self::Custom]
+    }
+
+    #[rustfmt::skip]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Rec709 => "Rec. 709 (default)",
+            Self::Rec601 => "Rec. 601",
+            Self::Custom => "custom",
         }
     }
 }
 
 
-const SMALLEST_CANNY_EDGE_THRESHOLD: f32 = 0.1;
+const DISTANCE_METRICS: [DistanceMetric; 2] = [DistanceMetric::Rgb, DistanceMetric::OkLab];
+
+#[rustfmt::skip]
+fn distance_metric_label(metric: DistanceMetric) -> &'static str {
+    match metric {
+        DistanceMetric::Rgb => "RGB (default)",
+        DistanceMetric::OkLab => "OKLab (perceptual)",
+    }
+}
+
+
+const THRESHOLD_PREVIEW_STYLES: [ThresholdPreviewStyle; 2] =
+    [ThresholdPreviewStyle::MaskBlack, ThresholdPreviewStyle::Dim];
+
+#[rustfmt::skip]
+fn threshold_preview_style_label(style: ThresholdPreviewStyle) -> &'static str {
+    match style {
+        ThresholdPreviewStyle::MaskBlack => "mask black",
+        ThresholdPreviewStyle::Dim => "dim",
+    }
+}
+
+
+const SEGMENT_ARRANGEMENTS: [PixelSegmentArrangement; 3] = [
+    PixelSegmentArrangement::Linear,
+    PixelSegmentArrangement::TowardCenter,
+    PixelSegmentArrangement::AwayFromCenter,
+];
 
-/// See <https://docs.rs/imageproc/latest/imageproc/edges/fn.canny.html> for more information.
-const LARGEST_CANNY_EDGE_THRESHOLD: f32 = 1140.39;
+#[rustfmt::skip]
+fn segment_arrangement_label(arrangement: PixelSegmentArrangement) -> &'static str {
+    match arrangement {
+        PixelSegmentArrangement::Linear => "edge to edge",
+        PixelSegmentArrangement::TowardCenter => "from edges inward",
+        PixelSegmentArrangement::AwayFromCenter => "from center outward",
+    }
+}
+
+
+const SMALLEST_HIGH_PASS_BLUR_SIGMA: f32 = 0.1;
+const LARGEST_HIGH_PASS_BLUR_SIGMA: f32 = 50.0;
+
+const SMALLEST_CANNY_BLUR_SIGMA: f32 = 0.1;
+const LARGEST_CANNY_BLUR_SIGMA: f32 = 10.0;
+
+/// The largest Canny edge dilation (in pixels) selectable in the GUI.
+const MAXIMUM_CANNY_EDGE_DILATION: u32 = 32;
+
+/// The initial target color of the color distance modes (sky blue).
+const DEFAULT_COLOR_DISTANCE_TARGET: [u8; 3] = [135, 206, 235];
+
+fn srgb_to_opaque_rgba(srgb: [u8; 3]) -> Rgba<u8> {
+    Rgba([srgb[0], srgb[1], srgb[2], u8::MAX])
+}
 
 
 pub struct UiPixelSegmentSelectionState {
     segment_selection_mode: UiSegmentSelectionMode,
     sorting_mode: UiSortingMode,
+    tiebreak_sorting_mode: Option<UiSortingMode>,
+
+    /// The target color of [`UiSortingMode::ColorDistance`],
+    /// shared by the sorting and tiebreak modes.
+    sorting_color_distance_target: [u8; 3],
+
+    /// How color distances are measured, used by both color distance selection and sorting.
+    color_distance_metric: DistanceMetric,
+
+    /// The weights of the red, green and blue components when computing luminance,
+    /// used by both luminance selection and sorting (see [`Self::luminance_weights`]).
+    luminance_weights: UiLuminanceWeights,
+    custom_luminance_weights: [f32; 3],
 
     luminance_range_low: f32,
     luminance_range_high: f32,
+
+    /// Whether the luminance range is given in percentiles of the image's luminances
+    /// (see [`PreparedSegmentSelectionMode::LuminancePercentileRange`]).
+    luminance_range_as_percentiles: bool,
+    luminance_percentile_low: f32,
+    luminance_percentile_high: f32,
+
+    /// Whether the low and high luminance thresholds move together, keeping them
+    /// `locked_band_width` apart (see [`Self::apply_locked_band_width`]).
+    lock_band_width: bool,
+    locked_band_width: f32,
+
+    /// Whether the pixels *outside* of the luminance range are selected instead
+    /// (the same goes for [`Self::hue_range_invert`] and [`Self::saturation_range_invert`]).
+    luminance_range_invert: bool,
+
     hue_range_low: f32,
     hue_range_high: f32,
+    hue_range_invert: bool,
     saturation_range_low: f32,
     saturation_range_high: f32,
+    saturation_range_invert: bool,
+    alpha_range_low: f32,
+    alpha_range_high: f32,
+    color_distance_target: [u8; 3],
+    color_distance_range_low: f32,
+    color_distance_range_high: f32,
     canny_edges_low: f32,
     canny_edges_high: f32,
     canny_edges_segment_starts_on_image_edge: bool,
+    canny_edges_segment_arrangement: PixelSegmentArrangement,
+    canny_edges_invert_edges: bool,
+    canny_edges_blur: bool,
+    canny_edges_blur_sigma: f32,
+    canny_edges_dilation: u32,
+    sobel_edges_threshold: f32,
+    high_pass_blur_sigma: f32,
+    high_pass_range_low: f32,
+    high_pass_range_high: f32,
 }
 
 impl UiPixelSegmentSelectionState {
@@ -161,38 +457,404 @@ impl UiPixelSegmentSelectionState {
         Self {
             segment_selection_mode: UiSegmentSelectionMode::LuminanceRange,
             sorting_mode: UiSortingMode::Luminance,
+            tiebreak_sorting_mode: None,
+            sorting_color_distance_target: DEFAULT_COLOR_DISTANCE_TARGET,
+            color_distance_metric: DistanceMetric::default(),
+            luminance_weights: UiLuminanceWeights::Rec709,
+            custom_luminance_weights: [
+                LuminanceWeights::REC_709.red,
+                LuminanceWeights::REC_709.green,
+                LuminanceWeights::REC_709.blue,
+            ],
             luminance_range_low: 0.0,
             luminance_range_high: 1.0,
+            luminance_range_as_percentiles: false,
+            luminance_percentile_low: 0.0,
+            luminance_percentile_high: 100.0,
+            lock_band_width: false,
+            locked_band_width: 1.0,
+            luminance_range_invert: false,
             hue_range_low: 0.0,
             hue_range_high: 360.0,
+            hue_range_invert: false,
             saturation_range_low: 0.0,
             saturation_range_high: 1.0,
+            saturation_range_invert: false,
+            alpha_range_low: 0.0,
+            alpha_range_high: 1.0,
+            color_distance_target: DEFAULT_COLOR_DISTANCE_TARGET,
+            color_distance_range_low: 0.0,
+            color_distance_range_high: 0.2,
             canny_edges_low: SMALLEST_CANNY_EDGE_THRESHOLD,
             canny_edges_high: LARGEST_CANNY_EDGE_THRESHOLD,
             canny_edges_segment_starts_on_image_edge: false,
+            canny_edges_segment_arrangement: PixelSegmentArrangement::Linear,
+            canny_edges_invert_edges: false,
+            canny_edges_blur: false,
+            canny_edges_blur_sigma: 1.0,
+            canny_edges_dilation: 0,
+            sobel_edges_threshold: 200.0,
+            high_pass_blur_sigma: 2.0,
+            high_pass_range_low: 0.2,
+            high_pass_range_high: 1.0,
         }
     }
 
-    // pub fn selection_mode(&self) -> ImmediateSegmentSelectionMode {
-    //     match self.mode {
-    //         UiImmediateSegmentSelectionMode::LuminanceRange => {
-    //             ImmediateSegmentSelectionMode::LuminanceRange {
-    //                 low: self.luminance_range_low,
-    //                 high: self.luminance_range_high,
-    //             }
-    //         }
-    //         UiImmediateSegmentSelectionMode::HueRange => ImmediateSegmentSelectionMode::HueRange {
-    //             low: self.hue_range_low,
-    //             high: self.hue_range_high,
-    //         },
-    //         UiImmediateSegmentSelectionMode::SaturationRange => {
-    //             ImmediateSegmentSelectionMode::SaturationRange {
-    //                 low: self.saturation_range_low,
-    //                 high: self.saturation_range_high,
-    //             }
-    //         }
-    //     }
-    // }
+    pub fn luminance_weights(&self) -> LuminanceWeights {
+        match self.luminance_weights {
+            UiLuminanceWeights::Rec709 => LuminanceWeights::REC_709,
+            UiLuminanceWeights::Rec601 => LuminanceWeights::REC_601,
+            UiLuminanceWeights::Custom => {
+                let [red, green, blue] = self.custom_luminance_weights;
+
+                LuminanceWeights { red, green, blue }
+            }
+        }
+    }
+
+    /// Switches to the given luminance weights, selecting the matching preset if there is one.
+    fn apply_luminance_weights(&mut self, weights: LuminanceWeights) {
+        self.luminance_weights = if weights == LuminanceWeights::REC_709 {
+            UiLuminanceWeights::Rec709
+        } else if weights == LuminanceWeights::REC_601 {
+            UiLuminanceWeights::Rec601
+        } else {
+            self.custom_luminance_weights = [weights.red, weights.green, weights.blue];
+            UiLuminanceWeights::Custom
+        };
+    }
+
+    /// Returns the luminance thresholds that are currently being edited (absolute ones or
+    /// percentiles, see [`Self::luminance_range_as_percentiles`]), along with their valid range.
+    fn active_luminance_thresholds(&mut self) -> (&mut f32, &mut f32, RangeInclusive<f32>) {
+        if self.luminance_range_as_percentiles {
+            (
+                &mut self.luminance_percentile_low,
+                &mut self.luminance_percentile_high,
+                0.0..=100.0,
+            )
+        } else {
+            (
+                &mut self.luminance_range_low,
+                &mut self.luminance_range_high,
+                0.0..=1.0,
+            )
+        }
+    }
+
+    /// Remembers the distance between the currently edited luminance thresholds
+    /// as the width to preserve while [`Self::lock_band_width`] is enabled.
+    fn capture_locked_band_width(&mut self) {
+        let (low, high, _) = self.active_luminance_thresholds();
+        let band_width = (*high - *low).max(0.0);
+
+        self.locked_band_width = band_width;
+    }
+
+    /// Moves the other luminance threshold along with the one that was just changed
+    /// so that they stay `locked_band_width` apart, clamping the band to the valid range.
+    fn apply_locked_band_width(&mut self, low_changed: bool, high_changed: bool) {
+        let band_width = self.locked_band_width;
+        let (low, high, range) = self.active_luminance_thresholds();
+
+        if low_changed {
+            *low = low.clamp(*range.start(), *range.end() - band_width);
+            *high = *low + band_width;
+        } else if high_changed {
+            *high = high.clamp(*range.start() + band_width, *range.end());
+            *low = *high - band_width;
+        }
+    }
+
+    pub fn to_prepared_selection_mode(&self) -> PreparedSegmentSelectionMode {
+        match self.segment_selection_mode {
+            UiSegmentSelectionMode::LuminanceRange if self.luminance_range_as_percentiles => {
+                PreparedSegmentSelectionMode::LuminancePercentileRange {
+                    low: self.luminance_percentile_low,
+                    high: self.luminance_percentile_high,
+                    invert: self.luminance_range_invert,
+                }
+            }
+            UiSegmentSelectionMode::LuminanceRange
+                if self.luminance_weights() != LuminanceWeights::REC_709 =>
+            {
+                PreparedSegmentSelectionMode::WeightedLuminanceRange {
+                    weights: self.luminance_weights(),
+                    low: self.luminance_range_low,
+                    high: self.luminance_range_high,
+                    invert: self.luminance_range_invert,
+                }
+            }
+            UiSegmentSelectionMode::LuminanceRange => {
+                PreparedSegmentSelectionMode::LuminanceRange {
+                    low: self.luminance_range_low,
+                    high: self.luminance_range_high,
+                    invert: self.luminance_range_invert,
+                }
+            }
+            UiSegmentSelectionMode::HueRange => PreparedSegmentSelectionMode::HueRange {
+                low: self.hue_range_low,
+                high: self.hue_range_high,
+                invert: self.hue_range_invert,
+            },
+            UiSegmentSelectionMode::SaturationRange => {
+                PreparedSegmentSelectionMode::SaturationRange {
+                    low: self.saturation_range_low,
+                    high: self.saturation_range_high,
+                    invert: self.saturation_range_invert,
+                }
+            }
+            UiSegmentSelectionMode::AlphaRange => PreparedSegmentSelectionMode::AlphaRange {
+                low: self.alpha_range_low,
+                high: self.alpha_range_high,
+            },
+            UiSegmentSelectionMode::ColorDistanceRange => {
+                PreparedSegmentSelectionMode::ColorDistanceRange {
+                    target: srgb_to_opaque_rgba(self.color_distance_target),
+                    low: self.color_distance_range_low,
+                    high: self.color_distance_range_high,
+                    distance_metric: self.color_distance_metric,
+                }
+            }
+            UiSegmentSelectionMode::CannyEdges => PreparedSegmentSelectionMode::CannyEdges {
+                low: self.canny_edges_low,
+                high: self.canny_edges_high,
+                segment_starts_on_image_edge: self.canny_edges_segment_starts_on_image_edge,
+                segment_arrangement: self.canny_edges_segment_arrangement,
+                invert_edges: self.canny_edges_invert_edges,
+                blur_sigma: self.canny_edges_blur.then_some(self.canny_edges_blur_sigma),
+                dilation: self.canny_edges_dilation,
+            },
+            UiSegmentSelectionMode::SobelEdges => PreparedSegmentSelectionMode::SobelEdges {
+                threshold: self.sobel_edges_threshold,
+            },
+            UiSegmentSelectionMode::HighPassRange => PreparedSegmentSelectionMode::HighPassRange {
+                blur_sigma: self.high_pass_blur_sigma,
+                low: self.high_pass_range_low,
+                high: self.high_pass_range_high,
+            },
+            // The mask itself lives in the shared state and is only filled in right before sorting.
+            UiSegmentSelectionMode::MaskImage => PreparedSegmentSelectionMode::MaskImage {
+                mask: Default::default(),
+            },
+        }
+    }
+
+    /// Switches to the given selection mode and overwrites its parameters,
+    /// keeping the parameters of all other modes intact.
+    pub fn apply_prepared_selection_mode(&mut self, selection_mode: PreparedSegmentSelectionMode) {
+        match selection_mode {
+            PreparedSegmentSelectionMode::LuminanceRange { low, high, invert } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::LuminanceRange;
+                self.luminance_range_as_percentiles = false;
+                self.luminance_range_low = low;
+                self.luminance_range_high = high;
+                self.luminance_range_invert = invert;
+                self.capture_locked_band_width();
+            }
+            PreparedSegmentSelectionMode::WeightedLuminanceRange {
+                weights,
+                low,
+                high,
+                invert,
+            } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::LuminanceRange;
+                self.luminance_range_as_percentiles = false;
+                self.apply_luminance_weights(weights);
+                self.luminance_range_low = low;
+                self.luminance_range_high = high;
+                self.luminance_range_invert = invert;
+                self.capture_locked_band_width();
+            }
+            PreparedSegmentSelectionMode::LuminancePercentileRange { low, high, invert } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::LuminanceRange;
+                self.luminance_range_as_percentiles = true;
+                self.luminance_percentile_low = low;
+                self.luminance_percentile_high = high;
+                self.luminance_range_invert = invert;
+                self.capture_locked_band_width();
+            }
+            PreparedSegmentSelectionMode::HueRange { low, high, invert } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::HueRange;
+                self.hue_range_low = low;
+                self.hue_range_high = high;
+                self.hue_range_invert = invert;
+            }
+            PreparedSegmentSelectionMode::SaturationRange { low, high, invert } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::SaturationRange;
+                self.saturation_range_low = low;
+                self.saturation_range_high = high;
+                self.saturation_range_invert = invert;
+            }
+            PreparedSegmentSelectionMode::AlphaRange { low, high } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::AlphaRange;
+                self.alpha_range_low = low;
+                self.alpha_range_high = high;
+            }
+            PreparedSegmentSelectionMode::ColorDistanceRange {
+                target,
+                low,
+                high,
+                distance_metric,
+            } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::ColorDistanceRange;
+                self.color_distance_target = [target.0[0], target.0[1], target.0[2]];
+                self.color_distance_range_low = low;
+                self.color_distance_range_high = high;
+                self.color_distance_metric = distance_metric;
+            }
+            PreparedSegmentSelectionMode::CannyEdges {
+                low,
+                high,
+                segment_starts_on_image_edge,
+                segment_arrangement,
+                invert_edges,
+                blur_sigma,
+                dilation,
+            } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::CannyEdges;
+                self.canny_edges_low = low;
+                self.canny_edges_high = high;
+                self.canny_edges_segment_starts_on_image_edge = segment_starts_on_image_edge;
+                self.canny_edges_segment_arrangement = segment_arrangement;
+                self.canny_edges_invert_edges = invert_edges;
+                self.canny_edges_blur = blur_sigma.is_some();
+                if let Some(blur_sigma) = blur_sigma {
+                    self.canny_edges_blur_sigma = blur_sigma;
+                }
+                self.canny_edges_dilation = dilation;
+            }
+            PreparedSegmentSelectionMode::SobelEdges { threshold } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::SobelEdges;
+                self.sobel_edges_threshold = threshold;
+            }
+            PreparedSegmentSelectionMode::HighPassRange {
+                blur_sigma,
+                low,
+                high,
+            } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::HighPassRange;
+                self.high_pass_blur_sigma = blur_sigma;
+                self.high_pass_range_low = low;
+                self.high_pass_range_high = high;
+            }
+            PreparedSegmentSelectionMode::MaskImage { .. } => {
+                self.segment_selection_mode = UiSegmentSelectionMode::MaskImage;
+            }
+            // Selection modes can't be combined in the interface, so only the first one
+            // is selected (while the parameters of both are kept).
+            PreparedSegmentSelectionMode::Combined { first, second, .. } => {
+                tracing::warn!(
+                    "Combined selection modes are not supported in the interface, \
+                    only the first one will be used."
+                );
+
+                self.apply_prepared_selection_mode(*second);
+                self.apply_prepared_selection_mode(*first);
+            }
+        }
+    }
+
+    /// Resizes the threshold range of the current segment selection mode to
+    /// `width_fraction` (`0.0..=1.0`) of the mode's full range, keeping it centered
+    /// on its current midpoint (shifted inwards if it would extend past either end).
+    ///
+    /// The Canny and Sobel edge thresholds are left untouched, as they aren't a range
+    /// of pixel values, but rather thresholds on the strength of detected edges.
+    /// The mask image selection mode has no range at all.
+    pub fn set_selection_range_width(&mut self, width_fraction: f32) {
+        let width_fraction = width_fraction.clamp(0.0, 1.0);
+
+        match self.segment_selection_mode {
+            UiSegmentSelectionMode::LuminanceRange if self.luminance_range_as_percentiles => {
+                resize_range_around_midpoint(
+                    &mut self.luminance_percentile_low,
+                    &mut self.luminance_percentile_high,
+                    width_fraction,
+                    100.0,
+                )
+            }
+            UiSegmentSelectionMode::LuminanceRange => resize_range_around_midpoint(
+                &mut self.luminance_range_low,
+                &mut self.luminance_range_high,
+                width_fraction,
+                1.0,
+            ),
+            UiSegmentSelectionMode::HueRange => resize_range_around_midpoint(
+                &mut self.hue_range_low,
+                &mut self.hue_range_high,
+                width_fraction,
+                360.0,
+            ),
+            UiSegmentSelectionMode::SaturationRange => resize_range_around_midpoint(
+                &mut self.saturation_range_low,
+                &mut self.saturation_range_high,
+                width_fraction,
+                1.0,
+            ),
+            UiSegmentSelectionMode::AlphaRange => resize_range_around_midpoint(
+                &mut self.alpha_range_low,
+                &mut self.alpha_range_high,
+                width_fraction,
+                1.0,
+            ),
+            UiSegmentSelectionMode::ColorDistanceRange => resize_range_around_midpoint(
+                &mut self.color_distance_range_low,
+                &mut self.color_distance_range_high,
+                width_fraction,
+                1.0,
+            ),
+            UiSegmentSelectionMode::CannyEdges
+            | UiSegmentSelectionMode::SobelEdges
+            | UiSegmentSelectionMode::MaskImage => {}
+            UiSegmentSelectionMode::HighPassRange => resize_range_around_midpoint(
+                &mut self.high_pass_range_low,
+                &mut self.high_pass_range_high,
+                width_fraction,
+                1.0,
+            ),
+        }
+    }
+}
+
+/// Converts a rectangle normalized to `0.0..=1.0` (see [`SharedState::sorting_region`])
+/// into the pixels of an image of the given size.
+fn normalized_rect_to_image_region(
+    normalized_rect: egui::Rect,
+    image_width: u32,
+    image_height: u32,
+) -> ImageRegion {
+    let to_pixels =
+        |fraction: f32, size: u32| (fraction.clamp(0.0, 1.0) * size as f32).round() as u32;
+
+    let left = to_pixels(normalized_rect.min.x, image_width);
+    let top = to_pixels(normalized_rect.min.y, image_height);
+    let right = to_pixels(normalized_rect.max.x, image_width);
+    let bottom = to_pixels(normalized_rect.max.y, image_height);
+
+    ImageRegion {
+        x: left,
+        y: top,
+        width: right.saturating_sub(left),
+        height: bottom.saturating_sub(top),
+    }
+}
+
+/// Resizes the `low..=high` range (which lies within `0.0..=full_range`)
+/// to `width_fraction` of `full_range`, keeping its midpoint where possible.
+fn resize_range_around_midpoint(
+    low: &mut f32,
+    high: &mut f32,
+    width_fraction: f32,
+    full_range: f32,
+) {
+    let new_width = width_fraction * full_range;
+    let midpoint = (*low + *high) / 2.0;
+
+    let new_low = (midpoint - new_width / 2.0).clamp(0.0, full_range - new_width);
+
+    *low = new_low;
+    *high = new_low + new_width;
 }
 
 
@@ -224,6 +886,8 @@ pub struct UiSegmentRandomizationState {
     uniform_high_inclusive: usize,
     normal_mean: f32,
     normal_standard_deviation: f32,
+    use_fixed_seed: bool,
+    fixed_seed: u64,
 }
 
 impl UiSegmentRandomizationState {
@@ -234,6 +898,20 @@ impl UiSegmentRandomizationState {
             uniform_high_inclusive: 500,
             normal_mean: 50.0,
             normal_standard_deviation: 30.0,
+            use_fixed_seed: false,
+            fixed_seed: 0,
+        }
+    }
+
+    pub fn to_segment_randomization_seed(&self) -> Option<u64> {
+        self.use_fixed_seed.then_some(self.fixed_seed)
+    }
+
+    pub fn apply_segment_randomization_seed(&mut self, seed: Option<u64>) {
+        self.use_fixed_seed = seed.is_some();
+
+        if let Some(seed) = seed {
+            self.fixed_seed = seed;
         }
     }
 
@@ -250,28 +928,170 @@ impl UiSegmentRandomizationState {
             }),
         }
     }
+
+    /// Switches to the given randomization mode and overwrites its parameters,
+    /// keeping the parameters of all other modes intact.
+    pub fn apply_segment_randomization_mode(
+        &mut self,
+        randomization_mode: Option<SegmentRandomizationMode>,
+    ) {
+        match randomization_mode {
+            None => {
+                self.mode = UiSegmentRandomizationMode::None;
+            }
+            Some(SegmentRandomizationMode::Uniform {
+                low_inclusive,
+                high_inclusive,
+            }) => {
+                self.mode = UiSegmentRandomizationMode::Uniform;
+                self.uniform_low_inclusive = low_inclusive;
+                self.uniform_high_inclusive = high_inclusive;
+            }
+            Some(SegmentRandomizationMode::Normal {
+                mean,
+                standard_deviation,
+            }) => {
+                self.mode = UiSegmentRandomizationMode::Normal;
+                self.normal_mean = mean;
+                self.normal_standard_deviation = standard_deviation;
+            }
+        }
+    }
 }
 
 
 
-fn construct_precise_normalized_slider(value: &mut f32) -> egui::Slider {
+/// The step of the normalized (`0.0..=1.0`) threshold sliders, e.g. for luminance or saturation.
+///
+/// Pressing the left/right arrow keys while such a slider is focused nudges it by
+/// this amount, or by [`SHIFT_KEYBOARD_NUDGE_MULTIPLIER`] times as much when holding Shift.
+const NORMALIZED_SLIDER_STEP: f32 = 0.0001;
+
+/// The step of the hue threshold sliders (`0.0..=360.0`), in degrees.
+///
+/// Keyboard nudging behaves the same as described in [`NORMALIZED_SLIDER_STEP`].
+const HUE_SLIDER_STEP: f32 = 0.01;
+
+/// The step of the luminance percentile sliders (`0.0..=100.0`).
+///
+/// Keyboard nudging behaves the same as described in [`NORMALIZED_SLIDER_STEP`].
+const PERCENTILE_SLIDER_STEP: f32 = 0.01;
+
+/// The step of the Canny edge detection threshold sliders.
+///
+/// Keyboard nudging behaves the same as described in [`NORMALIZED_SLIDER_STEP`].
+const CANNY_EDGE_THRESHOLD_SLIDER_STEP: f32 = 0.01;
+
+/// How many slider steps a single Shift+arrow key press moves a focused threshold slider by.
+const SHIFT_KEYBOARD_NUDGE_MULTIPLIER: f32 = 10.0;
+
+
+fn construct_precise_normalized_slider(value: &mut f32) -> egui::Slider<'_> {
     egui::Slider::new(value, 0.0..=1.0)
-        .step_by(0.0001)
+        .step_by(NORMALIZED_SLIDER_STEP as f64)
         .min_decimals(4)
         .max_decimals(6)
-        .drag_value_speed(0.0001)
+        .drag_value_speed(NORMALIZED_SLIDER_STEP as f64)
 }
 
-fn construct_precise_hue_slider(value: &mut f32) -> egui::Slider {
+fn construct_precise_hue_slider(value: &mut f32) -> egui::Slider<'_> {
     egui::Slider::new(value, 0.0..=360.0)
-        .step_by(0.001)
-        .min_decimals(4)
+        .step_by(HUE_SLIDER_STEP as f64)
+        .min_decimals(2)
+        .max_decimals(6)
+        .drag_value_speed(HUE_SLIDER_STEP as f64)
+}
+
+fn construct_precise_percentile_slider(value: &mut f32) -> egui::Slider<'_> {
+    egui::Slider::new(value, 0.0..=100.0)
+        .step_by(PERCENTILE_SLIDER_STEP as f64)
+        .min_decimals(2)
         .max_decimals(6)
-        .drag_value_speed(0.001)
+        .drag_value_speed(PERCENTILE_SLIDER_STEP as f64)
+}
+
+fn construct_precise_canny_edge_slider(value: &mut f32) -> egui::Slider<'_> {
+    egui::Slider::new(
+        value,
+        SMALLEST_CANNY_EDGE_THRESHOLD..=LARGEST_CANNY_EDGE_THRESHOLD,
+    )
+    .step_by(CANNY_EDGE_THRESHOLD_SLIDER_STEP as f64)
+    .min_decimals(2)
+    .max_decimals(6)
+    .drag_value_speed(CANNY_EDGE_THRESHOLD_SLIDER_STEP as f64)
+}
+
+
+/// Extends the built-in keyboard handling of a focused slider with Shift+arrow nudging.
+///
+/// `egui` already moves a focused slider by exactly one `step` per left/right arrow key press.
+/// When Shift is held, this adds the remaining `SHIFT_KEYBOARD_NUDGE_MULTIPLIER - 1` steps,
+/// so that each press moves the slider by [`SHIFT_KEYBOARD_NUDGE_MULTIPLIER`] steps in total.
+fn apply_shift_keyboard_nudge(
+    ui: &egui::Ui,
+    slider_response: &egui::Response,
+    value: &mut f32,
+    step: f32,
+    range: RangeInclusive<f32>,
+) {
+    if !slider_response.has_focus() {
+        return;
+    }
+
+    let number_of_nudges = ui.input(|input| {
+        if !input.modifiers.shift {
+            return 0;
+        }
+
+        input.num_presses(egui::Key::ArrowRight) as i32
+            - input.num_presses(egui::Key::ArrowLeft) as i32
+    });
+
+    if number_of_nudges == 0 {
+        return;
+    }
+
+    let additional_offset =
+        number_of_nudges as f32 * step * (SHIFT_KEYBOARD_NUDGE_MULTIPLIER - 1.0);
+
+    *value = (*value + additional_offset).clamp(*range.start(), *range.end());
+}
+
+/// Adds a threshold slider with a [`egui::DragValue`] next to it that edits the same value,
+/// so exact thresholds can be typed or pasted in (e.g. when reproducing a preset by hand).
+///
+/// The slider also gets Shift+arrow nudging, see [`apply_shift_keyboard_nudge`].
+/// Returns the union of the slider's and the text field's responses.
+fn add_threshold_slider(
+    ui: &mut egui::Ui,
+    value: &mut f32,
+    construct_slider: fn(&mut f32) -> egui::Slider<'_>,
+    label: &str,
+    step: f32,
+    range: RangeInclusive<f32>,
+) -> egui::Response {
+    ui.horizontal(|ui| {
+        let slider = ui.add(construct_slider(value).show_value(false));
+        apply_shift_keyboard_nudge(ui, &slider, value, step, range.clone());
+
+        let text_entry = ui.add(
+            egui::DragValue::new(value)
+                .range(range)
+                .speed(step)
+                .max_decimals(6),
+        );
+        ui.label(label);
+
+        slider | text_entry
+    })
+    .inner
 }
 
 
-fn construct_precise_custom_slider(value: &mut f32, range: RangeInclusive<f32>) -> egui::Slider {
+fn construct_precise_custom_slider(
+    value: &mut f32,
+    range: RangeInclusive<f32>,
+) -> egui::Slider<'_> {
     egui::Slider::new(value, range)
         .step_by(0.0001)
         .min_decimals(4)
@@ -283,26 +1103,334 @@ fn construct_precise_custom_slider(value: &mut f32, range: RangeInclusive<f32>)
 fn construct_precise_custom_slider_usize(
     value: &mut usize,
     range: RangeInclusive<usize>,
-) -> egui::Slider {
+) -> egui::Slider<'_> {
     egui::Slider::new(value, range)
 }
 
 
+/// The width of the selection threshold range (as a fraction of the full range)
+/// at a glitch intensity of `0.0`, see [`ImageProcessingSection::apply_glitch_intensity`].
+const GLITCH_INTENSITY_MIN_RANGE_WIDTH: f32 = 0.1;
+
+/// The mean randomized segment length (in pixels) at the lowest non-zero glitch intensity.
+const GLITCH_INTENSITY_MIN_SEGMENT_MEAN: f32 = 10.0;
+
+/// The mean randomized segment length (in pixels) at a glitch intensity of `1.0`.
+const GLITCH_INTENSITY_MAX_SEGMENT_MEAN: f32 = 300.0;
+
+/// The default largest width and height of proxy images, see [`crate::gui::ProxyImage`].
+const DEFAULT_PROXY_MAX_SIZE: u32 = 1024;
+const SMALLEST_PROXY_MAX_SIZE: u32 = 128;
+const LARGEST_PROXY_MAX_SIZE: u32 = 4096;
+
+
 pub struct ImageProcessingSection {
     segment_selection_state: UiPixelSegmentSelectionState,
     segment_sorting_direction: UiImageSortingDirection,
+
+    /// The angle of the sorting lines in degrees, used by the angled sorting directions.
+    sorting_angle_degrees: f32,
+
     randomization_state: UiSegmentRandomizationState,
+    premultiply_alpha: bool,
+    output_only_segments: bool,
+
+    /// Whether pixels with equal sorting keys keep their original order
+    /// (see [`PixelSortConfig::stable_sort`]).
+    stable_sort: bool,
+
+    /// Prepared segments shorter than this many pixels are left unsorted.
+    minimum_segment_length: usize,
+
+    /// Whether prepared segments longer than [`Self::maximum_segment_length`] are split.
+    limit_segment_length: bool,
+    maximum_segment_length: usize,
+
+    threshold_preview_style: ThresholdPreviewStyle,
+
+    /// Whether hovering over threshold sliders previews the fully sorted result
+    /// (on a downscaled image) instead of only the selected pixels.
+    live_sort_preview: bool,
+
+    /// Whether sorts and previews run on a downscaled proxy of the source image
+    /// (at most [`Self::proxy_max_size`] pixels wide and tall), see [`crate::gui::ProxyImage`].
+    proxy_mode: bool,
+    proxy_max_size: u32,
+
+    /// Whether pixel sorts are limited to [`Self::sorting_thread_count`] threads
+    /// (instead of rayon's automatic choice), see [`WorkerRequest::SetSortingThreadCount`].
+    limit_sorting_threads: bool,
+    sorting_thread_count: usize,
+
+    /// The number of threads the machine can run in parallel, which is also
+    /// the largest selectable [`Self::sorting_thread_count`].
+    available_thread_count: usize,
+
+    /// The value of the "Intensity" master slider (`0.0..=1.0`),
+    /// see [`Self::apply_glitch_intensity`].
+    glitch_intensity: f32,
+
+    /// The contents of the share code text field.
+    share_code_input: String,
+
+    /// The error that occurred while decoding the last pasted share code, if any.
+    share_code_error: Option<String>,
+
+    /// The error that occurred while saving or loading the last preset, if any.
+    preset_error: Option<String>,
+
+    /// The error that occurred while saving or applying the last recipe, if any.
+    recipe_error: Option<String>,
 }
 
 impl ImageProcessingSection {
     pub fn new() -> Self {
+        let available_thread_count = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+
         Self {
             segment_selection_state: UiPixelSegmentSelectionState::new(),
             segment_sorting_direction: UiImageSortingDirection::HorizontalAscending,
+            sorting_angle_degrees: 45.0,
             randomization_state: UiSegmentRandomizationState::new(),
+            premultiply_alpha: false,
+            output_only_segments: false,
+            stable_sort: false,
+            minimum_segment_length: 1,
+            limit_segment_length: false,
+            maximum_segment_length: 256,
+            threshold_preview_style: ThresholdPreviewStyle::MaskBlack,
+            live_sort_preview: false,
+            proxy_mode: false,
+            proxy_max_size: DEFAULT_PROXY_MAX_SIZE,
+            limit_sorting_threads: false,
+            sorting_thread_count: available_thread_count,
+            available_thread_count,
+            glitch_intensity: 0.5,
+            share_code_input: String::new(),
+            share_code_error: None,
+            preset_error: None,
+            recipe_error: None,
+        }
+    }
+
+    fn to_pixel_sort_config(&self) -> PixelSortConfig {
+        let sorting_color_distance_target =
+            srgb_to_opaque_rgba(self.segment_selection_state.sorting_color_distance_target);
+        let color_distance_metric = self.segment_selection_state.color_distance_metric;
+        let luminance_weights = self.segment_selection_state.luminance_weights();
+
+        PixelSortConfig {
+            selection_mode: self.segment_selection_state.to_prepared_selection_mode(),
+            randomization_mode: self.randomization_state.to_segment_randomization_mode(),
+            randomization_seed: self.randomization_state.to_segment_randomization_seed(),
+            minimum_segment_length: self.minimum_segment_length,
+            maximum_segment_length: self
+                .limit_segment_length
+                .then_some(self.maximum_segment_length),
+            sorting_mode: self
+                .segment_selection_state
+                .sorting_mode
+                .to_prepared_sorting_mode(
+                    sorting_color_distance_target,
+                    color_distance_metric,
+                    luminance_weights,
+                ),
+            tiebreak_mode: self
+                .segment_selection_state
+                .tiebreak_sorting_mode
+                .map(|mode| {
+                    mode.to_prepared_sorting_mode(
+                        sorting_color_distance_target,
+                        color_distance_metric,
+                        luminance_weights,
+                    )
+                }),
+            direction: self
+                .segment_sorting_direction
+                .to_image_sorting_direction(self.sorting_angle_degrees),
+            premultiply_alpha: self.premultiply_alpha,
+            output_only_segments: self.output_only_segments,
+            stable_sort: self.stable_sort,
         }
     }
 
+    /// Like [`Self::to_pixel_sort_config`], but also fills in the loaded selection mask
+    /// (which is not part of the configuration itself), if the mask image selection mode is used.
+    ///
+    /// In proxy mode, a mask matching the source image is resized to match `image` instead,
+    /// as `image` is then usually (derived from) the downscaled proxy.
+    fn to_executable_pixel_sort_config(
+        &self,
+        image: &RgbaImage,
+        state: &SharedState,
+    ) -> PixelSortConfig {
+        let mut config = self.to_pixel_sort_config();
+
+        if let PreparedSegmentSelectionMode::MaskImage { mask } = &mut config.selection_mode
+            && let Some(selection_mask) = &state.selection_mask
+        {
+            *mask = selection_mask.mask.clone();
+
+            if self.proxy_mode
+                && let Some(source_image) = &state.source_image
+                && mask.dimensions() == source_image.image.dimensions()
+            {
+                resize_selection_mask(&mut config.selection_mode, image.dimensions());
+            }
+        }
+
+        config
+    }
+
+    /// Returns the image that pixel sorts and threshold previews are applied to:
+    /// the last processed image if there is one, otherwise the source image
+    /// (or, in proxy mode, its proxy once it has been generated).
+    fn image_to_process(&self, state: &SharedState) -> Option<Arc<RgbaImage>> {
+        if let Some(processed_image) = &state.processed_image_last {
+            return Some(processed_image.image.clone());
+        }
+
+        let source_image = state.source_image.as_ref()?;
+
+        if self.proxy_mode
+            && let Some(proxy) = &source_image.proxy
+        {
+            return Some(proxy.image.clone());
+        }
+
+        Some(source_image.image.clone())
+    }
+
+    /// Sends a request to pixel sort `image` using the current parameters to the `worker`.
+    fn send_pixel_sort_request(
+        &self,
+        image: Arc<RgbaImage>,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        let config = self.to_executable_pixel_sort_config(&image, state);
+
+        let region_of_interest = state.sorting_region.map(|sorting_region| {
+            normalized_rect_to_image_region(sorting_region, image.width(), image.height())
+        });
+
+        let message_to_send = WorkerRequest::PerformPreparedPixelSorting {
+            image,
+            segment_selection_mode: config.selection_mode,
+            segment_randomization_mode: config.randomization_mode,
+            segment_randomization_seed: config.randomization_seed,
+            minimum_segment_length: config.minimum_segment_length,
+            maximum_segment_length: config.maximum_segment_length,
+            sorting_mode: config.sorting_mode,
+            tiebreak_mode: config.tiebreak_mode,
+            sorting_direction: config.direction,
+            premultiply_alpha: config.premultiply_alpha,
+            output_only_segments: config.output_only_segments,
+            stable_sort: config.stable_sort,
+            region_of_interest,
+        };
+
+        let _ = worker.sender().send(message_to_send);
+
+        state.is_processing_image = true;
+    }
+
+    fn apply_pixel_sort_config(&mut self, config: PixelSortConfig) {
+        self.segment_selection_state
+            .apply_prepared_selection_mode(config.selection_mode);
+        self.segment_selection_state.sorting_mode =
+            UiSortingMode::from_prepared_sorting_mode(config.sorting_mode);
+        self.segment_selection_state.tiebreak_sorting_mode = config
+            .tiebreak_mode
+            .map(UiSortingMode::from_prepared_sorting_mode);
+        if let Some(PreparedSegmentSortingMode::ColorDistance {
+            target,
+            distance_metric,
+        }) = [Some(config.sorting_mode), config.tiebreak_mode]
+            .into_iter()
+            .flatten()
+            .find(|mode| {
+                matches!(
+                    mode,
+                    PreparedSegmentSortingMode::ColorDistance { .. }
+                )
+            })
+        {
+            self.segment_selection_state.sorting_color_distance_target =
+                [target.0[0], target.0[1], target.0[2]];
+            self.segment_selection_state.color_distance_metric = distance_metric;
+        }
+        if let Some(PreparedSegmentSortingMode::WeightedLuminance { weights }) =
+            [Some(config.sorting_mode), config.tiebreak_mode]
+                .into_iter()
+                .flatten()
+                .find(|mode| {
+                    matches!(
+                        mode,
+                        PreparedSegmentSortingMode::WeightedLuminance { .. }
+                    )
+                })
+        {
+            self.segment_selection_state
+                .apply_luminance_weights(weights);
+        }
+        self.randomization_state
+            .apply_segment_randomization_mode(config.randomization_mode);
+        self.randomization_state
+            .apply_segment_randomization_seed(config.randomization_seed);
+        self.minimum_segment_length = config.minimum_segment_length;
+        self.limit_segment_length = config.maximum_segment_length.is_some();
+        if let Some(maximum_segment_length) = config.maximum_segment_length {
+            self.maximum_segment_length = maximum_segment_length;
+        }
+        self.segment_sorting_direction =
+            UiImageSortingDirection::from_image_sorting_direction(config.direction);
+        if let ImageSortingDirection::Angled { degrees, .. } = config.direction {
+            self.sorting_angle_degrees = degrees;
+        }
+        self.premultiply_alpha = config.premultiply_alpha;
+        self.output_only_segments = config.output_only_segments;
+        self.stable_sort = config.stable_sort;
+    }
+
+    /// Maps a single "glitch intensity" (`0.0..=1.0`) onto several sorting parameters at once.
+    ///
+    /// The mapping is as follows:
+    /// - the threshold range of the current segment selection mode is resized around its
+    ///   midpoint to span from [`GLITCH_INTENSITY_MIN_RANGE_WIDTH`] (at `0.0`) up to the full
+    ///   range (at `1.0`), linearly, so that more pixels end up being sorted
+    ///   (the Canny edge thresholds are not affected),
+    /// - at `0.0` segment randomization is turned off; above that, segments are randomly
+    ///   split with a normal distribution whose mean grows quadratically from
+    ///   [`GLITCH_INTENSITY_MIN_SEGMENT_MEAN`] to [`GLITCH_INTENSITY_MAX_SEGMENT_MEAN`] pixels,
+    ///   with the standard deviation always being half of the mean, so that higher intensities
+    ///   produce longer and more uneven streaks.
+    ///
+    /// There is no per-segment sort probability in the sorting pipeline yet,
+    /// so intensity does not (yet) control how many of the selected segments get sorted.
+    ///
+    /// Every parameter can still be fine-tuned individually afterwards.
+    fn apply_glitch_intensity(&mut self, intensity: f32) {
+        let intensity = intensity.clamp(0.0, 1.0);
+
+        self.segment_selection_state.set_selection_range_width(
+            GLITCH_INTENSITY_MIN_RANGE_WIDTH + (1.0 - GLITCH_INTENSITY_MIN_RANGE_WIDTH) * intensity,
+        );
+
+        if intensity == 0.0 {
+            self.randomization_state.mode = UiSegmentRandomizationMode::None;
+            return;
+        }
+
+        let segment_mean = GLITCH_INTENSITY_MIN_SEGMENT_MEAN
+            + (GLITCH_INTENSITY_MAX_SEGMENT_MEAN - GLITCH_INTENSITY_MIN_SEGMENT_MEAN)
+                * intensity.powi(2);
+
+        self.randomization_state.mode = UiSegmentRandomizationMode::Normal;
+        self.randomization_state.normal_mean = segment_mean;
+        self.randomization_state.normal_standard_deviation = segment_mean / 2.0;
+    }
+
     fn handle_threshold_preview_state(
         &mut self,
         should_display_preview: bool,
@@ -312,10 +1440,7 @@ impl ImageProcessingSection {
         state: &mut SharedState,
     ) {
         if !state.is_waiting_for_updated_preview && should_display_preview {
-            let image_to_preview_on = select_first_some(
-                state.processed_image_last.as_ref().map(|last| &last.image),
-                state.source_image.as_ref().map(|source| &source.image),
-            );
+            let image_to_preview_on = self.image_to_process(state);
 
             let last_preview_time = state
                 .threshold_preview
@@ -327,11 +1452,46 @@ impl ImageProcessingSection {
                 .unwrap_or(true);
 
             if should_redraw_preview && let Some(image_to_preview_on) = image_to_preview_on {
-                let _ = worker.sender().send(WorkerRequest::ShowThresholdPreview {
-                    image: image_to_preview_on.clone(),
-                    method: feedback_mode,
-                    requested_at: Instant::now(),
-                });
+                let live_sort_config = self
+                    .live_sort_preview
+                    .then(|| self.to_executable_pixel_sort_config(&image_to_preview_on, state))
+                    .filter(|config| {
+                        config
+                            .selection_mode
+                            .validate_for_image(
+                                image_to_preview_on.width(),
+                                image_to_preview_on.height(),
+                            )
+                            .is_ok()
+                    });
+
+                // Configurations that can't be sorted (e.g. without a selection mask)
+                // fall back to the regular threshold preview.
+                let request = if let Some(config) = live_sort_config {
+                    let region_of_interest = state.sorting_region.map(|sorting_region| {
+                        normalized_rect_to_image_region(
+                            sorting_region,
+                            image_to_preview_on.width(),
+                            image_to_preview_on.height(),
+                        )
+                    });
+
+                    WorkerRequest::ShowLiveSortPreview {
+                        image: image_to_preview_on,
+                        config,
+                        region_of_interest,
+                        requested_at: Instant::now(),
+                    }
+                } else {
+                    WorkerRequest::ShowThresholdPreview {
+                        image: image_to_preview_on,
+                        method: feedback_mode,
+                        style: self.threshold_preview_style,
+                        requested_at: Instant::now(),
+                    }
+                };
+
+                let _ = worker.sender().send(request);
 
                 state.is_waiting_for_updated_preview = true;
             }
@@ -385,15 +1545,15 @@ impl ImageProcessingSection {
             .on_hover_text("Reset view to source image.")
             .on_disabled_hover_text("Cannot reset to source image: no processed image yet.");
 
-        if reset_button.clicked() {
-            if let Some(processed_image) = state.processed_image_last.take() {
-                let texture_manager = ctx.tex_manager();
-                let mut locked_texture_manager = texture_manager.write();
+        if reset_button.clicked()
+            && let Some(processed_image) = state.processed_image_last.take()
+        {
+            let texture_manager = ctx.tex_manager();
+            let mut locked_texture_manager = texture_manager.write();
 
-                locked_texture_manager.free(processed_image.image_texture.id);
+            locked_texture_manager.free(processed_image.image_texture.id);
 
-                drop(processed_image);
-            }
+            drop(processed_image);
         }
 
         let undo_button = taffy_ui
@@ -409,7 +1569,7 @@ impl ImageProcessingSection {
                 },
                 margin: taffy::Rect {
                     left: taffy::LengthPercentageAuto::Length(0.0),
-                    right: taffy::LengthPercentageAuto::Length(14.0),
+                    right: taffy::LengthPercentageAuto::Length(8.0),
                     top: taffy::LengthPercentageAuto::Length(0.0),
                     bottom: taffy::LengthPercentageAuto::Length(0.0),
                 },
@@ -430,147 +1590,109 @@ impl ImageProcessingSection {
             .on_disabled_hover_text("Undo unavailable: no entries in history stack.");
 
         if undo_button.clicked() {
-            let last_history_entry = state.processed_image_history_stack.pop();
-
-            if let Some(last_history_entry) = last_history_entry {
-                let current_last_procesed = state
-                    .processed_image_last
-                    .take()
-                    .expect("non-empty history stack, but no last processed image?!");
-
-                let texture_manager = ctx.tex_manager();
-                {
-                    let mut locked_texture_manager = texture_manager.write();
-                    locked_texture_manager.free(current_last_procesed.image_texture.id);
-                };
-
-                let allocated_texture =
-                    allocate_texture_for_rgba8_image(&last_history_entry.image, &texture_manager);
-
-                state.processed_image_last = Some(ProcessedImage {
-                    image: last_history_entry.image,
-                    image_aspect_ratio: last_history_entry.image_aspect_ratio,
-                    image_texture: allocated_texture,
-                });
-            }
+            undo_processed_image(state, ctx);
         }
 
-        let sorting_button = taffy_ui
+        let redo_button = taffy_ui
             .style(taffy::Style {
                 flex_grow: 4.0,
                 min_size: taffy::Size {
-                    width: taffy::Dimension::Length(150.0),
+                    width: taffy::Dimension::Length(20.0),
                     height: taffy::Dimension::Length(24.0),
                 },
                 max_size: taffy::Size {
                     width: taffy::Dimension::Auto,
                     height: taffy::Dimension::Length(32.0),
                 },
-                ..Default::default()
-            })
-            .ui_add(egui::Button::new("Execute pixel sort"));
-        // .on_hover_text(
-        //     "Performs pixel sorting, always using the source image. \
-        //     If you want apply sorting to a processed image instead, manually export and re-import the image."
-        // );
-
-        #[allow(clippy::manual_map)]
-        if sorting_button.clicked() {
-            let image_to_sort = if let Some(processed_image_state) = &state.processed_image_last {
-                Some(processed_image_state.image.clone())
-            } else if let Some(source_image_state) = &state.source_image {
-                Some(source_image_state.image.clone())
-            } else {
-                None
-            };
-
-            if let Some(image_to_sort) = image_to_sort {
-                let sorting_mode = self
-                    .segment_selection_state
-                    .sorting_mode
-                    .to_prepared_sorting_mode();
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(14.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(0.0),
+                },
+                ..Default::default()
+            })
+            .disabled_if(state.processed_image_redo_stack.is_empty())
+            .ui_add(
+                egui::Button::new(egui_phosphor::regular::CLOCK_CLOCKWISE)
+                    .fill(Color32::TRANSPARENT),
+            )
+            .on_hover_text(format!(
+                "Redo by one step ({} undone entries available).",
+                state.processed_image_redo_stack.len()
+            ))
+            .on_disabled_hover_text("Redo unavailable: nothing has been undone.");
 
-                let sorting_direction = self.segment_sorting_direction.to_image_sorting_direction();
+        if redo_button.clicked() {
+            redo_processed_image(state, ctx);
+        }
 
-                let segment_randomization_mode =
-                    self.randomization_state.to_segment_randomization_mode();
+        let sorting_button = taffy_ui
+            .style(taffy::Style {
+                flex_grow: 4.0,
+                min_size: taffy::Size {
+                    width: taffy::Dimension::Length(150.0),
+                    height: taffy::Dimension::Length(24.0),
+                },
+                max_size: taffy::Size {
+                    width: taffy::Dimension::Auto,
+                    height: taffy::Dimension::Length(32.0),
+                },
+                ..Default::default()
+            })
+            .disabled_if(state.source_image.is_none() && state.processed_image_last.is_none())
+            .ui_add(egui::Button::new("Execute pixel sort"))
+            .on_disabled_hover_text("Load an image first.");
+        // .on_hover_text(
+        //     "Performs pixel sorting, always using the source image. \
+        //     If you want apply sorting to a processed image instead, manually export and re-import the image."
+        // );
 
-                let message_to_send: WorkerRequest = match self
-                    .segment_selection_state
-                    .segment_selection_mode
-                {
-                    UiSegmentSelectionMode::LuminanceRange => {
-                        WorkerRequest::PerformPreparedPixelSorting {
-                            image: image_to_sort,
-                            segment_selection_mode: PreparedSegmentSelectionMode::LuminanceRange {
-                                low: self.segment_selection_state.luminance_range_low,
-                                high: self.segment_selection_state.luminance_range_high,
-                            },
-                            segment_randomization_mode,
-                            sorting_mode,
-                            sorting_direction,
-                        }
-                    }
-                    UiSegmentSelectionMode::HueRange => {
-                        WorkerRequest::PerformPreparedPixelSorting {
-                            image: image_to_sort,
-                            segment_selection_mode: PreparedSegmentSelectionMode::HueRange {
-                                low: self.segment_selection_state.hue_range_low,
-                                high: self.segment_selection_state.hue_range_high,
-                            },
-                            segment_randomization_mode,
-                            sorting_mode,
-                            sorting_direction,
-                        }
-                    }
-                    UiSegmentSelectionMode::SaturationRange => {
-                        WorkerRequest::PerformPreparedPixelSorting {
-                            image: image_to_sort,
-                            segment_selection_mode: PreparedSegmentSelectionMode::SaturationRange {
-                                low: self.segment_selection_state.saturation_range_low,
-                                high: self.segment_selection_state.saturation_range_high,
-                            },
-                            segment_randomization_mode,
-                            sorting_mode,
-                            sorting_direction,
-                        }
-                    }
-                    UiSegmentSelectionMode::CannyEdges => {
-                        WorkerRequest::PerformPreparedPixelSorting {
-                            image: image_to_sort,
-                            segment_selection_mode: PreparedSegmentSelectionMode::CannyEdges {
-                                low: self.segment_selection_state.canny_edges_low,
-                                high: self.segment_selection_state.canny_edges_high,
-                                segment_starts_on_image_edge: self
-                                    .segment_selection_state
-                                    .canny_edges_segment_starts_on_image_edge,
-                            },
-                            segment_randomization_mode,
-                            sorting_mode,
-                            sorting_direction,
-                        }
-                    }
-                };
+        if sorting_button.clicked()
+            && let Some(image_to_sort) = self.image_to_process(state)
+        {
+            self.send_pixel_sort_request(image_to_sort, worker, state);
+        }
 
-                let _ = worker.sender().send(message_to_send);
+        if state.is_processing_image {
+            let progress_style = taffy::Style {
+                flex_grow: 1.0,
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(10.0),
+                    bottom: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                },
+                ..Default::default()
+            };
 
-                state.is_processing_image = true;
+            // Only pixel sorts report their progress, other processing shows a spinner.
+            if let Some(processing_progress) = state.processing_progress {
+                taffy_ui.style(progress_style).ui_add(
+                    egui::ProgressBar::new(processing_progress)
+                        .desired_width(60.0)
+                        .show_percentage(),
+                );
+            } else {
+                taffy_ui.style(progress_style).ui_add(egui::Spinner::new());
             }
-        }
 
-        if state.is_processing_image {
-            taffy_ui
+            let cancel_button = taffy_ui
                 .style(taffy::Style {
-                    flex_grow: 1.0,
                     margin: taffy::Rect {
-                        left: taffy::LengthPercentageAuto::Length(10.0),
+                        left: taffy::LengthPercentageAuto::Length(6.0),
                         bottom: taffy::LengthPercentageAuto::Length(0.0),
                         right: taffy::LengthPercentageAuto::Length(0.0),
                         top: taffy::LengthPercentageAuto::Length(0.0),
                     },
                     ..Default::default()
                 })
-                .ui_add(egui::Spinner::new());
+                .ui_add(egui::Button::new(egui_phosphor::regular::X).fill(Color32::TRANSPARENT))
+                .on_hover_text("Cancel the running pixel sort, keeping the previous image.");
+
+            if cancel_button.clicked() {
+                worker.cancel_current_operation();
+            }
         } else {
             let spinner_style = taffy_ui.egui_ui_mut().style().spacing.interact_size.y;
 
@@ -593,6 +1715,31 @@ impl ImageProcessingSection {
         }
     }
 
+    fn update_glitch_intensity_ui(&mut self, taffy_ui: &mut Tui) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(10.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                let intensity_slider = ui
+                    .add(egui::Slider::new(&mut self.glitch_intensity, 0.0..=1.0).text("Intensity"))
+                    .on_hover_text(
+                        "Adjusts the selection range width and segment randomization at once. \
+                        Individual settings below can still be fine-tuned afterwards.",
+                    );
+
+                if intensity_slider.changed() {
+                    self.apply_glitch_intensity(self.glitch_intensity);
+                }
+            });
+    }
+
     fn update_sorting_ui(
         &mut self,
         taffy_ui: &mut Tui,
@@ -624,6 +1771,44 @@ impl ImageProcessingSection {
                     })
             });
 
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(14.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                egui::ComboBox::from_label("Threshold preview style")
+                    .selected_text(threshold_preview_style_label(
+                        self.threshold_preview_style,
+                    ))
+                    .show_ui(ui, |ui| {
+                        for style in THRESHOLD_PREVIEW_STYLES {
+                            ui.selectable_value(
+                                &mut self.threshold_preview_style,
+                                style,
+                                threshold_preview_style_label(style),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "How pixels that would not be sorted are shown \
+                        while hovering over a threshold slider.",
+                    );
+
+                ui.checkbox(&mut self.live_sort_preview, "Live sort preview")
+                    .on_hover_text(format!(
+                        "While hovering over a threshold slider, shows the sorted result \
+                        (on a copy of the image downscaled to at most {LIVE_SORT_PREVIEW_MAX_SIZE} \
+                        pixels) instead of only the selected pixels.",
+                    ));
+            });
+
         let segment_selection_mode_dropdown_style = taffy::Style {
             display: taffy::Display::Flex,
             flex_direction: taffy::FlexDirection::Column,
@@ -642,33 +1827,156 @@ impl ImageProcessingSection {
                 taffy_ui
                     .style(segment_selection_mode_dropdown_style.clone())
                     .ui(|ui| {
-                        let low_threshold = ui.add(
-                            construct_precise_normalized_slider(
-                                &mut self.segment_selection_state.luminance_range_low,
+                        let percentiles_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.luminance_range_as_percentiles,
+                                "Interpret thresholds as percentiles",
                             )
-                            .text("Low threshold"),
-                        );
+                            .on_hover_text(
+                                "Thresholds select a share of the image's pixels (e.g. 50 to 100 \
+                                selects the brighter half) instead of absolute luminances, \
+                                which behaves more consistently across different images.",
+                            );
+
+                        let lock_band_width_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.lock_band_width,
+                                "Lock band width",
+                            )
+                            .on_hover_text(
+                                "Moves both thresholds together, keeping the distance between \
+                                them, so that a narrow band can be swept across the range.",
+                            );
 
-                        let high_threshold = ui.add(
-                            construct_precise_normalized_slider(
-                                &mut self.segment_selection_state.luminance_range_high,
+                        let invert_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.luminance_range_invert,
+                                "Invert selection",
                             )
-                            .text("High threshold"),
+                            .on_hover_text(
+                                "Selects the pixels outside of the range instead, \
+                                e.g. everything except the mid-tones.",
+                            );
+
+                        if lock_band_width_checkbox.changed() || percentiles_checkbox.changed() {
+                            self.segment_selection_state.capture_locked_band_width();
+                        }
+
+                        let (low_threshold, high_threshold) =
+                            if self.segment_selection_state.luminance_range_as_percentiles {
+                                let low_threshold = add_threshold_slider(
+                                    ui,
+                                    &mut self.segment_selection_state.luminance_percentile_low,
+                                    construct_precise_percentile_slider,
+                                    "Low percentile",
+                                    PERCENTILE_SLIDER_STEP,
+                                    0.0..=100.0,
+                                );
+
+                                let high_threshold = add_threshold_slider(
+                                    ui,
+                                    &mut self.segment_selection_state.luminance_percentile_high,
+                                    construct_precise_percentile_slider,
+                                    "High percentile",
+                                    PERCENTILE_SLIDER_STEP,
+                                    0.0..=100.0,
+                                );
+
+                                (low_threshold, high_threshold)
+                            } else {
+                                let low_threshold = add_threshold_slider(
+                                    ui,
+                                    &mut self.segment_selection_state.luminance_range_low,
+                                    construct_precise_normalized_slider,
+                                    "Low threshold",
+                                    NORMALIZED_SLIDER_STEP,
+                                    0.0..=1.0,
+                                );
+
+                                let high_threshold = add_threshold_slider(
+                                    ui,
+                                    &mut self.segment_selection_state.luminance_range_high,
+                                    construct_precise_normalized_slider,
+                                    "High threshold",
+                                    NORMALIZED_SLIDER_STEP,
+                                    0.0..=1.0,
+                                );
+
+                                (low_threshold, high_threshold)
+                            };
+
+                        if self.segment_selection_state.lock_band_width {
+                            self.segment_selection_state.apply_locked_band_width(
+                                low_threshold.changed(),
+                                high_threshold.changed(),
+                            );
+                        }
+
+                        let image_to_threshold = select_first_some(
+                            state.processed_image_last.as_ref().map(|last| &last.image),
+                            state.source_image.as_ref().map(|source| &source.image),
                         );
 
+                        let otsu_button = ui
+                            .add_enabled(
+                                image_to_threshold.is_some(),
+                                egui::Button::new("Auto (Otsu)"),
+                            )
+                            .on_hover_text(
+                                "Splits the image into dark and bright pixels \
+                                (using Otsu's method) and selects the bright ones.",
+                            )
+                            .on_disabled_hover_text("Load an image first.");
+
+                        if otsu_button.clicked()
+                            && let Some(image_to_threshold) = image_to_threshold
+                        {
+                            // The Otsu threshold is an absolute luminance, not a percentile.
+                            self.segment_selection_state.luminance_range_as_percentiles = false;
+                            self.segment_selection_state.luminance_range_low =
+                                compute_otsu_threshold(image_to_threshold);
+                            self.segment_selection_state.luminance_range_high = 1.0;
+                            self.segment_selection_state.capture_locked_band_width();
+                        }
+
                         let should_display_preview = low_threshold.contains_pointer()
                             || low_threshold.dragged()
                             || low_threshold.changed()
                             || high_threshold.contains_pointer()
                             || high_threshold.dragged()
-                            || high_threshold.changed();
+                            || high_threshold.changed()
+                            || percentiles_checkbox.changed()
+                            || invert_checkbox.contains_pointer()
+                            || invert_checkbox.changed()
+                            || otsu_button.contains_pointer();
+
+                        let feedback_mode =
+                            if self.segment_selection_state.luminance_range_as_percentiles {
+                                FeedbackSegmentSelectionMode::LuminancePercentileRange {
+                                    low: self.segment_selection_state.luminance_percentile_low,
+                                    high: self.segment_selection_state.luminance_percentile_high,
+                                    invert: self.segment_selection_state.luminance_range_invert,
+                                }
+                            } else if self.segment_selection_state.luminance_weights()
+                                != LuminanceWeights::REC_709
+                            {
+                                FeedbackSegmentSelectionMode::WeightedLuminanceRange {
+                                    weights: self.segment_selection_state.luminance_weights(),
+                                    low: self.segment_selection_state.luminance_range_low,
+                                    high: self.segment_selection_state.luminance_range_high,
+                                    invert: self.segment_selection_state.luminance_range_invert,
+                                }
+                            } else {
+                                FeedbackSegmentSelectionMode::LuminanceRange {
+                                    low: self.segment_selection_state.luminance_range_low,
+                                    high: self.segment_selection_state.luminance_range_high,
+                                    invert: self.segment_selection_state.luminance_range_invert,
+                                }
+                            };
 
                         self.handle_threshold_preview_state(
                             should_display_preview,
-                            FeedbackSegmentSelectionMode::LuminanceRange {
-                                low: self.segment_selection_state.luminance_range_low,
-                                high: self.segment_selection_state.luminance_range_high,
-                            },
+                            feedback_mode,
                             worker,
                             ctx,
                             state,
@@ -679,32 +1987,50 @@ impl ImageProcessingSection {
                 taffy_ui
                     .style(segment_selection_mode_dropdown_style.clone())
                     .ui(|ui| {
-                        let low_hue_threshold = ui.add(
-                            construct_precise_hue_slider(
-                                &mut self.segment_selection_state.hue_range_low,
-                            )
-                            .text("Low threshold"),
+                        let low_hue_threshold = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.hue_range_low,
+                            construct_precise_hue_slider,
+                            "Low threshold",
+                            HUE_SLIDER_STEP,
+                            0.0..=360.0,
+                        )
+                        .on_hover_text(
+                            "A low threshold above the high one selects a range that \
+                            wraps around 360° (e.g. 350° to 20° for reds).",
                         );
 
-                        let high_hue_threshold = ui.add(
-                            construct_precise_hue_slider(
-                                &mut self.segment_selection_state.hue_range_high,
-                            )
-                            .text("High threshold"),
+                        let high_hue_threshold = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.hue_range_high,
+                            construct_precise_hue_slider,
+                            "High threshold",
+                            HUE_SLIDER_STEP,
+                            0.0..=360.0,
                         );
 
+                        let invert_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.hue_range_invert,
+                                "Invert selection",
+                            )
+                            .on_hover_text("Selects the pixels outside of the hue range instead.");
+
                         let should_display_preview = low_hue_threshold.contains_pointer()
                             || low_hue_threshold.dragged()
                             || low_hue_threshold.changed()
                             || high_hue_threshold.contains_pointer()
                             || high_hue_threshold.dragged()
-                            || high_hue_threshold.changed();
+                            || high_hue_threshold.changed()
+                            || invert_checkbox.contains_pointer()
+                            || invert_checkbox.changed();
 
                         self.handle_threshold_preview_state(
                             should_display_preview,
                             FeedbackSegmentSelectionMode::HueRange {
                                 low: self.segment_selection_state.hue_range_low,
                                 high: self.segment_selection_state.hue_range_high,
+                                invert: self.segment_selection_state.hue_range_invert,
                             },
                             worker,
                             ctx,
@@ -716,32 +2042,144 @@ impl ImageProcessingSection {
                 taffy_ui
                     .style(segment_selection_mode_dropdown_style.clone())
                     .ui(|ui| {
-                        let saturation_threshold_low = ui.add(
-                            construct_precise_normalized_slider(
-                                &mut self.segment_selection_state.saturation_range_low,
-                            )
-                            .text("Low threshold"),
+                        let saturation_threshold_low = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.saturation_range_low,
+                            construct_precise_normalized_slider,
+                            "Low threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
                         );
 
-                        let saturation_threshold_high = ui.add(
-                            construct_precise_normalized_slider(
-                                &mut self.segment_selection_state.saturation_range_high,
-                            )
-                            .text("High threshold"),
+                        let saturation_threshold_high = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.saturation_range_high,
+                            construct_precise_normalized_slider,
+                            "High threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
                         );
 
+                        let invert_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.saturation_range_invert,
+                                "Invert selection",
+                            )
+                            .on_hover_text(
+                                "Selects the pixels outside of the saturation range instead.",
+                            );
+
                         let should_display_preview = saturation_threshold_low.contains_pointer()
                             || saturation_threshold_low.dragged()
                             || saturation_threshold_low.changed()
                             || saturation_threshold_high.contains_pointer()
                             || saturation_threshold_high.dragged()
-                            || saturation_threshold_high.changed();
+                            || saturation_threshold_high.changed()
+                            || invert_checkbox.contains_pointer()
+                            || invert_checkbox.changed();
 
                         self.handle_threshold_preview_state(
                             should_display_preview,
                             FeedbackSegmentSelectionMode::SaturationRange {
                                 low: self.segment_selection_state.saturation_range_low,
                                 high: self.segment_selection_state.saturation_range_high,
+                                invert: self.segment_selection_state.saturation_range_invert,
+                            },
+                            worker,
+                            ctx,
+                            state,
+                        );
+                    });
+            }
+            UiSegmentSelectionMode::AlphaRange => {
+                taffy_ui
+                    .style(segment_selection_mode_dropdown_style.clone())
+                    .ui(|ui| {
+                        let alpha_threshold_low = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.alpha_range_low,
+                            construct_precise_normalized_slider,
+                            "Low threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
+                        );
+
+                        let alpha_threshold_high = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.alpha_range_high,
+                            construct_precise_normalized_slider,
+                            "High threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
+                        );
+
+                        let should_display_preview = alpha_threshold_low.contains_pointer()
+                            || alpha_threshold_low.dragged()
+                            || alpha_threshold_low.changed()
+                            || alpha_threshold_high.contains_pointer()
+                            || alpha_threshold_high.dragged()
+                            || alpha_threshold_high.changed();
+
+                        self.handle_threshold_preview_state(
+                            should_display_preview,
+                            FeedbackSegmentSelectionMode::AlphaRange {
+                                low: self.segment_selection_state.alpha_range_low,
+                                high: self.segment_selection_state.alpha_range_high,
+                            },
+                            worker,
+                            ctx,
+                            state,
+                        );
+                    });
+            }
+            UiSegmentSelectionMode::ColorDistanceRange => {
+                taffy_ui
+                    .style(segment_selection_mode_dropdown_style.clone())
+                    .ui(|ui| {
+                        let target_color = ui
+                            .horizontal(|ui| {
+                                ui.label("Target color");
+                                ui.color_edit_button_srgb(
+                                    &mut self.segment_selection_state.color_distance_target,
+                                )
+                            })
+                            .inner;
+
+                        let color_distance_threshold_low = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.color_distance_range_low,
+                            construct_precise_normalized_slider,
+                            "Low threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
+                        );
+
+                        let color_distance_threshold_high = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.color_distance_range_high,
+                            construct_precise_normalized_slider,
+                            "High threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
+                        );
+
+                        let should_display_preview = target_color.changed()
+                            || color_distance_threshold_low.contains_pointer()
+                            || color_distance_threshold_low.dragged()
+                            || color_distance_threshold_low.changed()
+                            || color_distance_threshold_high.contains_pointer()
+                            || color_distance_threshold_high.dragged()
+                            || color_distance_threshold_high.changed();
+
+                        self.handle_threshold_preview_state(
+                            should_display_preview,
+                            FeedbackSegmentSelectionMode::ColorDistanceRange {
+                                target: srgb_to_opaque_rgba(
+                                    self.segment_selection_state.color_distance_target,
+                                ),
+                                low: self.segment_selection_state.color_distance_range_low,
+                                high: self.segment_selection_state.color_distance_range_high,
+                                distance_metric: self.segment_selection_state.color_distance_metric,
                             },
                             worker,
                             ctx,
@@ -753,20 +2191,22 @@ impl ImageProcessingSection {
                 taffy_ui
                     .style(segment_selection_mode_dropdown_style.clone())
                     .ui(|ui| {
-                        ui.add(
-                            construct_precise_custom_slider(
-                                &mut self.segment_selection_state.canny_edges_low,
-                                SMALLEST_CANNY_EDGE_THRESHOLD..=LARGEST_CANNY_EDGE_THRESHOLD,
-                            )
-                            .text("Low edge threshold"),
+                        let low_edge_threshold = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.canny_edges_low,
+                            construct_precise_canny_edge_slider,
+                            "Low edge threshold",
+                            CANNY_EDGE_THRESHOLD_SLIDER_STEP,
+                            SMALLEST_CANNY_EDGE_THRESHOLD..=LARGEST_CANNY_EDGE_THRESHOLD,
                         );
 
-                        ui.add(
-                            construct_precise_custom_slider(
-                                &mut self.segment_selection_state.canny_edges_high,
-                                SMALLEST_CANNY_EDGE_THRESHOLD..=LARGEST_CANNY_EDGE_THRESHOLD,
-                            )
-                            .text("High edge threshold"),
+                        let high_edge_threshold = add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.canny_edges_high,
+                            construct_precise_canny_edge_slider,
+                            "High edge threshold",
+                            CANNY_EDGE_THRESHOLD_SLIDER_STEP,
+                            SMALLEST_CANNY_EDGE_THRESHOLD..=LARGEST_CANNY_EDGE_THRESHOLD,
                         );
 
                         ui.add(egui::Checkbox::new(
@@ -775,10 +2215,222 @@ impl ImageProcessingSection {
                                 .canny_edges_segment_starts_on_image_edge,
                             "First segment starts on left/top of image",
                         ));
+
+                        let invert_edges_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.canny_edges_invert_edges,
+                                "Invert edges",
+                            )
+                            .on_hover_text(
+                                "Sorts the flat areas between detected edges \
+                                instead of the edges themselves.",
+                            );
+
+                        let blur_checkbox = ui
+                            .checkbox(
+                                &mut self.segment_selection_state.canny_edges_blur,
+                                "Blur before edge detection",
+                            )
+                            .on_hover_text("Suppresses edges caused by noise or fine texture.");
+
+                        let should_preview_blur_sigma =
+                            if self.segment_selection_state.canny_edges_blur {
+                                let blur_sigma = ui.add(
+                                    construct_precise_custom_slider(
+                                        &mut self.segment_selection_state.canny_edges_blur_sigma,
+                                        SMALLEST_CANNY_BLUR_SIGMA..=LARGEST_CANNY_BLUR_SIGMA,
+                                    )
+                                    .text("Blur sigma"),
+                                );
+
+                                blur_sigma.contains_pointer()
+                                    || blur_sigma.dragged()
+                                    || blur_sigma.changed()
+                            } else {
+                                false
+                            };
+
+                        let dilation_row = ui.horizontal(|ui| {
+                            let dilation = ui.add(
+                                egui::DragValue::new(
+                                    &mut self.segment_selection_state.canny_edges_dilation,
+                                )
+                                .range(0..=MAXIMUM_CANNY_EDGE_DILATION)
+                                .speed(0.1),
+                            );
+                            ui.label("Edge dilation (pixels)");
+
+                            dilation.contains_pointer() || dilation.dragged() || dilation.changed()
+                        });
+                        dilation_row.response.on_hover_text(
+                            "Widens the detected edges into thicker bands, \
+                            producing longer segments.",
+                        );
+
+                        egui::ComboBox::from_label("Segment ordering")
+                            .selected_text(segment_arrangement_label(
+                                self.segment_selection_state.canny_edges_segment_arrangement,
+                            ))
+                            .show_ui(ui, |ui| {
+                                for arrangement in SEGMENT_ARRANGEMENTS {
+                                    ui.selectable_value(
+                                        &mut self
+                                            .segment_selection_state
+                                            .canny_edges_segment_arrangement,
+                                        arrangement,
+                                        segment_arrangement_label(arrangement),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "How the sorted pixels are laid out between two detected edges.",
+                            );
+
+                        let should_display_preview = low_edge_threshold.contains_pointer()
+                            || low_edge_threshold.dragged()
+                            || low_edge_threshold.changed()
+                            || high_edge_threshold.contains_pointer()
+                            || high_edge_threshold.dragged()
+                            || high_edge_threshold.changed()
+                            || invert_edges_checkbox.changed()
+                            || blur_checkbox.changed()
+                            || should_preview_blur_sigma
+                            || dilation_row.inner;
+
+                        self.handle_threshold_preview_state(
+                            should_display_preview,
+                            FeedbackSegmentSelectionMode::CannyEdges {
+                                low: self.segment_selection_state.canny_edges_low,
+                                high: self.segment_selection_state.canny_edges_high,
+                                invert_edges: self.segment_selection_state.canny_edges_invert_edges,
+                                blur_sigma: self
+                                    .segment_selection_state
+                                    .canny_edges_blur
+                                    .then_some(self.segment_selection_state.canny_edges_blur_sigma),
+                                dilation: self.segment_selection_state.canny_edges_dilation,
+                            },
+                            worker,
+                            ctx,
+                            state,
+                        );
+                    });
+            }
+            UiSegmentSelectionMode::SobelEdges => {
+                taffy_ui
+                    .style(segment_selection_mode_dropdown_style.clone())
+                    .ui(|ui| {
+                        ui.add(
+                            construct_precise_custom_slider(
+                                &mut self.segment_selection_state.sobel_edges_threshold,
+                                0.0..=LARGEST_SOBEL_GRADIENT_MAGNITUDE,
+                            )
+                            .text("Edge threshold"),
+                        );
+                    });
+            }
+            UiSegmentSelectionMode::HighPassRange => {
+                taffy_ui
+                    .style(segment_selection_mode_dropdown_style.clone())
+                    .ui(|ui| {
+                        ui.add(
+                            construct_precise_custom_slider(
+                                &mut self.segment_selection_state.high_pass_blur_sigma,
+                                SMALLEST_HIGH_PASS_BLUR_SIGMA..=LARGEST_HIGH_PASS_BLUR_SIGMA,
+                            )
+                            .text("Blur sigma"),
+                        );
+
+                        add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.high_pass_range_low,
+                            construct_precise_normalized_slider,
+                            "Low threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
+                        );
+
+                        add_threshold_slider(
+                            ui,
+                            &mut self.segment_selection_state.high_pass_range_high,
+                            construct_precise_normalized_slider,
+                            "High threshold",
+                            NORMALIZED_SLIDER_STEP,
+                            0.0..=1.0,
+                        );
+                    });
+            }
+            UiSegmentSelectionMode::MaskImage => {
+                taffy_ui
+                    .style(segment_selection_mode_dropdown_style.clone())
+                    .ui(|ui| {
+                        let mask_file_name = state
+                            .selection_mask
+                            .as_ref()
+                            .and_then(|selection_mask| selection_mask.file_path.file_name())
+                            .map(|file_name| file_name.to_string_lossy().into_owned());
+
+                        ui.label(match mask_file_name {
+                            Some(mask_file_name) => format!("Mask: {mask_file_name}"),
+                            None => "No mask loaded".to_string(),
+                        })
+                        .on_hover_text(
+                            "Only non-black pixels of the mask are sorted. \
+                            The mask must be as large as the image.",
+                        );
+
+                        let open_mask_button = ui.add_enabled(
+                            !state.is_loading_selection_mask,
+                            egui::Button::new("Open mask"),
+                        );
+
+                        if open_mask_button.clicked() {
+                            let optionally_picked_file = new_file_dialog(state)
+                                .set_title("Open mask")
+                                .add_filter("Image", &SUPPORTED_INPUT_IMAGE_EXTENSIONS)
+                                .pick_file();
+
+                            if let Some(picked_file) = optionally_picked_file {
+                                remember_last_used_directory(state, &picked_file);
+
+                                let _ = worker.sender().send(WorkerRequest::OpenSelectionMask {
+                                    input_file_path: picked_file,
+                                });
+
+                                state.is_loading_selection_mask = true;
+                            }
+                        }
                     });
             }
         }
 
+        if self
+            .segment_selection_state
+            .to_prepared_selection_mode()
+            .selects_full_range()
+        {
+            taffy_ui
+                .style(taffy::Style {
+                    margin: taffy::Rect {
+                        left: taffy::LengthPercentageAuto::Length(0.0),
+                        right: taffy::LengthPercentageAuto::Length(0.0),
+                        top: taffy::LengthPercentageAuto::Length(6.0),
+                        bottom: taffy::LengthPercentageAuto::Length(0.0),
+                    },
+                    ..Default::default()
+                })
+                .ui(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} Full range selected: entire rows will be sorted.",
+                            egui_phosphor::regular::INFO
+                        ))
+                        .weak()
+                        .small(),
+                    )
+                });
+        }
+
         taffy_ui
             .style(taffy::Style {
                 margin: taffy::Rect {
@@ -803,31 +2455,308 @@ impl ImageProcessingSection {
                     })
             });
 
-
-
         taffy_ui
             .style(taffy::Style {
                 margin: taffy::Rect {
                     left: taffy::LengthPercentageAuto::Length(0.0),
                     right: taffy::LengthPercentageAuto::Length(0.0),
-                    top: taffy::LengthPercentageAuto::Length(18.0),
+                    top: taffy::LengthPercentageAuto::Length(4.0),
                     bottom: taffy::LengthPercentageAuto::Length(8.0),
                 },
                 ..Default::default()
             })
             .ui(|ui| -> egui::InnerResponse<Option<()>> {
-                egui::ComboBox::from_label("Segment randomization mode")
-                    .selected_text(self.randomization_state.mode.label())
+                egui::ComboBox::from_label("Tiebreak by")
+                    .selected_text(
+                        self.segment_selection_state
+                            .tiebreak_sorting_mode
+                            .map_or("none", UiSortingMode::label),
+                    )
                     .show_ui(ui, |ui| {
-                        for mode in UiSegmentRandomizationMode::modes() {
+                        ui.selectable_value(
+                            &mut self.segment_selection_state.tiebreak_sorting_mode,
+                            None,
+                            "none",
+                        );
+
+                        for mode in UiSortingMode::modes() {
                             ui.selectable_value(
-                                &mut self.randomization_state.mode,
-                                mode,
+                                &mut self.segment_selection_state.tiebreak_sorting_mode,
+                                Some(mode),
                                 mode.label(),
                             );
                         }
                     })
-            });
+            })
+            .response
+            .on_hover_text(
+                "Secondary sorting key for pixels with equal sorting values \
+                (e.g. sorting by luminance with a hue tiebreak avoids flat bands).",
+            );
+
+        let sorts_by_color_distance = self.segment_selection_state.sorting_mode
+            == UiSortingMode::ColorDistance
+            || self.segment_selection_state.tiebreak_sorting_mode
+                == Some(UiSortingMode::ColorDistance);
+
+        if sorts_by_color_distance {
+            taffy_ui
+                .style(taffy::Style {
+                    margin: taffy::Rect {
+                        left: taffy::LengthPercentageAuto::Length(0.0),
+                        right: taffy::LengthPercentageAuto::Length(0.0),
+                        top: taffy::LengthPercentageAuto::Length(4.0),
+                        bottom: taffy::LengthPercentageAuto::Length(8.0),
+                    },
+                    ..Default::default()
+                })
+                .ui(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Sorting target color");
+                        ui.color_edit_button_srgb(
+                            &mut self.segment_selection_state.sorting_color_distance_target,
+                        )
+                    })
+                    .response
+                    .on_hover_text("Pixels are sorted by how close they are to this color.")
+                });
+        }
+
+        let uses_color_distance = self.segment_selection_state.segment_selection_mode
+            == UiSegmentSelectionMode::ColorDistanceRange
+            || sorts_by_color_distance;
+
+        if uses_color_distance {
+            taffy_ui
+                .style(taffy::Style {
+                    margin: taffy::Rect {
+                        left: taffy::LengthPercentageAuto::Length(0.0),
+                        right: taffy::LengthPercentageAuto::Length(0.0),
+                        top: taffy::LengthPercentageAuto::Length(4.0),
+                        bottom: taffy::LengthPercentageAuto::Length(8.0),
+                    },
+                    ..Default::default()
+                })
+                .ui(|ui| {
+                    egui::ComboBox::from_label("Color distance metric")
+                        .selected_text(distance_metric_label(
+                            self.segment_selection_state.color_distance_metric,
+                        ))
+                        .show_ui(ui, |ui| {
+                            for metric in DISTANCE_METRICS {
+                                ui.selectable_value(
+                                    &mut self.segment_selection_state.color_distance_metric,
+                                    metric,
+                                    distance_metric_label(metric),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "How the distance between two colors is measured \
+                            when selecting or sorting by it. \
+                            OKLab follows how different the colors look much more closely.",
+                        );
+                });
+        }
+
+        let uses_luminance = (self.segment_selection_state.segment_selection_mode
+            == UiSegmentSelectionMode::LuminanceRange
+            && !self.segment_selection_state.luminance_range_as_percentiles)
+            || self.segment_selection_state.sorting_mode == UiSortingMode::Luminance
+            || self.segment_selection_state.tiebreak_sorting_mode == Some(UiSortingMode::Luminance);
+
+        if uses_luminance {
+            taffy_ui
+                .style(taffy::Style {
+                    margin: taffy::Rect {
+                        left: taffy::LengthPercentageAuto::Length(0.0),
+                        right: taffy::LengthPercentageAuto::Length(0.0),
+                        top: taffy::LengthPercentageAuto::Length(4.0),
+                        bottom: taffy::LengthPercentageAuto::Length(8.0),
+                    },
+                    ..Default::default()
+                })
+                .ui(|ui| {
+                    egui::ComboBox::from_label("Luminance weights")
+                        .selected_text(self.segment_selection_state.luminance_weights.label())
+                        .show_ui(ui, |ui| {
+                            for weights in UiLuminanceWeights::weights() {
+                                ui.selectable_value(
+                                    &mut self.segment_selection_state.luminance_weights,
+                                    weights,
+                                    weights.label(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "How much the red, green and blue channels contribute to luminance \
+                            when selecting or sorting by it. \
+                            Percentile thresholds always use Rec. 709.",
+                        );
+
+                    if self.segment_selection_state.luminance_weights == UiLuminanceWeights::Custom
+                    {
+                        ui.horizontal(|ui| {
+                            for (weight, label) in self
+                                .segment_selection_state
+                                .custom_luminance_weights
+                                .iter_mut()
+                                .zip(["R", "G", "B"])
+                            {
+                                ui.label(label);
+                                ui.add(
+                                    egui::DragValue::new(weight)
+                                        .range(0.0..=1.0)
+                                        .speed(0.001)
+                                        .max_decimals(4),
+                                );
+                            }
+                        });
+                    }
+                });
+        }
+
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(4.0),
+                    bottom: taffy::LengthPercentageAuto::Length(8.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.checkbox(&mut self.premultiply_alpha, "Premultiply alpha")
+                    .on_hover_text(
+                        "Multiplies colors by alpha before selecting and sorting pixels, \
+                        so mostly transparent pixels are treated as dark. \
+                        Useful for images destined for alpha compositing.",
+                    )
+            });
+
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(8.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.checkbox(
+                    &mut self.output_only_segments,
+                    "Output only sorted segments",
+                )
+                .on_hover_text(
+                    "Makes every pixel outside of the sorted segments fully transparent, \
+                        e.g. for layering the result over the original in an external editor.",
+                )
+            });
+
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(8.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.checkbox(&mut self.stable_sort, "Stable sort")
+                    .on_hover_text(
+                        "Keeps pixels with equal sorting keys in their original order, \
+                        which is slightly slower, but gives the same result on every run.",
+                    )
+            });
+
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(8.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut self.minimum_segment_length)
+                            .range(1..=10000)
+                            .suffix(" px"),
+                    );
+                    ui.label("Minimum segment length");
+                })
+                .response
+                .on_hover_text(
+                    "Leaves segments shorter than this unsorted, \
+                    which reduces speckle noise on noisy photos.",
+                )
+            });
+
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(0.0),
+                    bottom: taffy::LengthPercentageAuto::Length(8.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(
+                        &mut self.limit_segment_length,
+                        "Maximum segment length",
+                    );
+                    ui.add_enabled(
+                        self.limit_segment_length,
+                        egui::DragValue::new(&mut self.maximum_segment_length)
+                            .range(1..=10000)
+                            .suffix(" px"),
+                    );
+                })
+                .response
+                .on_hover_text(
+                    "Splits longer segments into consecutive pieces of this length, \
+                    so that large uniform areas don't turn into one long smear.",
+                )
+            });
+
+
+
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(18.0),
+                    bottom: taffy::LengthPercentageAuto::Length(8.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| -> egui::InnerResponse<Option<()>> {
+                egui::ComboBox::from_label("Segment randomization mode")
+                    .selected_text(self.randomization_state.mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in UiSegmentRandomizationMode::modes() {
+                            ui.selectable_value(
+                                &mut self.randomization_state.mode,
+                                mode,
+                                mode.label(),
+                            );
+                        }
+                    })
+            });
 
         let segment_randomization_mode_dropdown_style = taffy::Style {
             display: taffy::Display::Flex,
@@ -888,6 +2817,28 @@ impl ImageProcessingSection {
             }
         }
 
+        if self.randomization_state.mode != UiSegmentRandomizationMode::None {
+            taffy_ui
+                .style(segment_randomization_mode_dropdown_style)
+                .ui(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(
+                            &mut self.randomization_state.use_fixed_seed,
+                            "Fixed seed",
+                        )
+                        .on_hover_text(
+                            "Splits segments the same way on every run and, \
+                                row by row, across images (e.g. frames of a video).",
+                        );
+
+                        ui.add_enabled(
+                            self.randomization_state.use_fixed_seed,
+                            egui::DragValue::new(&mut self.randomization_state.fixed_seed),
+                        );
+                    })
+                });
+        }
+
 
         taffy_ui
             .style(taffy::Style {
@@ -913,6 +2864,29 @@ impl ImageProcessingSection {
                     })
             });
 
+        if self.segment_sorting_direction.is_angled() {
+            taffy_ui
+                .style(taffy::Style {
+                    margin: taffy::Rect {
+                        left: taffy::LengthPercentageAuto::Length(0.0),
+                        right: taffy::LengthPercentageAuto::Length(0.0),
+                        top: taffy::LengthPercentageAuto::Length(0.0),
+                        bottom: taffy::LengthPercentageAuto::Length(12.0),
+                    },
+                    ..Default::default()
+                })
+                .ui(|ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.sorting_angle_degrees, 0.0..=360.0)
+                            .text("Sorting angle (degrees)"),
+                    )
+                    .on_hover_text(
+                        "Counter-clockwise from left-to-right: 0 degrees sorts like horizontal, \
+                        90 degrees like vertical (bottom to top).",
+                    );
+                });
+        }
+
         taffy_ui
             .style(taffy::Style {
                 display: taffy::Display::Flex,
@@ -930,6 +2904,426 @@ impl ImageProcessingSection {
             .add(|taffy_ui| {
                 self.update_sorting_ui_actions(taffy_ui, worker, ctx, state);
             });
+
+        self.update_proxy_ui(taffy_ui, worker, state);
+
+        self.update_sorting_threads_ui(taffy_ui, worker);
+
+        self.update_share_code_ui(taffy_ui, ctx, state);
+
+        self.update_recipe_ui(taffy_ui, worker, state);
+    }
+
+    fn update_sorting_threads_ui(&mut self, taffy_ui: &mut Tui, worker: &WorkerHandle) {
+        taffy_ui
+            .style(taffy::Style {
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(8.0),
+                    bottom: taffy::LengthPercentageAuto::Length(2.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.horizontal(|ui| {
+                    let limit_checkbox = ui
+                        .checkbox(&mut self.limit_sorting_threads, "Limit threads")
+                        .on_hover_text(
+                            "Limits how many CPU threads pixel sorts use, \
+                            e.g. to keep the machine responsive. \
+                            By default, all available threads are used.",
+                        );
+
+                    let thread_count_drag_value = ui
+                        .add_enabled(
+                            self.limit_sorting_threads,
+                            egui::DragValue::new(&mut self.sorting_thread_count)
+                                .range(1..=self.available_thread_count)
+                                .speed(0.1)
+                                .suffix(" threads"),
+                        )
+                        .on_hover_text("The largest number of threads pixel sorts may use.");
+
+                    if limit_checkbox.changed() || thread_count_drag_value.changed() {
+                        let thread_count = self
+                            .limit_sorting_threads
+                            .then(|| NonZeroUsize::new(self.sorting_thread_count))
+                            .flatten();
+
+                        let _ = worker
+                            .sender()
+                            .send(WorkerRequest::SetSortingThreadCount { thread_count });
+                    }
+                });
+            });
+    }
+
+    fn update_proxy_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                display: taffy::Display::Flex,
+                flex_direction: taffy::FlexDirection::Column,
+                align_items: Some(taffy::AlignItems::Center),
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(12.0),
+                    bottom: taffy::LengthPercentageAuto::Length(2.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.proxy_mode, "Proxy mode")
+                        .on_hover_text(
+                            "Sorts and previews a downscaled copy of the source image, \
+                        which is much faster on large images. Use \"Render full resolution\" \
+                        to apply the final parameters to the full-size image.",
+                        );
+
+                    ui.add_enabled(
+                        self.proxy_mode,
+                        egui::DragValue::new(&mut self.proxy_max_size)
+                            .range(SMALLEST_PROXY_MAX_SIZE..=LARGEST_PROXY_MAX_SIZE)
+                            .speed(8.0)
+                            .suffix(" px"),
+                    )
+                    .on_hover_text("The largest width and height of the proxy image.");
+                });
+
+                let render_button = ui
+                    .add_enabled(
+                        self.proxy_mode
+                            && state.source_image.is_some()
+                            && !state.is_processing_image,
+                        egui::Button::new("Render full resolution"),
+                    )
+                    .on_hover_text("Sorts the full-size source image using the current parameters.")
+                    .on_disabled_hover_text(
+                        "Only available in proxy mode, once an image has been loaded.",
+                    );
+
+                if render_button.clicked()
+                    && let Some(source_image) = &state.source_image
+                {
+                    self.send_pixel_sort_request(source_image.image.clone(), worker, state);
+                }
+            });
+
+        // The proxy is (re)generated whenever it is missing or was generated for a different size.
+        if self.proxy_mode
+            && !state.is_generating_proxy_image
+            && let Some(source_image) = &state.source_image
+            && source_image
+                .proxy
+                .as_ref()
+                .is_none_or(|proxy| proxy.max_size != self.proxy_max_size)
+        {
+            let _ = worker.sender().send(WorkerRequest::GenerateProxyImage {
+                image: source_image.image.clone(),
+                max_size: self.proxy_max_size,
+            });
+
+            state.is_generating_proxy_image = true;
+        }
+    }
+
+    /// Lets the user pick a JSON file and saves the current sorting settings to it as a preset.
+    fn save_preset(&mut self, state: &mut SharedState) {
+        let Some(preset_file_path) = new_file_dialog(state)
+            .set_title("Save preset")
+            .set_file_name("preset.json")
+            .add_filter("Processing preset", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        remember_last_used_directory(state, &preset_file_path);
+
+        let preset = ProcessingPreset::new(self.to_pixel_sort_config());
+
+        self.preset_error = match fs::write(&preset_file_path, preset.to_json()) {
+            Ok(()) => None,
+            Err(error) => Some(format!("Failed to save preset: {error}.")),
+        };
+    }
+
+    /// Lets the user pick a JSON preset file and applies the sorting settings saved in it.
+    fn load_preset(&mut self, state: &mut SharedState) {
+        let Some(preset_file_path) = new_file_dialog(state)
+            .set_title("Load preset")
+            .add_filter("Processing preset", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        remember_last_used_directory(state, &preset_file_path);
+
+        let preset_result = fs::read_to_string(&preset_file_path)
+            .map_err(|error| format!("Failed to read preset: {error}."))
+            .and_then(|preset_json| {
+                ProcessingPreset::from_json(&preset_json)
+                    .map_err(|error| format!("Invalid preset: {error}."))
+            });
+
+        match preset_result {
+            Ok(preset) => {
+                self.apply_pixel_sort_config(preset.config);
+                self.preset_error = None;
+            }
+            Err(error) => {
+                self.preset_error = Some(error);
+            }
+        }
+    }
+
+    /// Lets the user pick a JSON file and saves the pixel sorts that produced
+    /// the current processed image to it as a recipe.
+    fn save_recipe(&mut self, state: &mut SharedState) {
+        let Some(processed_image) = &state.processed_image_last else {
+            return;
+        };
+
+        let recipe = ProcessingRecipe::new(processed_image.recorded_operations.clone());
+
+        let Some(recipe_file_path) = new_file_dialog(state)
+            .set_title("Save recipe")
+            .set_file_name("recipe.json")
+            .add_filter("Processing recipe", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        remember_last_used_directory(state, &recipe_file_path);
+
+        self.recipe_error = match fs::write(&recipe_file_path, recipe.to_json()) {
+            Ok(()) => None,
+            Err(error) => Some(format!("Failed to save recipe: {error}.")),
+        };
+    }
+
+    /// Lets the user pick a JSON recipe file and applies its pixel sorts, one after another,
+    /// to the image that would otherwise be processed next (see [`Self::image_to_process`]).
+    fn apply_recipe(&mut self, worker: &WorkerHandle, state: &mut SharedState) {
+        let Some(image) = self.image_to_process(state) else {
+            return;
+        };
+
+        let Some(recipe_file_path) = new_file_dialog(state)
+            .set_title("Apply recipe")
+            .add_filter("Processing recipe", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        remember_last_used_directory(state, &recipe_file_path);
+
+        let recipe_result = fs::read_to_string(&recipe_file_path)
+            .map_err(|error| format!("Failed to read recipe: {error}."))
+            .and_then(|recipe_json| {
+                ProcessingRecipe::from_json(&recipe_json)
+                    .map_err(|error| format!("Invalid recipe: {error}."))
+            });
+
+        let recipe = match recipe_result {
+            Ok(recipe) => recipe,
+            Err(error) => {
+                self.recipe_error = Some(error);
+                return;
+            }
+        };
+
+        let mut operations = recipe.operations.into_iter();
+
+        let Some(first_operation) = operations.next() else {
+            self.recipe_error = Some("The recipe contains no operations.".to_string());
+            return;
+        };
+
+        self.recipe_error = None;
+
+        state.pending_recipe_operations = operations.collect();
+        send_recorded_operation(state, worker, image, first_operation);
+    }
+
+    fn update_recipe_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        worker: &WorkerHandle,
+        state: &mut SharedState,
+    ) {
+        let number_of_recorded_operations = state
+            .processed_image_last
+            .as_ref()
+            .map(|processed_image| processed_image.recorded_operations.len())
+            .unwrap_or(0);
+
+        taffy_ui
+            .style(taffy::Style {
+                display: taffy::Display::Flex,
+                flex_direction: taffy::FlexDirection::Column,
+                align_items: Some(taffy::AlignItems::Center),
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(8.0),
+                    bottom: taffy::LengthPercentageAuto::Length(2.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.horizontal(|ui| {
+                    let save_recipe_button = ui
+                        .add_enabled(
+                            number_of_recorded_operations > 0,
+                            egui::Button::new(format!(
+                                "{} Save recipe",
+                                egui_phosphor::regular::LIST_NUMBERS
+                            )),
+                        )
+                        .on_hover_text(format!(
+                            "Saves the {number_of_recorded_operations} pixel sort(s) \
+                            that produced the current image to a JSON file.",
+                        ))
+                        .on_disabled_hover_text("No pixel sorts have been applied yet.");
+
+                    if save_recipe_button.clicked() {
+                        self.save_recipe(state);
+                    }
+
+                    let apply_recipe_button = ui
+                        .add_enabled(
+                            !state.is_processing_image && state.source_image.is_some(),
+                            egui::Button::new(format!(
+                                "{} Apply recipe",
+                                egui_phosphor::regular::PLAY
+                            )),
+                        )
+                        .on_hover_text(
+                            "Applies the pixel sorts saved in a JSON recipe file \
+                            to the current image, one after another.",
+                        );
+
+                    if apply_recipe_button.clicked() {
+                        self.apply_recipe(worker, state);
+                    }
+                });
+
+                if let Some(recipe_error) = &self.recipe_error {
+                    ui.colored_label(ui.visuals().error_fg_color, recipe_error);
+                }
+            });
+    }
+
+    fn update_share_code_ui(
+        &mut self,
+        taffy_ui: &mut Tui,
+        ctx: &egui::Context,
+        state: &mut SharedState,
+    ) {
+        taffy_ui
+            .style(taffy::Style {
+                display: taffy::Display::Flex,
+                flex_direction: taffy::FlexDirection::Column,
+                align_items: Some(taffy::AlignItems::Center),
+                margin: taffy::Rect {
+                    left: taffy::LengthPercentageAuto::Length(0.0),
+                    right: taffy::LengthPercentageAuto::Length(0.0),
+                    top: taffy::LengthPercentageAuto::Length(12.0),
+                    bottom: taffy::LengthPercentageAuto::Length(2.0),
+                },
+                ..Default::default()
+            })
+            .ui(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.share_code_input)
+                        .hint_text("Share code")
+                        .desired_width(f32::INFINITY),
+                );
+
+                ui.horizontal(|ui| {
+                    let copy_button = ui
+                        .button(format!(
+                            "{} Copy share code",
+                            egui_phosphor::regular::COPY
+                        ))
+                        .on_hover_text(
+                            "Copies a short code describing the current sorting settings.",
+                        );
+
+                    if copy_button.clicked() {
+                        let share_code = self.to_pixel_sort_config().to_share_code();
+
+                        ctx.copy_text(share_code.clone());
+                        self.share_code_input = share_code;
+                        self.share_code_error = None;
+                    }
+
+                    let paste_button = ui
+                        .button(format!(
+                            "{} Paste share code",
+                            egui_phosphor::regular::CLIPBOARD_TEXT
+                        ))
+                        .on_hover_text(
+                            "Applies the sorting settings from the code in the field above.",
+                        );
+
+                    if paste_button.clicked() {
+                        match PixelSortConfig::from_share_code(&self.share_code_input) {
+                            Ok(config) => {
+                                self.apply_pixel_sort_config(config);
+                                self.share_code_error = None;
+                            }
+                            Err(error) => {
+                                self.share_code_error =
+                                    Some(format!("Invalid share code: {error}."));
+                            }
+                        }
+                    }
+                });
+
+                if let Some(share_code_error) = &self.share_code_error {
+                    ui.colored_label(ui.visuals().error_fg_color, share_code_error);
+                }
+
+                ui.horizontal(|ui| {
+                    let save_preset_button = ui
+                        .button(format!(
+                            "{} Save preset",
+                            egui_phosphor::regular::FLOPPY_DISK
+                        ))
+                        .on_hover_text("Saves the current sorting settings to a JSON file.");
+
+                    if save_preset_button.clicked() {
+                        self.save_preset(state);
+                    }
+
+                    let load_preset_button = ui
+                        .button(format!(
+                            "{} Load preset",
+                            egui_phosphor::regular::FOLDER_OPEN
+                        ))
+                        .on_hover_text("Applies the sorting settings saved in a JSON file.");
+
+                    if load_preset_button.clicked() {
+                        self.load_preset(state);
+                    }
+                });
+
+                if let Some(preset_error) = &self.preset_error {
+                    ui.colored_label(ui.visuals().error_fg_color, preset_error);
+                }
+            });
     }
 
     pub(super) fn update(
@@ -991,6 +3385,7 @@ impl ImageProcessingSection {
                         ))
                     });
 
+                self.update_glitch_intensity_ui(taffy_ui);
                 self.update_sorting_ui(taffy_ui, worker, ctx, state);
             });
     }
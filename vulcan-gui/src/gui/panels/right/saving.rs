@@ -1,15 +1,68 @@
+use std::sync::Arc;
+
 use egui_taffy::{Tui, TuiBuilderLogic, taffy};
+use image::RgbaImage;
+use vulcan_core::io::{
+    DEFAULT_JPEG_QUALITY,
+    OutputFormat,
+    PngCompression,
+    normalize_output_file_path_for_format,
+};
 
 use crate::{
-    gui::SharedState,
+    gui::{
+        SharedState,
+        new_file_dialog,
+        panels::ConditionalDisabledTuiBuilder,
+        remember_last_used_directory,
+    },
     worker::{WorkerHandle, WorkerRequest},
 };
 
-pub struct ImageSaveSection {}
+const PNG_COMPRESSIONS: [PngCompression; 3] = [
+    PngCompression::Fast,
+    PngCompression::Balanced,
+    PngCompression::Best,
+];
+
+#[rustfmt::skip]
+fn png_compression_label(compression: PngCompression) -> &'static str {
+    match compression {
+        PngCompression::Fast => "fast (larger file)",
+        PngCompression::Balanced => "balanced",
+        PngCompression::Best => "best (smaller file)",
+    }
+}
+
+
+/// Returns the image that saving or copying exports: the processed image,
+/// or the source image if nothing has been processed yet.
+#[allow(clippy::manual_map)]
+fn image_to_export(state: &SharedState) -> Option<Arc<RgbaImage>> {
+    if let Some(processed_image_state) = &state.processed_image_last {
+        Some(processed_image_state.image.clone())
+    } else if let Some(source_image_state) = &state.source_image {
+        Some(source_image_state.image.clone())
+    } else {
+        None
+    }
+}
+
+
+pub struct ImageSaveSection {
+    /// The compression of saved PNG images.
+    png_compression: PngCompression,
+
+    /// The quality of saved JPEG images, from `1` to `100`.
+    jpeg_quality: u8,
+}
 
 impl ImageSaveSection {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            png_compression: PngCompression::default(),
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+        }
     }
 
     pub(super) fn update(
@@ -91,49 +144,99 @@ impl ImageSaveSection {
                     ));
 
                 if file_picker_button.clicked() {
-                    #[allow(clippy::manual_map)]
-                    let image_to_save =
-                        if let Some(processed_image_state) = &state.processed_image_last {
-                            Some(processed_image_state.image.clone())
-                        } else if let Some(source_image_state) = &state.source_image {
-                            Some(source_image_state.image.clone())
-                        } else {
-                            None
-                        };
-
-                    if let Some(image_to_save) = image_to_save {
-                        let starting_file_name = state
-                            .source_image
-                            .as_ref()
-                            .and_then(|source| {
-                                source
-                                    .file_path
-                                    .with_extension("png")
-                                    .file_name()
-                                    .map(|name| name.to_string_lossy().to_string())
-                            })
-                            .unwrap_or("sorted-image.png".to_string());
-
-                        let optional_output_file_path = rfd::FileDialog::new()
-                            .set_title("Save file")
-                            .set_file_name(starting_file_name)
-                            .save_file();
-
-                        if let Some(mut output_file_path) = optional_output_file_path {
-                            if output_file_path.extension().is_none() {
-                                output_file_path.set_extension("png");
-                            }
-
-                            let _ = worker.sender().send(WorkerRequest::SaveImage {
-                                image: image_to_save,
-                                output_file_path,
-                            });
-
-                            state.is_saving_image = true;
-                        }
-                    }
+                    self.save_file_dialog(state, worker);
+                }
+
+                let copy_button = taffy_ui
+                    .style(taffy::Style {
+                        min_size: taffy::Size {
+                            width: taffy::Dimension::Percent(0.75),
+                            height: taffy::Dimension::Auto,
+                        },
+                        max_size: taffy::Size {
+                            width: taffy::Dimension::Percent(1.0),
+                            height: taffy::Dimension::Length(20.0),
+                        },
+                        margin: taffy::Rect {
+                            left: taffy::LengthPercentageAuto::Length(0.0),
+                            right: taffy::LengthPercentageAuto::Length(0.0),
+                            top: taffy::LengthPercentageAuto::Length(6.0),
+                            bottom: taffy::LengthPercentageAuto::Length(0.0),
+                        },
+                        ..Default::default()
+                    })
+                    .disabled_if(
+                        (state.processed_image_last.is_none() && state.source_image.is_none())
+                            || state.is_copying_image,
+                    )
+                    .ui_add(egui::Button::new(
+                        egui::RichText::new(format!(
+                            "{} Copy to clipboard",
+                            egui_phosphor::regular::COPY
+                        ))
+                        .size(14f32),
+                    ))
+                    .on_disabled_hover_text("Load an image first.");
+
+                if copy_button.clicked()
+                    && let Some(image_to_copy) = image_to_export(state)
+                {
+                    let _ = worker.sender().send(WorkerRequest::CopyImageToClipboard {
+                        image: image_to_copy,
+                    });
+
+                    state.is_copying_image = true;
                 }
 
+                taffy_ui
+                    .style(taffy::Style {
+                        margin: taffy::Rect {
+                            left: taffy::LengthPercentageAuto::Length(0.0),
+                            right: taffy::LengthPercentageAuto::Length(0.0),
+                            top: taffy::LengthPercentageAuto::Length(8.0),
+                            bottom: taffy::LengthPercentageAuto::Length(2.0),
+                        },
+                        ..Default::default()
+                    })
+                    .ui(|ui| {
+                        egui::ComboBox::from_label("PNG compression")
+                            .selected_text(png_compression_label(self.png_compression))
+                            .show_ui(ui, |ui| {
+                                for compression in PNG_COMPRESSIONS {
+                                    ui.selectable_value(
+                                        &mut self.png_compression,
+                                        compression,
+                                        png_compression_label(compression),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "Only applies when saving as PNG. \
+                                Stronger compression produces smaller files, but takes longer.",
+                            )
+                    });
+
+                taffy_ui
+                    .style(taffy::Style {
+                        margin: taffy::Rect {
+                            left: taffy::LengthPercentageAuto::Length(0.0),
+                            right: taffy::LengthPercentageAuto::Length(0.0),
+                            top: taffy::LengthPercentageAuto::Length(4.0),
+                            bottom: taffy::LengthPercentageAuto::Length(2.0),
+                        },
+                        ..Default::default()
+                    })
+                    .ui(|ui| {
+                        ui.add(
+                            egui::Slider::new(&mut self.jpeg_quality, 1..=100).text("JPEG quality"),
+                        )
+                        .on_hover_text(
+                            "Only applies when saving as JPEG. \
+                            Lower quality produces smaller files with more artifacts.",
+                        )
+                    });
+
                 if state.is_saving_image {
                     taffy_ui
                         .style(taffy::Style {
@@ -149,4 +252,66 @@ impl ImageSaveSection {
                 }
             });
     }
+
+    /// Lets the user pick an output path and asks the worker to save the processed image
+    /// (or the source image, if nothing has been processed yet) there.
+    pub(super) fn save_file_dialog(&self, state: &mut SharedState, worker: &WorkerHandle) {
+        if let Some(image_to_save) = image_to_export(state) {
+            let starting_file_name = state
+                .source_image
+                .as_ref()
+                .and_then(|source| {
+                    source
+                        .file_path
+                        .with_extension("png")
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .unwrap_or("sorted-image.png".to_string());
+
+            let optional_output_file_path = new_file_dialog(state)
+                .set_title("Save file")
+                .set_file_name(starting_file_name)
+                .add_filter("PNG image", &["png"])
+                .add_filter("JPEG image", &["jpg", "jpeg"])
+                .add_filter("WebP image (lossless)", &["webp"])
+                .add_filter("BMP image", &["bmp"])
+                .add_filter("TIFF image", &["tiff", "tif"])
+                .save_file();
+
+            if let Some(output_file_path) = optional_output_file_path {
+                remember_last_used_directory(state, &output_file_path);
+
+                // The format is picked based on the chosen extension,
+                // falling back to PNG for unknown (or missing) extensions.
+                let format = match output_file_path
+                    .extension()
+                    .and_then(OutputFormat::from_extension)
+                {
+                    Some(OutputFormat::Png { .. }) | None => OutputFormat::Png {
+                        compression: self.png_compression,
+                    },
+                    Some(OutputFormat::Jpeg { .. }) => OutputFormat::Jpeg {
+                        quality: self.jpeg_quality,
+                    },
+                    Some(format) => format,
+                };
+
+                let _ = worker.sender().send(WorkerRequest::SaveImage {
+                    image: image_to_save,
+                    output_file_path: normalize_output_file_path_for_format(
+                        output_file_path,
+                        format,
+                    ),
+                    format,
+                    icc_profile: state
+                        .source_image
+                        .as_ref()
+                        .and_then(|source| source.icc_profile.clone()),
+                });
+
+                state.is_saving_image = true;
+            }
+        }
+    }
 }
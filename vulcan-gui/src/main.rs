@@ -1,12 +1,20 @@
+use clap::Parser;
 use eframe::NativeOptions;
 use egui::{FontDefinitions, Vec2};
 use egui_phosphor::Variant;
 use miette::miette;
 use tracing::Level;
 
-use crate::{gui::VulcanGui, worker::WorkerHandle};
+use crate::{
+    cli::{CliArgs, Command},
+    generate::cmd_generate,
+    gui::VulcanGui,
+    worker::WorkerHandle,
+};
 
 mod cancellation;
+mod cli;
+mod generate;
 mod gui;
 mod utilities;
 mod worker;
@@ -40,7 +48,7 @@ pub fn cmd_gui() -> miette::Result<()> {
             egui_phosphor::add_to_fonts(&mut fonts, Variant::Regular);
             context.egui_ctx.set_fonts(fonts);
 
-            Ok(Box::new(VulcanGui::new(worker)))
+            Ok(Box::new(VulcanGui::new(worker, context.storage)))
         }),
     )
     .map_err(|err| miette!("Failed to run eframe: {:?}", err))?;
@@ -49,7 +57,9 @@ pub fn cmd_gui() -> miette::Result<()> {
 }
 
 fn initialize_tracing() {
+    // Logs go to standard error, as `generate` can write the sorted image to standard output.
     let fmt_subscriber = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
         .with_ansi(false)
         .with_max_level(Level::INFO)
         .finish();
@@ -59,7 +69,12 @@ fn initialize_tracing() {
 }
 
 fn main() -> miette::Result<()> {
+    let args = CliArgs::parse();
+
     initialize_tracing();
 
-    cmd_gui()
+    match args.command {
+        None | Some(Command::Gui) => cmd_gui(),
+        Some(Command::Generate(generate_args)) => cmd_generate(generate_args),
+    }
 }
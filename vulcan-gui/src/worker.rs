@@ -1,29 +1,57 @@
 use std::{
+    any::Any,
+    borrow::Cow,
     fs,
     io,
-    ops::Deref,
+    num::NonZeroUsize,
+    ops::{ControlFlow, Deref},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
     thread::{self, JoinHandle},
-    time::{Duration, Instant},
+    time::Instant,
 };
 
-use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
-use image::{DynamicImage, RgbaImage};
+use arboard::{Clipboard, ImageData};
+use crossbeam_channel::{Receiver, Sender};
+use image::{DynamicImage, GrayImage, ImageFormat, RgbaImage, imageops};
+use rayon::ThreadPool;
 use thiserror::Error;
 use vulcan_core::{
-    feedback::{FeedbackSegmentSelectionMode, PIXEL_BLACK, mask_out_non_targeted_pixels},
-    io::{ImageSaveError, save_image_as_png},
+    adjustments::{
+        AdjustmentError,
+        Axis,
+        GeometricOperation,
+        adjust_brightness_contrast,
+        apply_geometric_operation,
+        invert_rgba_image,
+        mirror_tile,
+        posterize,
+        swap_channels,
+    },
+    feedback::{
+        FeedbackSegmentSelectionMode,
+        PixelPropertyMaps,
+        ThresholdPreviewStyle,
+        render_threshold_preview_with_property_maps,
+    },
+    io::{ImageSaveError, ImageSaveOptions, OutputFormat, decode_image_from_memory, save_image},
+    parallelism::build_sorting_thread_pool,
     pixel_sorting::{
+        ImageRegion,
         ImageSortingDirection,
+        builder::{PixelSortError, prepare_configured_pixel_sort},
+        config::{PixelSortConfig, RecordedOperation},
         immediate::{ImmediateSegmentSelectionMode, PixelSortOptions, perform_pixel_sort},
         prepared::{
             PreparedSegmentSelectionMode,
             PreparedSegmentSortingMode,
             SegmentRandomizationMode,
             execute_axis_aligned_prepared_pixel_sort,
-            prepare_pixel_sort,
-            randomize_prepared_segments,
+            execute_cancellable_axis_aligned_prepared_pixel_sort,
         },
     },
 };
@@ -34,6 +62,16 @@ use crate::cancellation::CancellationToken;
 pub enum WorkerRequest {
     OpenSourceImage {
         input_file_path: PathBuf,
+
+        /// Whether to rotate/flip the image into its display orientation
+        /// according to its EXIF metadata (see [`decode_image_from_memory`]).
+        respect_orientation: bool,
+    },
+
+    /// Loads the image at `input_file_path` as a grayscale selection mask
+    /// (see [`PreparedSegmentSelectionMode::MaskImage`]).
+    OpenSelectionMask {
+        input_file_path: PathBuf,
     },
 
     #[allow(dead_code)]
@@ -44,23 +82,131 @@ pub enum WorkerRequest {
     },
 
     PerformPreparedPixelSorting {
+        /// The image to sort. Sorting consumes an owned image: if the worker holds the only
+        /// reference to it, its buffer is sorted in-place, otherwise it is copied first
+        /// (e.g. when the GUI still keeps the image around as the source or for undo).
+        /// Senders that don't need the image afterwards should hand over their only [`Arc`].
         image: Arc<RgbaImage>,
         segment_selection_mode: PreparedSegmentSelectionMode,
         segment_randomization_mode: Option<SegmentRandomizationMode>,
+        segment_randomization_seed: Option<u64>,
+        minimum_segment_length: usize,
+        maximum_segment_length: Option<usize>,
         sorting_mode: PreparedSegmentSortingMode,
+        tiebreak_mode: Option<PreparedSegmentSortingMode>,
         sorting_direction: ImageSortingDirection,
+        premultiply_alpha: bool,
+        output_only_segments: bool,
+        stable_sort: bool,
+
+        /// If set, only pixels inside this region are sorted; all others are left unchanged.
+        region_of_interest: Option<ImageRegion>,
+    },
+
+    ApplyPosterization {
+        image: Arc<RgbaImage>,
+        levels_per_channel: u8,
+    },
+
+    ApplyMirrorTile {
+        image: Arc<RgbaImage>,
+        axis: Axis,
+    },
+
+    ApplyChannelSwap {
+        image: Arc<RgbaImage>,
+        mapping: [usize; 4],
+    },
+
+    /// Flips or rotates the image (see [`apply_geometric_operation`]).
+    ApplyGeometricOp {
+        image: Arc<RgbaImage>,
+        op: GeometricOperation,
+    },
+
+    /// Adds `brightness` (in channel units) to each color channel after scaling it by `contrast`
+    /// (see [`adjust_brightness_contrast`]). `0` and `1.0` leave the image unchanged.
+    AdjustBrightnessContrast {
+        image: Arc<RgbaImage>,
+        brightness: i32,
+        contrast: f32,
+    },
+
+    /// Inverts the colors of the image (see [`invert_rgba_image`]).
+    InvertColors {
+        image: Arc<RgbaImage>,
     },
 
     ShowThresholdPreview {
         image: Arc<RgbaImage>,
         method: FeedbackSegmentSelectionMode,
+        style: ThresholdPreviewStyle,
         requested_at: Instant,
     },
 
+    /// Pixel sorts a downscaled copy of `image` (at most [`LIVE_SORT_PREVIEW_MAX_SIZE`] pixels
+    /// wide and tall) and responds with it as a [`WorkerResponse::ProcessedThresholdPreview`].
+    ///
+    /// The `config` must be valid for `image` (see
+    /// [`PreparedSegmentSelectionMode::validate_for_image`]). The `region_of_interest` is given
+    /// in pixels of the full-size `image`. Segment lengths (including randomized ones) are not
+    /// scaled, so the preview is only an approximation of the full-size result.
+    ShowLiveSortPreview {
+        image: Arc<RgbaImage>,
+        config: PixelSortConfig,
+        region_of_interest: Option<ImageRegion>,
+        requested_at: Instant,
+    },
+
+    /// Downscales `image` to at most `max_size` pixels wide and tall (keeping its aspect ratio),
+    /// producing a proxy that can be processed much faster than the full-size image.
+    GenerateProxyImage {
+        image: Arc<RgbaImage>,
+        max_size: u32,
+    },
+
+    /// Loads the image at `file_path` and downscales it into a thumbnail
+    /// (at most [`THUMBNAIL_MAX_SIZE`] pixels wide and tall) for the recent files list.
+    GenerateThumbnail {
+        file_path: PathBuf,
+    },
+
     SaveImage {
         image: Arc<RgbaImage>,
         output_file_path: PathBuf,
+        format: OutputFormat,
+
+        /// The ICC color profile to embed into the saved image, if any
+        /// (usually the one of the source image).
+        icc_profile: Option<Arc<Vec<u8>>>,
     },
+
+    /// Puts the image onto the system clipboard (as raw RGBA pixels).
+    CopyImageToClipboard {
+        image: Arc<RgbaImage>,
+    },
+
+    /// Limits pixel sorts (and live sort previews) to `thread_count` threads from now on.
+    /// If `None`, they run on rayon's global thread pool, which picks the count automatically.
+    SetSortingThreadCount {
+        thread_count: Option<NonZeroUsize>,
+    },
+
+    /// Wakes up the background worker and makes it exit.
+    /// Sent by [`WorkerHandle::stop_worker_and_join`].
+    Shutdown,
+}
+
+impl WorkerRequest {
+    /// Returns when the preview was requested if this is a preview request
+    /// ([`Self::ShowThresholdPreview`] or [`Self::ShowLiveSortPreview`]), otherwise `None`.
+    fn preview_requested_at(&self) -> Option<Instant> {
+        match self {
+            Self::ShowThresholdPreview { requested_at, .. }
+            | Self::ShowLiveSortPreview { requested_at, .. } => Some(*requested_at),
+            _ => None,
+        }
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -68,14 +214,52 @@ pub enum WorkerResponse {
     OpenedSourceImage {
         file_path: PathBuf,
         image: RgbaImage,
+
+        /// The ICC color profile embedded in the opened image, if it had one.
+        icc_profile: Option<Vec<u8>>,
+
+        file_info: ImageFileInfo,
     },
 
     FailedToOpenSourceImage {
         error: ImageLoadError,
     },
 
+    OpenedSelectionMask {
+        file_path: PathBuf,
+        mask: GrayImage,
+    },
+
+    FailedToOpenSelectionMask {
+        error: ImageLoadError,
+    },
+
     ProcessedImage {
         image: RgbaImage,
+
+        /// The pixel sort that produced the image, if it was produced by
+        /// [`WorkerRequest::PerformPreparedPixelSorting`] (and not e.g. by an adjustment).
+        operation: Option<RecordedOperation>,
+    },
+
+    /// How far along the currently running pixel sort is, as a fraction from `0.0` to `1.0`.
+    /// Sent repeatedly while sorting, before the final [`WorkerResponse::ProcessedImage`].
+    ProcessingProgress {
+        fraction: f32,
+    },
+
+    /// The processing was cancelled (see [`WorkerHandle::cancel_current_operation`])
+    /// before it finished, so there is no new processed image.
+    CancelledProcessing,
+
+    /// The pixel sort couldn't be started, e.g. because the selection mask
+    /// doesn't match the image.
+    FailedToSortImage {
+        error: PixelSortError,
+    },
+
+    FailedToAdjustImage {
+        error: AdjustmentError,
     },
 
     ProcessedThresholdPreview {
@@ -83,6 +267,23 @@ pub enum WorkerResponse {
         requested_at: Instant,
     },
 
+    GeneratedProxyImage {
+        /// The image the proxy was generated from (see [`WorkerRequest::GenerateProxyImage`]).
+        source_image: Arc<RgbaImage>,
+        max_size: u32,
+        proxy_image: RgbaImage,
+    },
+
+    GeneratedThumbnail {
+        file_path: PathBuf,
+        thumbnail: RgbaImage,
+    },
+
+    FailedToGenerateThumbnail {
+        file_path: PathBuf,
+        error: ImageLoadError,
+    },
+
     SavedImage {
         output_file_path: PathBuf,
     },
@@ -90,12 +291,31 @@ pub enum WorkerResponse {
     FailedToSaveImage {
         error: ImageSaveError,
     },
+
+    CopiedImageToClipboard,
+
+    FailedToCopyImageToClipboard {
+        error: arboard::Error,
+    },
+
+    /// Handling a request panicked (i.e. hit a bug), so the request has no other response.
+    /// The worker itself keeps running and handles the following requests as usual.
+    OperationPanicked {
+        message: String,
+    },
 }
 
+/// The largest width and height of thumbnails generated by [`WorkerRequest::GenerateThumbnail`].
+pub const THUMBNAIL_MAX_SIZE: u32 = 128;
+
+/// The largest width and height of images sorted by [`WorkerRequest::ShowLiveSortPreview`].
+pub const LIVE_SORT_PREVIEW_MAX_SIZE: u32 = 512;
+
 pub struct WorkerHandle {
     request_sender: Sender<WorkerRequest>,
     response_receiver: Receiver<WorkerResponse>,
     background_thread_cancellation_token: CancellationToken,
+    operation_cancellation_token: CancellationToken,
     background_thread_join_handle: JoinHandle<()>,
 }
 
@@ -107,11 +327,15 @@ impl WorkerHandle {
         let cancellation_token = CancellationToken::new();
         let cancellation_token_clone = cancellation_token.clone();
 
+        let operation_cancellation_token = CancellationToken::new();
+        let operation_cancellation_token_clone = operation_cancellation_token.clone();
+
         let background_thread_join_handle = thread::spawn(move || {
             background_worker_loop(
                 req_receiver,
                 resp_sender,
                 cancellation_token_clone,
+                operation_cancellation_token_clone,
             );
         });
 
@@ -119,6 +343,7 @@ impl WorkerHandle {
             request_sender: req_sender,
             response_receiver: resp_receiver,
             background_thread_cancellation_token: cancellation_token,
+            operation_cancellation_token,
             background_thread_join_handle,
         }
     }
@@ -131,10 +356,23 @@ impl WorkerHandle {
         &self.response_receiver
     }
 
+    /// Asks the worker to abort the pixel sort it is currently performing, if any.
+    /// The worker then responds with [`WorkerResponse::CancelledProcessing`].
+    ///
+    /// This doesn't go through the request channel, as the worker only receives
+    /// the next request once it has finished the current one.
+    pub fn cancel_current_operation(&self) {
+        self.operation_cancellation_token.cancel();
+    }
+
     #[allow(dead_code)]
     pub fn stop_worker_and_join(self) {
         self.background_thread_cancellation_token.cancel();
 
+        // The worker blocks while waiting for requests, so we wake it up. If the worker has
+        // already exited, the channel is disconnected and there is nobody left to wake up.
+        let _ = self.request_sender.send(WorkerRequest::Shutdown);
+
         self.background_thread_join_handle
             .join()
             .expect("background worker thread has panicked");
@@ -156,157 +394,901 @@ pub enum ImageLoadError {
     },
 }
 
-fn load_image_from_path(path: &Path) -> Result<RgbaImage, ImageLoadError> {
+/// Information about the file an image was loaded from.
+#[derive(Clone, Copy, Debug)]
+pub struct ImageFileInfo {
+    /// The size of the file on disk, in bytes.
+    pub file_size: u64,
+
+    /// The format the image was decoded from, if it could be detected from the file contents.
+    pub format: Option<ImageFormat>,
+}
+
+/// An image loaded by [`load_image_from_path`].
+struct LoadedImage {
+    image: RgbaImage,
+
+    /// The ICC color profile embedded in the image, if it has one
+    /// and if it still applies to the image after the conversion to RGBA8.
+    icc_profile: Option<Vec<u8>>,
+
+    file_info: ImageFileInfo,
+}
+
+/// Loads the image at `path` and converts it to RGBA8 (see [`decode_image_from_memory`]).
+fn load_image_from_path(
+    path: &Path,
+    respect_orientation: bool,
+) -> Result<LoadedImage, ImageLoadError> {
     let loaded_file_bytes =
         fs::read(path).map_err(|error| ImageLoadError::FileReadError { error })?;
 
-    let parsed_image = image::load_from_memory(&loaded_file_bytes)
+    let decoded_image = decode_image_from_memory(&loaded_file_bytes, respect_orientation)
         .map_err(|error| ImageLoadError::ImageParseError { error })?;
 
-    let image_as_rgba8 = parsed_image.to_rgba8();
+    let file_info = ImageFileInfo {
+        file_size: loaded_file_bytes.len() as u64,
+        format: image::guess_format(&loaded_file_bytes).ok(),
+    };
+
+    Ok(LoadedImage {
+        image: decoded_image.image,
+        icc_profile: decoded_image.icc_profile,
+        file_info,
+    })
+}
+
+/// Caches the pixel property maps of the image that threshold previews were last rendered for,
+/// so that previews rendered while dragging a threshold slider don't recompute them every frame.
+///
+/// # Invalidation
+/// The cache is keyed by the identity of the image's [`Arc`] allocation (see [`Arc::ptr_eq`]).
+/// Images behind an [`Arc`] are never modified in-place: processing, undo and reset
+/// all produce (or switch to) a different [`Arc`]. As such, a request for any other image
+/// simply replaces the cached maps, and there is no need for an explicit dirty flag.
+///
+/// The cache holds on to a clone of the [`Arc`] itself (not just its address), so the image
+/// can't be freed while it is cached. This guards against stale entries: e.g. after an undo
+/// drops the latest processed image, a new image could otherwise be allocated at the same
+/// address and be mistaken for the cached one.
+struct PixelPropertyMapCache {
+    cached: Option<(Arc<RgbaImage>, PixelPropertyMaps)>,
+}
+
+impl PixelPropertyMapCache {
+    fn new() -> Self {
+        Self { cached: None }
+    }
+
+    /// Returns the property maps for the given `image`, replacing the cached ones
+    /// if they belong to a different image.
+    fn maps_for_image(&mut self, image: &Arc<RgbaImage>) -> &mut PixelPropertyMaps {
+        let is_cached = self
+            .cached
+            .as_ref()
+            .is_some_and(|(cached_image, _)| Arc::ptr_eq(cached_image, image));
+
+        if !is_cached {
+            self.cached = None;
+        }
+
+        let (_, property_maps) = self
+            .cached
+            .get_or_insert_with(|| (image.clone(), PixelPropertyMaps::new()));
+
+        property_maps
+    }
+}
+
+/// Puts the given `image` onto the system clipboard, connecting to the clipboard first
+/// if `clipboard` is `None`.
+///
+/// The connection is kept around (instead of being dropped right away) because on some platforms
+/// (e.g. X11) the clipboard contents are served by their owner for as long as it's alive.
+fn copy_image_to_clipboard(
+    clipboard: &mut Option<Clipboard>,
+    image: &RgbaImage,
+) -> Result<(), arboard::Error> {
+    let clipboard = match clipboard {
+        Some(clipboard) => clipboard,
+        None => clipboard.insert(Clipboard::new()?),
+    };
 
-    Ok(image_as_rgba8)
+    clipboard.set_image(ImageData {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        bytes: Cow::Borrowed(image.as_raw()),
+    })
+}
+
+/// Returns the dimensions of `image` scaled down (keeping its aspect ratio)
+/// to fit into `max_size`. Images that already fit keep their dimensions.
+fn downscaled_dimensions(image: &RgbaImage, max_size: u32) -> (u32, u32) {
+    let largest_side = image.width().max(image.height());
+
+    if largest_side <= max_size {
+        return image.dimensions();
+    }
+
+    let scale = max_size as f32 / largest_side as f32;
+    let scale_side = |side: u32| ((side as f32 * scale).round() as u32).max(1);
+
+    (
+        scale_side(image.width()),
+        scale_side(image.height()),
+    )
+}
+
+/// Returns a copy of `image` scaled down to [`downscaled_dimensions`].
+fn downscale_image(image: &RgbaImage, max_size: u32) -> RgbaImage {
+    let (width, height) = downscaled_dimensions(image, max_size);
+
+    if image.dimensions() == (width, height) {
+        image.clone()
+    } else {
+        imageops::thumbnail(image, width, height)
+    }
+}
+
+/// Resizes the selection masks of the mask image selection mode (if used, including
+/// inside combined selection modes) to the given `dimensions`. Empty masks and other
+/// selection modes are left unchanged.
+pub fn resize_selection_mask(
+    selection_mode: &mut PreparedSegmentSelectionMode,
+    dimensions: (u32, u32),
+) {
+    match selection_mode {
+        // Nearest-neighbour sampling keeps the mask's selection boundaries sharp.
+        PreparedSegmentSelectionMode::MaskImage { mask }
+            if !mask.is_empty() && mask.dimensions() != dimensions =>
+        {
+            *mask = Arc::new(imageops::resize(
+                mask.as_ref(),
+                dimensions.0,
+                dimensions.1,
+                imageops::FilterType::Nearest,
+            ));
+        }
+        PreparedSegmentSelectionMode::Combined { first, second, .. } => {
+            resize_selection_mask(first, dimensions);
+            resize_selection_mask(second, dimensions);
+        }
+        _ => {}
+    }
+}
+
+/// Scales the pixel `region` of an image sized `from_dimensions`
+/// to the same relative region of an image sized `to_dimensions`.
+fn scale_image_region(
+    region: ImageRegion,
+    from_dimensions: (u32, u32),
+    to_dimensions: (u32, u32),
+) -> ImageRegion {
+    let scale_x = |x: u32| {
+        ((x as f32 * to_dimensions.0 as f32 / from_dimensions.0 as f32).round() as u32)
+            .min(to_dimensions.0)
+    };
+    let scale_y = |y: u32| {
+        ((y as f32 * to_dimensions.1 as f32 / from_dimensions.1 as f32).round() as u32)
+            .min(to_dimensions.1)
+    };
+
+    let left = scale_x(region.x);
+    let top = scale_y(region.y);
+    let right = scale_x(region.x.saturating_add(region.width));
+    let bottom = scale_y(region.y.saturating_add(region.height));
+
+    ImageRegion {
+        x: left,
+        y: top,
+        width: right.saturating_sub(left),
+        height: bottom.saturating_sub(top),
+    }
+}
+
+/// Downscales `image` (and the selection mask and region of interest along with it, if any)
+/// to at most [`LIVE_SORT_PREVIEW_MAX_SIZE`] and pixel sorts it as described by `config`.
+fn render_live_sort_preview(
+    image: &RgbaImage,
+    mut config: PixelSortConfig,
+    region_of_interest: Option<ImageRegion>,
+) -> RgbaImage {
+    let preview_image = downscale_image(image, LIVE_SORT_PREVIEW_MAX_SIZE);
+
+    resize_selection_mask(
+        &mut config.selection_mode,
+        preview_image.dimensions(),
+    );
+
+    let region_of_interest = region_of_interest.map(|region| {
+        scale_image_region(
+            region,
+            image.dimensions(),
+            preview_image.dimensions(),
+        )
+    });
+
+    // The GUI only requests previews of valid configurations, but should the downscaled
+    // selection mask still be rejected, the unsorted preview is shown instead.
+    let prepared_sort =
+        match prepare_configured_pixel_sort(preview_image, &config, region_of_interest) {
+            Ok(prepared_sort) => prepared_sort,
+            Err(error) => {
+                tracing::warn!("Failed to prepare live sort preview: {error}");
+                return downscale_image(image, LIVE_SORT_PREVIEW_MAX_SIZE);
+            }
+        };
+
+    execute_axis_aligned_prepared_pixel_sort(
+        prepared_sort,
+        config.output_only_segments,
+        config.stable_sort,
+    )
+}
+
+/// Runs `operation` inside `sorting_thread_pool` if one has been configured
+/// (see [`WorkerRequest::SetSortingThreadCount`]), or directly (i.e. on rayon's
+/// global thread pool) otherwise.
+fn run_in_sorting_thread_pool<R, F>(sorting_thread_pool: Option<&ThreadPool>, operation: F) -> R
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    match sorting_thread_pool {
+        Some(sorting_thread_pool) => sorting_thread_pool.install(operation),
+        None => operation(),
+    }
+}
+
+/// Dequeues the preview requests queued directly after `preview_request` (e.g. while
+/// a slider is being dragged) and returns the newest of them (by `requested_at`), as rendering
+/// the older ones would only waste time. If a request that isn't a preview is dequeued
+/// in the process, it is returned as well, so that it can be handled next.
+fn coalesce_queued_preview_requests(
+    preview_request: WorkerRequest,
+    request_receiver: &Receiver<WorkerRequest>,
+) -> (WorkerRequest, Option<WorkerRequest>) {
+    let mut newest_preview_request = preview_request;
+
+    while let Ok(next_request) = request_receiver.try_recv() {
+        let Some(next_requested_at) = next_request.preview_requested_at() else {
+            return (newest_preview_request, Some(next_request));
+        };
+
+        if newest_preview_request
+            .preview_requested_at()
+            .is_none_or(|newest_requested_at| next_requested_at >= newest_requested_at)
+        {
+            newest_preview_request = next_request;
+        }
+    }
+
+    (newest_preview_request, None)
+}
+
+/// The state the background worker keeps between requests, see [`handle_worker_request`].
+struct BackgroundWorkerState {
+    property_map_cache: PixelPropertyMapCache,
+    clipboard: Option<Clipboard>,
+    sorting_thread_pool: Option<ThreadPool>,
+}
+
+/// Extracts the message of a panic from its payload (see [`panic::catch_unwind`]).
+fn panic_payload_to_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Handles a single request, sending the response(s) to `response_sender`.
+///
+/// Returns [`ControlFlow::Break`] if the worker should exit, i.e. on
+/// [`WorkerRequest::Shutdown`] or if the response channel is disconnected.
+fn handle_worker_request(
+    request: WorkerRequest,
+    response_sender: &Sender<WorkerResponse>,
+    operation_cancellation_token: &CancellationToken,
+    worker_state: &mut BackgroundWorkerState,
+) -> ControlFlow<()> {
+    let BackgroundWorkerState {
+        property_map_cache,
+        clipboard,
+        sorting_thread_pool,
+    } = worker_state;
+
+    match request {
+        WorkerRequest::Shutdown => {
+            tracing::debug!("Received shutdown request, exiting background worker.");
+            return ControlFlow::Break(());
+        }
+        WorkerRequest::SetSortingThreadCount { thread_count } => {
+            *sorting_thread_pool = thread_count.and_then(|thread_count| {
+                build_sorting_thread_pool(thread_count)
+                    .inspect_err(|error| {
+                        tracing::error!(
+                            "Failed to build sorting thread pool, \
+                            falling back to the global thread pool: {error}"
+                        );
+                    })
+                    .ok()
+            });
+        }
+        WorkerRequest::OpenSourceImage {
+            input_file_path: file_path,
+            respect_orientation,
+        } => {
+            let loaded_image_result = load_image_from_path(&file_path, respect_orientation);
+
+            let response_result = match loaded_image_result {
+                Ok(LoadedImage {
+                    image,
+                    icc_profile,
+                    file_info,
+                }) => response_sender.send(WorkerResponse::OpenedSourceImage {
+                    image,
+                    file_path,
+                    icc_profile,
+                    file_info,
+                }),
+                Err(error) => {
+                    response_sender.send(WorkerResponse::FailedToOpenSourceImage { error })
+                }
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::OpenSelectionMask {
+            input_file_path: file_path,
+        } => {
+            // Masks are usually painted over the displayed image,
+            // so they are loaded in their display orientation as well.
+            let response_result = match load_image_from_path(&file_path, true) {
+                Ok(LoadedImage { image, .. }) => {
+                    response_sender.send(WorkerResponse::OpenedSelectionMask {
+                        file_path,
+                        mask: DynamicImage::ImageRgba8(image).into_luma8(),
+                    })
+                }
+                Err(error) => {
+                    response_sender.send(WorkerResponse::FailedToOpenSelectionMask { error })
+                }
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::PerformImmediatePixelSorting {
+            image,
+            method,
+            options,
+        } => {
+            let sorted_image = run_in_sorting_thread_pool(sorting_thread_pool.as_ref(), || {
+                perform_pixel_sort(Arc::unwrap_or_clone(image), method, options)
+            });
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedImage {
+                image: sorted_image,
+                operation: None,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::PerformPreparedPixelSorting {
+            image,
+            segment_selection_mode,
+            segment_randomization_mode,
+            segment_randomization_seed,
+            minimum_segment_length,
+            maximum_segment_length,
+            sorting_mode,
+            tiebreak_mode,
+            sorting_direction,
+            premultiply_alpha,
+            output_only_segments,
+            stable_sort,
+            region_of_interest,
+        } => {
+            let config = PixelSortConfig {
+                selection_mode: segment_selection_mode,
+                randomization_mode: segment_randomization_mode,
+                randomization_seed: segment_randomization_seed,
+                minimum_segment_length,
+                maximum_segment_length,
+                sorting_mode,
+                tiebreak_mode,
+                direction: sorting_direction,
+                premultiply_alpha,
+                output_only_segments,
+                stable_sort,
+            };
+
+            let operation = RecordedOperation {
+                config: config.clone(),
+                region_of_interest,
+            };
+
+            // Progress is only reported in whole percent,
+            // so that we don't flood the response channel with updates.
+            let last_reported_percent = AtomicU32::new(0);
+
+            let sort_result = run_in_sorting_thread_pool(sorting_thread_pool.as_ref(), || {
+                let prepared_sort = prepare_configured_pixel_sort(
+                    Arc::unwrap_or_clone(image),
+                    &config,
+                    region_of_interest,
+                )?;
+
+                // DEBUGONLY
+                // println!("prepared: {prepared_sort:?}");
+
+                let sorted_image = execute_cancellable_axis_aligned_prepared_pixel_sort(
+                    prepared_sort,
+                    output_only_segments,
+                    stable_sort,
+                    || operation_cancellation_token.is_cancelled(),
+                    |fraction| {
+                        let percent = (fraction * 100.0) as u32;
+
+                        if last_reported_percent.fetch_max(percent, Ordering::Relaxed) < percent {
+                            let _ = response_sender.send(WorkerResponse::ProcessingProgress {
+                                fraction: percent as f32 / 100.0,
+                            });
+                        }
+                    },
+                );
+
+                Ok(sorted_image)
+            });
+
+            let response_result = match sort_result {
+                Ok(Some(sorted_image)) => response_sender.send(WorkerResponse::ProcessedImage {
+                    image: sorted_image,
+                    operation: Some(operation),
+                }),
+                Ok(None) => {
+                    tracing::debug!("Prepared pixel sort was cancelled.");
+                    response_sender.send(WorkerResponse::CancelledProcessing)
+                }
+                Err(error) => response_sender.send(WorkerResponse::FailedToSortImage { error }),
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::ApplyPosterization {
+            image,
+            levels_per_channel,
+        } => {
+            let mut image_copy = Arc::unwrap_or_clone(image);
+
+            posterize(&mut image_copy, levels_per_channel);
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedImage {
+                image: image_copy,
+                operation: None,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::ApplyMirrorTile { image, axis } => {
+            let tiled_image = mirror_tile(&image, axis);
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedImage {
+                image: tiled_image,
+                operation: None,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::ApplyChannelSwap { image, mapping } => {
+            let mut image_copy = Arc::unwrap_or_clone(image);
+
+            let response_result = match swap_channels(&mut image_copy, mapping) {
+                Ok(()) => response_sender.send(WorkerResponse::ProcessedImage {
+                    image: image_copy,
+                    operation: None,
+                }),
+                Err(error) => response_sender.send(WorkerResponse::FailedToAdjustImage { error }),
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::ApplyGeometricOp { image, op } => {
+            let transformed_image = apply_geometric_operation(&image, op);
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedImage {
+                image: transformed_image,
+                operation: None,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::AdjustBrightnessContrast {
+            image,
+            brightness,
+            contrast,
+        } => {
+            let mut image_copy = Arc::unwrap_or_clone(image);
+
+            adjust_brightness_contrast(&mut image_copy, brightness as f32, contrast);
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedImage {
+                image: image_copy,
+                operation: None,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::InvertColors { image } => {
+            let mut image_copy = Arc::unwrap_or_clone(image);
+
+            invert_rgba_image(&mut image_copy);
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedImage {
+                image: image_copy,
+                operation: None,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::ShowThresholdPreview {
+            image,
+            method,
+            style,
+            requested_at,
+        } => {
+            let mut image_copy = image.deref().to_owned();
+
+            render_threshold_preview_with_property_maps(
+                &mut image_copy,
+                method,
+                style,
+                property_map_cache.maps_for_image(&image),
+            );
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedThresholdPreview {
+                image: image_copy,
+                requested_at,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::ShowLiveSortPreview {
+            image,
+            config,
+            region_of_interest,
+            requested_at,
+        } => {
+            let preview_image = run_in_sorting_thread_pool(sorting_thread_pool.as_ref(), || {
+                render_live_sort_preview(&image, config, region_of_interest)
+            });
+
+            let response_result = response_sender.send(WorkerResponse::ProcessedThresholdPreview {
+                image: preview_image,
+                requested_at,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::GenerateProxyImage { image, max_size } => {
+            let proxy_image = downscale_image(&image, max_size);
+
+            let response_result = response_sender.send(WorkerResponse::GeneratedProxyImage {
+                source_image: image,
+                max_size,
+                proxy_image,
+            });
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::GenerateThumbnail { file_path } => {
+            // Thumbnails are only ever displayed, so they always use the display orientation.
+            let response_result = match load_image_from_path(&file_path, true) {
+                Ok(LoadedImage { image, .. }) => {
+                    let thumbnail = DynamicImage::ImageRgba8(image)
+                        .thumbnail(THUMBNAIL_MAX_SIZE, THUMBNAIL_MAX_SIZE)
+                        .to_rgba8();
+
+                    response_sender.send(WorkerResponse::GeneratedThumbnail {
+                        file_path,
+                        thumbnail,
+                    })
+                }
+                Err(error) => response_sender
+                    .send(WorkerResponse::FailedToGenerateThumbnail { file_path, error }),
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::SaveImage {
+            image,
+            output_file_path,
+            format,
+            icc_profile,
+        } => {
+            let save_result = save_image(
+                &DynamicImage::ImageRgba8(image.deref().to_owned()),
+                &output_file_path,
+                format,
+                ImageSaveOptions {
+                    overwrite_existing: false,
+                    icc_profile: icc_profile.as_deref().map(Vec::as_slice),
+                },
+            );
+
+            let response_result = match save_result {
+                Ok(_) => response_sender.send(WorkerResponse::SavedImage { output_file_path }),
+                Err(error) => response_sender.send(WorkerResponse::FailedToSaveImage { error }),
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+        WorkerRequest::CopyImageToClipboard { image } => {
+            let response_result = match copy_image_to_clipboard(clipboard, &image) {
+                Ok(()) => response_sender.send(WorkerResponse::CopiedImageToClipboard),
+                Err(error) => {
+                    response_sender.send(WorkerResponse::FailedToCopyImageToClipboard { error })
+                }
+            };
+
+            if response_result.is_err() {
+                tracing::error!("Background worker's response channel is disconnected.");
+                return ControlFlow::Break(());
+            }
+        }
+    }
+
+    ControlFlow::Continue(())
 }
 
 fn background_worker_loop(
     request_receiver: Receiver<WorkerRequest>,
     response_sender: Sender<WorkerResponse>,
     cancellation_token: CancellationToken,
+    operation_cancellation_token: CancellationToken,
 ) {
+    let mut worker_state = BackgroundWorkerState {
+        property_map_cache: PixelPropertyMapCache::new(),
+        clipboard: None,
+        sorting_thread_pool: None,
+    };
+
+    // A request that was dequeued while coalescing preview requests, to be handled next.
+    let mut deferred_request: Option<WorkerRequest> = None;
+
     loop {
         if cancellation_token.is_cancelled() {
             tracing::debug!("Cancellation token is set, exiting background worker.");
             break;
         }
 
-        let request_result = request_receiver.recv_timeout(Duration::from_millis(50));
-        let request = match request_result {
-            Ok(request) => request,
-            Err(error) => match error {
-                RecvTimeoutError::Timeout => continue,
-                RecvTimeoutError::Disconnected => {
+        // A cancellation only applies to the operation that was running when it was requested.
+        operation_cancellation_token.reset();
+
+        let request = match deferred_request.take() {
+            Some(deferred_request) => deferred_request,
+            None => {
+                let Ok(request) = request_receiver.recv() else {
                     tracing::error!(
                         "Background worker's request channel is empty and disconnected."
                     );
                     break;
-                }
-            },
-        };
-
-        match request {
-            WorkerRequest::OpenSourceImage {
-                input_file_path: file_path,
-            } => {
-                let loaded_image_result = load_image_from_path(&file_path);
-
-                let response_result = match loaded_image_result {
-                    Ok(image) => {
-                        response_sender.send(WorkerResponse::OpenedSourceImage { image, file_path })
-                    }
-                    Err(error) => {
-                        response_sender.send(WorkerResponse::FailedToOpenSourceImage { error })
-                    }
                 };
 
-                if response_result.is_err() {
-                    tracing::error!("Background worker's response channel is disconnected.");
-                    break;
-                }
+                request
             }
-            WorkerRequest::PerformImmediatePixelSorting {
-                image,
-                method,
-                options,
-            } => {
-                let image_copy = image.deref().to_owned();
-                let sorted_image = perform_pixel_sort(image_copy, method, options);
+        };
 
-                let response_result = response_sender.send(WorkerResponse::ProcessedImage {
-                    image: sorted_image,
-                });
+        let request = if request.preview_requested_at().is_some() {
+            let (newest_preview_request, next_request) =
+                coalesce_queued_preview_requests(request, &request_receiver);
+
+            deferred_request = next_request;
+            newest_preview_request
+        } else {
+            request
+        };
+
+        // Requests are handled in isolation, so that a bug in one operation (e.g. in sorting)
+        // is reported to the GUI instead of silently killing the worker.
+        let handling_result = panic::catch_unwind(AssertUnwindSafe(|| {
+            handle_worker_request(
+                request,
+                &response_sender,
+                &operation_cancellation_token,
+                &mut worker_state,
+            )
+        }));
 
-                if response_result.is_err() {
+        match handling_result {
+            Ok(ControlFlow::Continue(())) => {}
+            Ok(ControlFlow::Break(())) => break,
+            Err(panic_payload) => {
+                let message = panic_payload_to_message(panic_payload.as_ref());
+                tracing::error!("Background worker panicked while handling a request: {message}");
+
+                // The panic may have left the cached maps half-updated.
+                worker_state.property_map_cache = PixelPropertyMapCache::new();
+
+                if response_sender
+                    .send(WorkerResponse::OperationPanicked { message })
+                    .is_err()
+                {
                     tracing::error!("Background worker's response channel is disconnected.");
                     break;
                 }
             }
-            WorkerRequest::PerformPreparedPixelSorting {
-                image,
-                segment_selection_mode,
-                segment_randomization_mode,
-                sorting_mode,
-                sorting_direction,
-            } => {
-                let image_copy = image.deref().to_owned();
+        }
+    }
+}
 
-                let prepared_sort = prepare_pixel_sort(
-                    image_copy,
-                    segment_selection_mode,
-                    sorting_mode,
-                    sorting_direction,
-                );
 
-                let prepared_sort =
-                    if let Some(segment_randomization_mode) = segment_randomization_mode {
-                        randomize_prepared_segments(prepared_sort, segment_randomization_mode)
-                    } else {
-                        prepared_sort
-                    };
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-                // DEBUGONLY
-                // println!("prepared: {prepared_sort:?}");
+    use image::{Rgba, RgbaImage};
+    use vulcan_core::{
+        io::PngCompression,
+        pixel_sorting::{
+            ImageSortingDirection,
+            PixelSegmentSortDirection,
+            prepared::{PreparedSegmentSelectionMode, PreparedSegmentSortingMode},
+        },
+    };
 
-                let sorted_image = execute_axis_aligned_prepared_pixel_sort(prepared_sort);
+    use super::*;
 
-                let response_result = response_sender.send(WorkerResponse::ProcessedImage {
-                    image: sorted_image,
-                });
+    const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
 
-                if response_result.is_err() {
-                    tracing::error!("Background worker's response channel is disconnected.");
-                    break;
-                }
-            }
-            WorkerRequest::ShowThresholdPreview {
-                image,
-                method,
-                requested_at,
-            } => {
-                let mut image_copy = image.deref().to_owned();
+    fn receive_response(worker: &WorkerHandle) -> WorkerResponse {
+        worker
+            .receiver()
+            .recv_timeout(RESPONSE_TIMEOUT)
+            .expect("worker should respond in time")
+    }
 
-                mask_out_non_targeted_pixels(&mut image_copy, method, PIXEL_BLACK);
+    #[test]
+    fn worker_opens_sorts_and_saves_image() {
+        let temporary_directory =
+            tempfile::tempdir().expect("failed to create temporary directory");
+        let input_file_path = temporary_directory.path().join("input.png");
+        let output_file_path = temporary_directory.path().join("output.png");
 
-                let response_result =
-                    response_sender.send(WorkerResponse::ProcessedThresholdPreview {
-                        image: image_copy,
-                        requested_at,
-                    });
+        let input_image = RgbaImage::from_fn(16, 8, |x, y| {
+            Rgba([(x * 16) as u8, (y * 32) as u8, 128, u8::MAX])
+        });
+        input_image
+            .save(&input_file_path)
+            .expect("failed to write input image");
 
-                if response_result.is_err() {
-                    tracing::error!("Background worker's response channel is disconnected.");
-                    break;
-                }
-            }
-            WorkerRequest::SaveImage {
-                image,
-                output_file_path,
+        let worker = WorkerHandle::initialize();
+
+        worker
+            .sender()
+            .send(WorkerRequest::OpenSourceImage {
+                input_file_path: input_file_path.clone(),
+                respect_orientation: true,
+            })
+            .expect("failed to send request");
+
+        let opened_image = match receive_response(&worker) {
+            WorkerResponse::OpenedSourceImage {
+                file_path, image, ..
             } => {
-                let save_result = save_image_as_png(
-                    &DynamicImage::ImageRgba8(image.deref().to_owned()),
-                    &output_file_path,
-                    false,
-                );
+                assert_eq!(file_path, input_file_path);
+                image
+            }
+            _ => panic!("expected an OpenedSourceImage response"),
+        };
+        assert_eq!(opened_image, input_image);
 
-                let response_result = match save_result {
-                    Ok(_) => response_sender.send(WorkerResponse::SavedImage { output_file_path }),
-                    Err(error) => response_sender.send(WorkerResponse::FailedToSaveImage { error }),
-                };
+        worker
+            .sender()
+            .send(WorkerRequest::PerformPreparedPixelSorting {
+                image: Arc::new(opened_image),
+                segment_selection_mode: PreparedSegmentSelectionMode::LuminanceRange {
+                    low: 0.0,
+                    high: 1.0,
+                    invert: false,
+                },
+                segment_randomization_mode: None,
+                segment_randomization_seed: None,
+                minimum_segment_length: 1,
+                maximum_segment_length: None,
+                sorting_mode: PreparedSegmentSortingMode::Luminance,
+                tiebreak_mode: None,
+                sorting_direction: ImageSortingDirection::Horizontal(
+                    PixelSegmentSortDirection::Descending,
+                ),
+                premultiply_alpha: false,
+                output_only_segments: false,
+                stable_sort: false,
+                region_of_interest: None,
+            })
+            .expect("failed to send request");
 
-                if response_result.is_err() {
-                    tracing::error!("Background worker's response channel is disconnected.");
-                    break;
+        let sorted_image = loop {
+            match receive_response(&worker) {
+                WorkerResponse::ProcessingProgress { fraction } => {
+                    assert!((0.0..=1.0).contains(&fraction));
                 }
+                WorkerResponse::ProcessedImage { image, .. } => break image,
+                _ => panic!("expected a ProcessedImage response"),
             }
+        };
+        assert_eq!(
+            sorted_image.dimensions(),
+            input_image.dimensions()
+        );
+
+        worker
+            .sender()
+            .send(WorkerRequest::SaveImage {
+                image: Arc::new(sorted_image),
+                output_file_path: output_file_path.clone(),
+                format: OutputFormat::Png {
+                    compression: PngCompression::Fast,
+                },
+                icc_profile: None,
+            })
+            .expect("failed to send request");
+
+        match receive_response(&worker) {
+            WorkerResponse::SavedImage {
+                output_file_path: saved_file_path,
+            } => assert_eq!(saved_file_path, output_file_path),
+            _ => panic!("expected a SavedImage response"),
         }
+        assert!(output_file_path.is_file());
+
+        worker.stop_worker_and_join();
     }
 }